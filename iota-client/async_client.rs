@@ -0,0 +1,603 @@
+use std::sync::Arc;
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tokio::prelude::future::Loop;
+use tokio::prelude::*;
+
+use iota_validation::input_validator;
+
+use crate::batch::{self, BatchSizes};
+use crate::builder::ClientBuilder;
+use crate::core::*;
+use crate::error::{decode, ClientError};
+use crate::node_pool::NodePool;
+use crate::options::*;
+use crate::quorum::{self, Quorum};
+use crate::retry::{self, RetryPolicy};
+use crate::transport::{HttpTransport, Transport};
+use crate::BoxFuture;
+
+/// An asynchronous client using one or more IRI URIs. Every call
+/// returns a future, which can be driven by any Tokio executor.
+///
+/// Commands are routed through a `NodePool`: they are sent to the
+/// first healthy node and fail over to the next one on transport errors.
+/// With a `Quorum` configured, balance, inclusion state and spent address
+/// reads are sent to several nodes and must be answered identically.
+///
+/// ```no_run
+/// use iota_client::AsyncClient;
+/// use tokio::prelude::*;
+///
+/// let client = iota_client::AsyncClient::new("https://nodes.devnet.iota.org");
+/// let node_info = client
+///     .get_node_info()
+///     .map(|info| println!("{:?}", info))
+///     .map_err(|e| eprintln!("{}", e));
+/// tokio::run(node_info);
+/// ```
+#[derive(Clone, Debug)]
+pub struct AsyncClient {
+    /// IRI nodes every command is routed to
+    pub nodes: NodePool,
+    /// Transport every command is sent with, HTTP by default
+    pub transport: Arc<dyn Transport>,
+    /// Quorum settings for security critical reads, if any
+    pub quorum: Option<Quorum>,
+    /// Retry policy for commands failing on every node, if any
+    pub retry: Option<RetryPolicy>,
+    /// Maximum number of items sent in a single request
+    pub batch_sizes: BatchSizes,
+}
+
+impl Default for AsyncClient {
+    fn default() -> AsyncClient {
+        AsyncClient::with_pool(NodePool::default())
+    }
+}
+
+impl AsyncClient {
+    /// Create a new instance of AsyncClient
+    pub fn new(uri: &str) -> AsyncClient {
+        AsyncClient::with_nodes(&[uri])
+    }
+
+    /// Create a builder to configure timeouts, headers, authentication
+    /// and TLS before connecting, see `ClientBuilder`
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+
+    /// Create a new instance of AsyncClient routing commands
+    /// to several IRI nodes, in order of preference
+    pub fn with_nodes(uris: &[&str]) -> AsyncClient {
+        AsyncClient::with_pool(NodePool::new(uris))
+    }
+
+    /// Create a new instance of AsyncClient using an existing pool
+    pub fn with_pool(nodes: NodePool) -> AsyncClient {
+        AsyncClient {
+            nodes,
+            transport: Arc::new(HttpTransport::default()),
+            quorum: None,
+            retry: None,
+            batch_sizes: BatchSizes::default(),
+        }
+    }
+
+    /// Sends every command with `transport` instead of HTTP
+    pub fn with_transport<T: Transport + 'static>(mut self, transport: T) -> AsyncClient {
+        self.transport = Arc::new(transport);
+        self
+    }
+
+    /// Requires `get_balances`, `get_inclusion_states` and
+    /// `were_addresses_spent_from` to be answered identically by
+    /// `quorum.threshold` out of `quorum.nodes` nodes of the pool
+    pub fn with_quorum(mut self, quorum: Quorum) -> AsyncClient {
+        self.quorum = Some(quorum);
+        self
+    }
+
+    /// Retries commands failing with a retryable error, see `RetryPolicy`
+    pub fn with_retry(mut self, policy: RetryPolicy) -> AsyncClient {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Splits large inputs of `find_transactions`, `get_trytes`,
+    /// `get_balances` and `get_inclusion_states` into batches of at
+    /// most these sizes
+    pub fn with_batch_sizes(mut self, batch_sizes: BatchSizes) -> AsyncClient {
+        self.batch_sizes = batch_sizes;
+        self
+    }
+
+    /// URI of the node the next command will be sent to
+    pub fn uri(&self) -> Option<String> {
+        self.nodes.candidates().into_iter().next()
+    }
+
+    /// Queries `getNodeInfo` on every node of the pool. Nodes that are
+    /// unreachable or not synced (latest milestone index differs from
+    /// latest solid subtangle milestone index) are marked as unhealthy
+    /// for the cooldown period, the others as healthy. Resolves to the
+    /// URIs of the healthy nodes.
+    pub fn health_check(&self) -> BoxFuture<Vec<String>> {
+        let command = get_node_info::get_node_info();
+        let checks: Vec<_> = self
+            .nodes
+            .uris()
+            .into_iter()
+            .map(|uri| {
+                let nodes = self.nodes.clone();
+                send::<GetNodeInfoResponse>(&*self.transport, &uri, command.clone()).then(
+                    move |res| {
+                        let synced = res
+                            .map(|info| {
+                                info.latest_milestone_index()
+                                    == info.latest_solid_subtangle_milestone_index()
+                            })
+                            .unwrap_or(false);
+                        if synced {
+                            nodes.mark_healthy(&uri);
+                            Ok::<_, ClientError>(Some(uri))
+                        } else {
+                            nodes.mark_unhealthy(&uri);
+                            Ok(None)
+                        }
+                    },
+                )
+            })
+            .collect();
+        Box::new(future::join_all(checks).map(|uris| uris.into_iter().flatten().collect()))
+    }
+
+    /// Runs `attempt` under the retry policy if one is configured
+    /// and `command` may be retried, or once otherwise
+    fn retrying<T, F>(&self, command: &Value, attempt: F) -> BoxFuture<T>
+    where
+        T: Send + 'static,
+        F: Fn() -> BoxFuture<T> + Send + 'static,
+    {
+        let name = command["command"].as_str().unwrap_or_default();
+        match &self.retry {
+            Some(policy) if policy.applies_to(name) => {
+                retry::retry(policy.clone(), name.to_string(), attempt)
+            }
+            _ => attempt(),
+        }
+    }
+
+    /// Sends a command to the pool, failing over to the next node
+    /// whenever one cannot be reached, and retrying as configured.
+    /// Commands that may not be retried don't fail over either, as the
+    /// unreachable node may have received them.
+    fn request<T>(&self, command: Value) -> BoxFuture<T>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        let client = self.clone();
+        self.retrying(&command.clone(), move || {
+            client.send_to_pool(command.clone())
+        })
+    }
+
+    fn send_to_pool<T>(&self, command: Value) -> BoxFuture<T>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        let candidates = self.nodes.candidates();
+        if candidates.is_empty() {
+            return Box::new(future::err(ClientError::Other(format_err!(
+                "No IRI node configured"
+            ))));
+        }
+        let name = command["command"].as_str().unwrap_or_default();
+        let failover = self.retry.as_ref().map_or_else(
+            || !retry::NON_IDEMPOTENT_COMMANDS.contains(&name),
+            |policy| policy.applies_to(name),
+        );
+        let transport = self.transport.clone();
+        let nodes = self.nodes.clone();
+        Box::new(future::loop_fn(
+            candidates.into_iter(),
+            move |mut remaining| {
+                let uri = remaining.next().unwrap_or_default();
+                let nodes = nodes.clone();
+                send(&*transport, &uri, command.clone()).then(move |res| match res {
+                    Ok(resp) => {
+                        nodes.mark_healthy(&uri);
+                        Ok(Loop::Break(resp))
+                    }
+                    Err(e) if e.is_transport() => {
+                        nodes.mark_unhealthy(&uri);
+                        if failover && remaining.len() > 0 {
+                            Ok(Loop::Continue(remaining))
+                        } else {
+                            Err(e)
+                        }
+                    }
+                    Err(e) => Err(e),
+                })
+            },
+        ))
+    }
+
+    /// Sends a command to several nodes at once when a quorum is
+    /// configured, comparing their answers by `key`. Falls back to
+    /// a regular request otherwise.
+    fn quorum_request<T, K, F>(&self, command: Value, key: F) -> BoxFuture<T>
+    where
+        T: DeserializeOwned + Send + 'static,
+        K: PartialEq + std::fmt::Debug,
+        F: Fn(&T) -> K + Send + Sync + 'static,
+    {
+        let quorum = match self.quorum {
+            Some(quorum) => quorum,
+            None => return self.request(command),
+        };
+        let client = self.clone();
+        let key = Arc::new(key);
+        self.retrying(&command.clone(), move || {
+            let key = key.clone();
+            client.send_to_quorum(command.clone(), quorum, move |answer| key(answer))
+        })
+    }
+
+    fn send_to_quorum<T, K, F>(&self, command: Value, quorum: Quorum, key: F) -> BoxFuture<T>
+    where
+        T: DeserializeOwned + Send + 'static,
+        K: PartialEq + std::fmt::Debug,
+        F: Fn(&T) -> K + Send + 'static,
+    {
+        if let Err(e) = quorum.validate() {
+            return Box::new(future::err(e));
+        }
+        let name = command["command"].as_str().unwrap_or_default().to_string();
+        let answers: Vec<_> = self
+            .nodes
+            .candidates()
+            .into_iter()
+            .take(quorum.nodes)
+            .map(|uri| {
+                let nodes = self.nodes.clone();
+                send(&*self.transport, &uri, command.clone()).then(move |res| {
+                    match &res {
+                        Err(e) if e.is_transport() => nodes.mark_unhealthy(&uri),
+                        _ => nodes.mark_healthy(&uri),
+                    }
+                    Ok((uri, res))
+                })
+            })
+            .collect();
+        Box::new(
+            future::join_all(answers)
+                .and_then(move |answers| quorum::decide(&name, quorum.threshold, answers, key)),
+        )
+    }
+
+    /// Add a list of neighbors to your node. It should be noted that
+    /// this is only temporary, and the added neighbors will be removed
+    /// from your set of neighbors after you relaunch IRI.
+    pub fn add_neighbors(&self, uris: &[String]) -> BoxFuture<AddNeighborsResponse> {
+        self.request(add_neighbors::add_neighbors(uris))
+    }
+
+    /// Performs proof of work
+    ///
+    /// * `trunk_transaction` - trunk transaction to confirm
+    /// * `branch_transaction` - branch transaction to confirm
+    /// * `min_weight_magnitude` - Difficulty of PoW
+    /// * `trytes` - tryes to use for PoW
+    pub fn attach_to_tangle(
+        &self,
+        options: AttachOptions<'_, '_, '_>,
+    ) -> BoxFuture<AttachToTangleResponse> {
+        if !input_validator::is_hash(options.trunk_transaction) {
+            return Box::new(future::err(ClientError::InvalidInput(format!(
+                "Provided trunk transaction is not valid: {:?}",
+                options.trunk_transaction
+            ))));
+        }
+        if !input_validator::is_hash(options.branch_transaction) {
+            return Box::new(future::err(ClientError::InvalidInput(format!(
+                "Provided branch transaction is not valid: {:?}",
+                options.branch_transaction
+            ))));
+        }
+        if !input_validator::is_array_of_trytes(options.trytes) {
+            return Box::new(future::err(ClientError::InvalidInput(format!(
+                "Provided trytes are not valid: {:?}",
+                options.trytes
+            ))));
+        }
+
+        self.request(attach_to_tangle::attach_to_tangle(options))
+    }
+
+    /// Broadcast a list of transactions to all neighbors.
+    /// The input trytes for this call are provided by attachToTangle.
+    pub fn broadcast_transactions(
+        &self,
+        trytes: &[String],
+    ) -> BoxFuture<BroadcastTransactionsResponse> {
+        if !input_validator::is_array_of_attached_trytes(trytes) {
+            return Box::new(future::err(ClientError::InvalidInput(format!(
+                "Provided trytes are not valid: {:?}",
+                trytes
+            ))));
+        }
+
+        self.request(broadcast_transactions::broadcast_transactions(trytes))
+    }
+
+    /// Checks whether the given tail transactions are consistent,
+    /// meaning they can be approved together. If they are not, the
+    /// `info` field of the response tells why.
+    pub fn check_consistency(&self, hashes: &[String]) -> BoxFuture<CheckConsistencyResponse> {
+        for hash in hashes {
+            if !input_validator::is_hash(hash) {
+                return Box::new(future::err(ClientError::InvalidInput(format!(
+                    "Provided hash is not valid: {:?}",
+                    hash
+                ))));
+            }
+        }
+        self.request(check_consistency::check_consistency(hashes))
+    }
+
+    /// Finds transactions the match any of the provided parameters.
+    /// Fields with more values than `batch_sizes.max_find_transactions`
+    /// are split into concurrent requests.
+    pub fn find_transactions(
+        &self,
+        options: FindTransactionsOptions,
+    ) -> BoxFuture<FindTransactionsResponse> {
+        let batches: Vec<_> =
+            batch::split_find_transactions(&options, self.batch_sizes.max_find_transactions)
+                .into_iter()
+                .map(|options| self.request(find_transactions::find_transactions(options)))
+                .collect();
+        Box::new(future::join_all(batches).map(FindTransactionsResponse::merge))
+    }
+
+    /// Returns the balance based on the latest confirmed milestone.
+    /// In addition to the balances, it also returns the referencing tips (or milestone),
+    /// as well as the index with which the confirmed balance was
+    /// determined. The balances is returned as a list in the same
+    /// order as the addresses were provided as input.
+    ///
+    /// More addresses than `batch_sizes.max_requests_list` are split into
+    /// batches. Unless tips are given, the first batch is sent alone and
+    /// the others concurrently, referencing the milestone of the first
+    /// one, so that every balance is computed against the same milestone.
+    pub fn get_balances(&self, options: GetBalancesOptions) -> BoxFuture<GetBalancesResponse> {
+        if !input_validator::is_array_of_hashes(&options.addresses) {
+            return Box::new(future::err(ClientError::InvalidInput(format!(
+                "Provided addresses are not valid: {:?}",
+                options.addresses
+            ))));
+        }
+        let mut batches = batch::split(&options.addresses, self.batch_sizes.max_requests_list)
+            .into_iter()
+            .map(|addresses| GetBalancesOptions {
+                addresses,
+                ..options.clone()
+            });
+        let first = match batches.next() {
+            Some(first) => first,
+            None => return Box::new(future::ok(GetBalancesResponse::default())),
+        };
+        let rest: Vec<GetBalancesOptions> = batches.collect();
+        if rest.is_empty() {
+            return self.get_balances_batch(first);
+        }
+        let client = self.clone();
+        Box::new(self.get_balances_batch(first).and_then(move |first| {
+            let references = first.references().clone().unwrap_or_default();
+            let rest: Vec<_> = rest
+                .into_iter()
+                .map(|mut options| {
+                    if options.tips.is_empty() {
+                        options.tips = references.clone();
+                    }
+                    client.get_balances_batch(options)
+                })
+                .collect();
+            future::join_all(rest).map(move |mut responses| {
+                responses.insert(0, first);
+                GetBalancesResponse::merge(responses)
+            })
+        }))
+    }
+
+    fn get_balances_batch(&self, options: GetBalancesOptions) -> BoxFuture<GetBalancesResponse> {
+        self.quorum_request(
+            get_balances::get_balances(options),
+            |resp: &GetBalancesResponse| (resp.balances().clone(), resp.milestone_index()),
+        )
+    }
+
+    /// Get the inclusion states of a set of transactions. This is
+    /// for determining if a transaction was accepted and confirmed
+    /// by the network or not. You can search for multiple tips (and
+    /// thus, milestones) to get past inclusion states of transactions.
+    ///
+    /// This API call simply returns a list of boolean values in the
+    /// same order as the transaction list you submitted, thus you get
+    /// a true/false whether a transaction is confirmed or not.
+    ///
+    /// More transactions than `batch_sizes.max_requests_list` are split
+    /// into concurrent requests.
+    pub fn get_inclusion_states(
+        &self,
+        options: GetInclusionStatesOptions,
+    ) -> BoxFuture<GetInclusionStatesResponse> {
+        if !input_validator::is_array_of_hashes(&options.transactions) {
+            return Box::new(future::err(ClientError::InvalidInput(format!(
+                "Provided transactions are not valid: {:?}",
+                options.transactions
+            ))));
+        }
+        if !options.tips.is_empty() && !input_validator::is_array_of_hashes(&options.tips) {
+            return Box::new(future::err(ClientError::InvalidInput(format!(
+                "Provided tips are not valid: {:?}",
+                options.tips
+            ))));
+        }
+
+        let batches: Vec<_> =
+            batch::split(&options.transactions, self.batch_sizes.max_requests_list)
+                .into_iter()
+                .map(|transactions| {
+                    self.quorum_request(
+                        get_inclusion_states::get_inclusion_states(GetInclusionStatesOptions {
+                            transactions,
+                            tips: options.tips.clone(),
+                        }),
+                        |resp: &GetInclusionStatesResponse| resp.states().clone(),
+                    )
+                })
+                .collect();
+        Box::new(future::join_all(batches).map(GetInclusionStatesResponse::merge))
+    }
+
+    /// Returns the hashes of transactions the node knows are
+    /// referenced but has not received yet
+    pub fn get_missing_transactions(&self) -> BoxFuture<GetMissingTransactionsResponse> {
+        self.request(get_missing_transactions::get_missing_transactions())
+    }
+
+    /// Returns the set of neighbors you are connected with, as
+    /// well as their activity count. The activity counter is reset
+    /// after restarting IRI.
+    pub fn get_neighbors(&self) -> BoxFuture<GetNeighborsResponse> {
+        self.request(get_neighbors::get_neighbors())
+    }
+
+    /// Gets the API limits and settings of the specified node, such as
+    /// the maximum request sizes and the milestone start index
+    pub fn get_node_api_configuration(&self) -> BoxFuture<GetNodeAPIConfigurationResponse> {
+        self.request(get_node_api_configuration::get_node_api_configuration())
+    }
+
+    /// Gets information about the specified node
+    pub fn get_node_info(&self) -> BoxFuture<GetNodeInfoResponse> {
+        self.request(get_node_info::get_node_info())
+    }
+
+    /// Returns the list of tips
+    pub fn get_tips(&self) -> BoxFuture<GetTipsResponse> {
+        self.request(get_tips::get_tips())
+    }
+
+    /// Tip selection which returns `trunkTransaction` and
+    /// `branchTransaction`. The input value depth determines
+    /// how many milestones to go back to for finding the
+    /// transactions to approve. The higher your depth value,
+    /// the more work you have to do as you are confirming more
+    /// transactions. If the depth is too large (usually above 15,
+    /// it depends on the node's configuration) an error will be
+    /// returned. The reference is an optional hash of a transaction
+    /// you want to approve. If it can't be found at the specified
+    /// depth then an error will be returned.
+    pub fn get_transactions_to_approve(
+        &self,
+        options: GetTransactionsToApproveOptions<'_>,
+    ) -> BoxFuture<GetTransactionsToApprove> {
+        if let Some(reference) = options.reference {
+            if !input_validator::is_hash(reference) {
+                return Box::new(future::err(ClientError::InvalidInput(format!(
+                    "Provided reference is not valid: {:?}",
+                    reference
+                ))));
+            }
+        }
+        self.request(get_transactions_to_approve::get_transactions_to_approve(
+            options,
+        ))
+    }
+
+    /// Returns the raw transaction data (trytes) of a specific
+    /// transaction. These trytes can then be easily converted
+    /// into the actual transaction object. See utility functions
+    /// for more details.
+    ///
+    /// More hashes than `batch_sizes.max_get_trytes` are split into
+    /// concurrent requests.
+    pub fn get_trytes(&self, hashes: &[String]) -> BoxFuture<GetTrytesResponse> {
+        if !input_validator::is_array_of_hashes(hashes) {
+            return Box::new(future::err(ClientError::InvalidInput(format!(
+                "Provided hashes are not valid: {:?}",
+                hashes
+            ))));
+        }
+        let batches: Vec<_> = batch::split(hashes, self.batch_sizes.max_get_trytes)
+            .into_iter()
+            .map(|hashes| self.request(get_trytes::get_trytes(&hashes)))
+            .collect();
+        Box::new(future::join_all(batches).map(GetTrytesResponse::merge))
+    }
+
+    /// Interupts an existing PoW request if you made one
+    pub fn interrupt_attaching_to_tangle(&self) -> BoxFuture<Value> {
+        self.request(interrupt_attaching_to_tangle::interrupt_attaching_to_tangle())
+    }
+
+    /// Removes a list of neighbors to your node.
+    /// This is only temporary, and if you have your neighbors
+    /// added via the command line, they will be retained after
+    /// you restart your node.
+    pub fn remove_neighbors(&self, uris: &[String]) -> BoxFuture<RemoveNeighborsResponse> {
+        self.request(remove_neighbors::remove_neighbors(uris))
+    }
+
+    /// Store transactions into the local storage.
+    /// The trytes to be used for this call are
+    /// returned by attachToTangle.
+    pub fn store_transactions(&self, trytes: &[String]) -> BoxFuture<StoreTransactionsResponse> {
+        if !input_validator::is_array_of_attached_trytes(trytes) {
+            return Box::new(future::err(ClientError::InvalidInput(format!(
+                "Provided trytes are not valid: {:?}",
+                trytes
+            ))));
+        }
+        self.request(store_transactions::store_transactions(trytes))
+    }
+
+    /// Check if a list of addresses was ever spent from.
+    pub fn were_addresses_spent_from(
+        &self,
+        addresses: &[String],
+    ) -> BoxFuture<WereAddressesSpentFromResponse> {
+        let addresses: Vec<String> = addresses
+            .iter()
+            .filter(|address| input_validator::is_address(address))
+            .map(|address| iota_signing::checksum::remove_checksum(address))
+            .collect();
+        if addresses.is_empty() {
+            return Box::new(future::err(ClientError::InvalidInput(
+                "No valid addresses provided.".into(),
+            )));
+        }
+        self.quorum_request(
+            were_addresses_spent_from::were_addresses_spent_from(&addresses),
+            |resp: &WereAddressesSpentFromResponse| resp.clone().states(),
+        )
+    }
+}
+
+/// Sends a command to a single node and decodes its answer,
+/// turning any IRI `error` or `exception` into a `ClientError`
+fn send<T>(
+    transport: &dyn Transport,
+    uri: &str,
+    command: Value,
+) -> impl Future<Item = T, Error = ClientError>
+where
+    T: DeserializeOwned,
+{
+    transport.request(uri, command).and_then(decode)
+}
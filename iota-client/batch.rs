@@ -0,0 +1,98 @@
+use crate::options::FindTransactionsOptions;
+
+/// Maximum number of items sent in a single request. Larger inputs are
+/// split into batches that are sent concurrently, and their results are
+/// merged in the original order. The defaults match IRI's defaults;
+/// lower them to match the `maxFindTransactions`, `maxGetTrytes` and
+/// `maxRequestsList` settings of the nodes you use.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BatchSizes {
+    /// Values per field of a `findTransactions` request
+    pub max_find_transactions: usize,
+    /// Hashes per `getTrytes` request
+    pub max_get_trytes: usize,
+    /// Addresses per `getBalances` request and transactions
+    /// per `getInclusionStates` request
+    pub max_requests_list: usize,
+}
+
+impl Default for BatchSizes {
+    fn default() -> BatchSizes {
+        BatchSizes {
+            max_find_transactions: 1_000,
+            max_get_trytes: 10_000,
+            max_requests_list: 1_000,
+        }
+    }
+}
+
+/// Splits `items` into batches of at most `size` items
+pub(crate) fn split<T: Clone>(items: &[T], size: usize) -> Vec<Vec<T>> {
+    items.chunks(size.max(1)).map(<[T]>::to_vec).collect()
+}
+
+/// Splits every field of `options` into batches of at most `size` values.
+/// IRI intersects the fields of a request, so every combination of
+/// batches is returned, and the union of their results is the result
+/// of the original request.
+pub(crate) fn split_find_transactions(
+    options: &FindTransactionsOptions,
+    size: usize,
+) -> Vec<FindTransactionsOptions> {
+    let fields = |values: &[String]| {
+        if values.is_empty() {
+            vec![vec![]]
+        } else {
+            split(values, size)
+        }
+    };
+    let mut batches = Vec::new();
+    for bundles in fields(&options.bundles) {
+        for addresses in fields(&options.addresses) {
+            for tags in fields(&options.tags) {
+                for approvees in fields(&options.approvees) {
+                    batches.push(FindTransactionsOptions {
+                        bundles: bundles.clone(),
+                        addresses: addresses.clone(),
+                        tags: tags.clone(),
+                        approvees,
+                    });
+                }
+            }
+        }
+    }
+    batches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(prefix: &str, count: usize) -> Vec<String> {
+        (0..count).map(|i| format!("{}{}", prefix, i)).collect()
+    }
+
+    #[test]
+    fn test_split_keeps_order() {
+        let batches = split(&values("A", 5), 2);
+        assert_eq!(batches.len(), 3);
+        assert_eq!(batches.concat(), values("A", 5));
+    }
+
+    #[test]
+    fn test_split_find_transactions_combines_fields() {
+        let options = FindTransactionsOptions {
+            addresses: values("A", 3),
+            tags: values("T", 2),
+            ..FindTransactionsOptions::default()
+        };
+        let batches = split_find_transactions(&options, 2);
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].addresses, values("A", 2));
+        assert_eq!(batches[1].addresses, vec!["A2"]);
+        assert!(batches.iter().all(|batch| batch.tags == values("T", 2)));
+
+        let batches = split_find_transactions(&FindTransactionsOptions::default(), 2);
+        assert_eq!(batches.len(), 1);
+    }
+}
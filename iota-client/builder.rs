@@ -0,0 +1,255 @@
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, USER_AGENT};
+use reqwest::r#async::Client as HttpClient;
+use tokio::runtime::Runtime;
+
+pub use reqwest::{Certificate, Proxy};
+
+use crate::async_client::AsyncClient;
+use crate::batch::BatchSizes;
+use crate::client::Client;
+use crate::error::ClientError;
+use crate::node_pool::NodePool;
+use crate::retry::RetryPolicy;
+use crate::transport::{HttpTransport, DEFAULT_API_VERSION};
+use crate::Result;
+
+/// Configures the HTTP connection to IRI nodes before creating a
+/// `Client` or an `AsyncClient`. Every setting applies to every
+/// command sent by the client.
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// let mut client = iota_client::ClientBuilder::new()
+///     .node("https://nodes.devnet.iota.org")
+///     .timeout(Duration::from_secs(30))
+///     .connect_timeout(Duration::from_secs(5))
+///     .bearer_auth("secret")
+///     .header("X-Request-Source", "wallet")
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Default)]
+pub struct ClientBuilder {
+    nodes: Vec<String>,
+    cooldown: Option<Duration>,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    headers: HeaderMap,
+    root_certificates: Vec<Certificate>,
+    proxies: Vec<Proxy>,
+    api_version: Option<String>,
+    retry: Option<RetryPolicy>,
+    batch_sizes: BatchSizes,
+    error: Option<ClientError>,
+}
+
+impl ClientBuilder {
+    /// Create a new builder without any node
+    pub fn new() -> ClientBuilder {
+        ClientBuilder::default()
+    }
+
+    /// Adds an IRI node. Nodes are tried in the order they were added.
+    pub fn node(mut self, uri: &str) -> ClientBuilder {
+        self.nodes.push(uri.to_string());
+        self
+    }
+
+    /// Adds several IRI nodes, in order of preference
+    pub fn nodes(mut self, uris: &[&str]) -> ClientBuilder {
+        self.nodes.extend(uris.iter().map(|uri| uri.to_string()));
+        self
+    }
+
+    /// Sets how long a failing node is skipped before it is tried again
+    pub fn cooldown(mut self, cooldown: Duration) -> ClientBuilder {
+        self.cooldown = Some(cooldown);
+        self
+    }
+
+    /// Sets a timeout for every request, from connecting until the
+    /// response body has been read
+    pub fn timeout(mut self, timeout: Duration) -> ClientBuilder {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets a timeout for connecting to a node
+    pub fn connect_timeout(mut self, timeout: Duration) -> ClientBuilder {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Adds a header sent with every request, replacing any previous value
+    pub fn header(mut self, name: &str, value: &str) -> ClientBuilder {
+        match (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            (Ok(header), Ok(value)) => {
+                self.headers.insert(header, value);
+            }
+            (Err(e), _) => self.fail(format!("Invalid header name {:?}: {}", name, e)),
+            (_, Err(e)) => self.fail(format!("Invalid value for header {}: {}", name, e)),
+        }
+        self
+    }
+
+    /// Authenticates every request with HTTP basic auth
+    pub fn basic_auth(self, username: &str, password: Option<&str>) -> ClientBuilder {
+        let credentials = format!("{}:{}", username, password.unwrap_or_default());
+        self.authorization(format!("Basic {}", base64::encode(&credentials)))
+    }
+
+    /// Authenticates every request with a bearer token
+    pub fn bearer_auth(self, token: &str) -> ClientBuilder {
+        self.authorization(format!("Bearer {}", token))
+    }
+
+    fn authorization(mut self, value: String) -> ClientBuilder {
+        match HeaderValue::from_str(&value) {
+            Ok(mut value) => {
+                value.set_sensitive(true);
+                self.headers.insert(AUTHORIZATION, value);
+            }
+            Err(_) => self.fail("Invalid credentials for the Authorization header".into()),
+        }
+        self
+    }
+
+    /// Trusts an additional root certificate when connecting over TLS
+    pub fn add_root_certificate(mut self, certificate: Certificate) -> ClientBuilder {
+        self.root_certificates.push(certificate);
+        self
+    }
+
+    /// Sends requests through a proxy
+    pub fn proxy(mut self, proxy: Proxy) -> ClientBuilder {
+        self.proxies.push(proxy);
+        self
+    }
+
+    /// Sets the User-Agent header
+    pub fn user_agent(self, user_agent: &str) -> ClientBuilder {
+        self.header(USER_AGENT.as_str(), user_agent)
+    }
+
+    /// Sets the `X-IOTA-API-Version` header, `1` by default
+    pub fn api_version(mut self, version: &str) -> ClientBuilder {
+        self.api_version = Some(version.to_string());
+        self
+    }
+
+    /// Retries commands failing with a retryable error, see `RetryPolicy`
+    pub fn retry(mut self, policy: RetryPolicy) -> ClientBuilder {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Sets the maximum number of items sent in a single request, see `BatchSizes`
+    pub fn batch_sizes(mut self, batch_sizes: BatchSizes) -> ClientBuilder {
+        self.batch_sizes = batch_sizes;
+        self
+    }
+
+    /// Creates a blocking `Client` with these settings
+    pub fn build(self) -> Result<Client> {
+        Ok(Client {
+            runtime: Runtime::new().map_err(|e| ClientError::Other(e.into()))?,
+            inner: self.build_async()?,
+        })
+    }
+
+    /// Creates an `AsyncClient` with these settings
+    pub fn build_async(self) -> Result<AsyncClient> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        let api_version = self
+            .api_version
+            .unwrap_or_else(|| DEFAULT_API_VERSION.to_string());
+        ensure_input!(
+            HeaderValue::from_str(&api_version).is_ok(),
+            "Invalid API version: {:?}",
+            api_version
+        );
+
+        let mut http = HttpClient::builder().default_headers(self.headers);
+        if let Some(timeout) = self.timeout {
+            http = http.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            http = http.connect_timeout(timeout);
+        }
+        for certificate in self.root_certificates {
+            http = http.add_root_certificate(certificate);
+        }
+        for proxy in self.proxies {
+            http = http.proxy(proxy);
+        }
+        let transport = HttpTransport::new(http.build()?).with_api_version(&api_version);
+
+        let uris: Vec<&str> = self.nodes.iter().map(String::as_str).collect();
+        let mut nodes = NodePool::new(&uris);
+        if let Some(cooldown) = self.cooldown {
+            nodes = nodes.with_cooldown(cooldown);
+        }
+        let mut client = AsyncClient::with_pool(nodes).with_transport(transport);
+        client.retry = self.retry;
+        client.batch_sizes = self.batch_sizes;
+        Ok(client)
+    }
+
+    fn fail(&mut self, message: String) {
+        if self.error.is_none() {
+            self.error = Some(ClientError::InvalidInput(message));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_header_fails_build() {
+        let res = ClientBuilder::new()
+            .node("http://localhost:14265")
+            .header("X-Bad\n", "value")
+            .build_async();
+        assert!(res
+            .unwrap_err()
+            .to_string()
+            .starts_with("Invalid header name"));
+
+        let res = ClientBuilder::new()
+            .header("X-Ok", "bad\nvalue")
+            .build_async();
+        assert!(res
+            .unwrap_err()
+            .to_string()
+            .starts_with("Invalid value for header"));
+    }
+
+    #[test]
+    fn test_invalid_api_version_fails_build() {
+        let res = ClientBuilder::new().api_version("1\n").build_async();
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_nodes_in_order() {
+        let client = ClientBuilder::new()
+            .node("http://a")
+            .nodes(&["http://b", "http://c"])
+            .build_async()
+            .unwrap();
+        assert_eq!(
+            client.nodes.uris(),
+            vec!["http://a", "http://b", "http://c"]
+        );
+    }
+}
@@ -1,70 +1,121 @@
-use reqwest::r#async::Response;
 use serde_json::Value;
-use tokio::prelude::*;
 use tokio::runtime::Runtime;
 
-use iota_validation::input_validator;
-
+use crate::async_client::AsyncClient;
+use crate::batch::BatchSizes;
+use crate::builder::ClientBuilder;
 use crate::core::*;
+use crate::node_pool::NodePool;
 use crate::options::*;
+use crate::quorum::Quorum;
+use crate::retry::RetryPolicy;
+use crate::transport::Transport;
 use crate::Result;
 
-// TODO once async/await is stable, this file needs to be updated
-
-/// An instance of the client using IRI URI
+/// An instance of the client using one or more IRI URIs. This is a
+/// blocking wrapper around `AsyncClient`, driving every call to
+/// completion on its own Tokio runtime.
 #[derive(Debug)]
-pub struct Client<'a> {
-    /// URI of IRI connection
-    pub uri: &'a str,
+pub struct Client {
     /// Handle to the Tokio runtime
     pub runtime: Runtime,
-    /// A reqwest Client to make Requests with
-    pub client: reqwest::r#async::Client,
+    /// The asynchronous client every call is delegated to
+    pub inner: AsyncClient,
 }
 
-impl<'a> Default for Client<'a> {
-    fn default() -> Client<'a> {
+impl Default for Client {
+    fn default() -> Client {
         Client {
-            uri: "",
             runtime: Runtime::new().unwrap(),
-            client: reqwest::r#async::Client::new(),
+            inner: AsyncClient::default(),
         }
     }
 }
 
-impl<'a> Client<'a> {
+impl Client {
     /// Create a new instance of Client
-    pub fn new(uri: &str) -> Client<'_> {
+    pub fn new(uri: &str) -> Client {
+        Client {
+            runtime: Runtime::new().unwrap(),
+            inner: AsyncClient::new(uri),
+        }
+    }
+
+    /// Create a builder to configure timeouts, headers, authentication
+    /// and TLS before connecting, see `ClientBuilder`
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+
+    /// Create a new instance of Client routing commands
+    /// to several IRI nodes, in order of preference
+    pub fn with_nodes(uris: &[&str]) -> Client {
+        Client::with_pool(NodePool::new(uris))
+    }
+
+    /// Create a new instance of Client using an existing pool
+    pub fn with_pool(nodes: NodePool) -> Client {
         Client {
-            uri: uri,
             runtime: Runtime::new().unwrap(),
-            client: reqwest::r#async::Client::new(),
+            inner: AsyncClient::with_pool(nodes),
         }
     }
 
+    /// Sends every command with `transport` instead of HTTP
+    pub fn with_transport<T: Transport + 'static>(mut self, transport: T) -> Client {
+        self.inner = self.inner.with_transport(transport);
+        self
+    }
+
+    /// Requires `get_balances`, `get_inclusion_states` and
+    /// `were_addresses_spent_from` to be answered identically by
+    /// `quorum.threshold` out of `quorum.nodes` nodes of the pool
+    pub fn with_quorum(mut self, quorum: Quorum) -> Client {
+        self.inner = self.inner.with_quorum(quorum);
+        self
+    }
+
+    /// Retries commands failing with a retryable error, see `RetryPolicy`
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Client {
+        self.inner = self.inner.with_retry(policy);
+        self
+    }
+
+    /// Splits large inputs of `find_transactions`, `get_trytes`,
+    /// `get_balances` and `get_inclusion_states` into batches of at
+    /// most these sizes
+    pub fn with_batch_sizes(mut self, batch_sizes: BatchSizes) -> Client {
+        self.inner = self.inner.with_batch_sizes(batch_sizes);
+        self
+    }
+
+    /// URI of the node the next command will be sent to
+    pub fn uri(&self) -> Option<String> {
+        self.inner.uri()
+    }
+
+    /// Queries `getNodeInfo` on every node of the pool, marking the
+    /// ones that are unreachable or not synced as unhealthy. Returns
+    /// the URIs of the healthy nodes.
+    pub fn health_check(&mut self) -> Result<Vec<String>> {
+        self.runtime.block_on(self.inner.health_check())
+    }
+
     /// Add a list of neighbors to your node. It should be noted that
     /// this is only temporary, and the added neighbors will be removed
     /// from your set of neighbors after you relaunch IRI.
-    /// ```
+    /// ```no_run
     /// use iota_client;
     /// let mut client = iota_client::Client::new("https://nodes.devnet.iota.org");
     /// let resp = client.add_neighbors(&vec!["".into()]).unwrap();
     /// println!("{:?}", resp);
     /// ```
     pub fn add_neighbors(&mut self, uris: &[String]) -> Result<AddNeighborsResponse> {
-        let parsed_resp: AddNeighborsResponse = self
-            .runtime
-            .block_on(
-                add_neighbors::add_neighbors(&self.client, self.uri, uris)
-                    .and_then(|mut resp| resp.json()),
-            )
-            .unwrap();
-        Ok(parsed_resp)
+        self.runtime.block_on(self.inner.add_neighbors(uris))
     }
 
     /// Performs proof of work
     ///
-    /// * `uri` - the uri used to make the request
     /// * `trunk_transaction` - trunk transaction to confirm
     /// * `branch_transaction` - branch transaction to confirm
     /// * `min_weight_magnitude` - Difficulty of PoW
@@ -73,38 +124,7 @@ impl<'a> Client<'a> {
         &mut self,
         options: AttachOptions<'_, '_, '_>,
     ) -> Result<AttachToTangleResponse> {
-        ensure!(
-            input_validator::is_hash(&options.trunk_transaction),
-            "Provided trunk transaction is not valid: {:?}",
-            options.trunk_transaction
-        );
-        ensure!(
-            input_validator::is_hash(&options.branch_transaction),
-            "Provided branch transaction is not valid: {:?}",
-            options.branch_transaction
-        );
-        ensure!(
-            input_validator::is_array_of_trytes(&options.trytes),
-            "Provided trytes are not valid: {:?}",
-            options.trytes
-        );
-
-        let attach_resp: AttachToTangleResponse = self
-            .runtime
-            .block_on(
-                attach_to_tangle::attach_to_tangle(&self.client, self.uri, options)
-                    .and_then(|mut resp| resp.json()),
-            )
-            .unwrap();
-
-        if let Some(error) = attach_resp.error() {
-            return Err(format_err!("{}", error));
-        }
-        if let Some(exception) = attach_resp.exception() {
-            return Err(format_err!("{}", exception));
-        }
-
-        Ok(attach_resp)
+        self.runtime.block_on(self.inner.attach_to_tangle(options))
     }
 
     /// Broadcast a list of transactions to all neighbors.
@@ -113,48 +133,15 @@ impl<'a> Client<'a> {
         &mut self,
         trytes: &[String],
     ) -> Result<BroadcastTransactionsResponse> {
-        ensure!(
-            input_validator::is_array_of_attached_trytes(&trytes),
-            "Provided trytes are not valid: {:?}",
-            trytes
-        );
-
-        let parsed_response: BroadcastTransactionsResponse = self
-            .runtime
-            .block_on(
-                broadcast_transactions::broadcast_transactions(&self.client, self.uri, trytes)
-                    .and_then(|mut resp| resp.json()),
-            )
-            .unwrap();
-        // let parsed_response: BroadcastTransactionsResponse = self.runtime.block_on(resp.json()).unwrap();
-
-        if let Some(error) = parsed_response.error() {
-            return Err(format_err!("{}", error));
-        }
-        if let Some(exception) = parsed_response.exception() {
-            return Err(format_err!("{}", exception));
-        }
-
-        Ok(parsed_response)
+        self.runtime
+            .block_on(self.inner.broadcast_transactions(trytes))
     }
 
-    /// Checks for consistency of given hashes, not part of the public api
-    pub fn check_consistency(&mut self, hashes: &[String]) -> Result<Value> {
-        for hash in hashes {
-            ensure!(
-                input_validator::is_hash(hash),
-                "Provided hash is not valid: {:?}",
-                hash
-            );
-        }
-        let parsed: Value = self
-            .runtime
-            .block_on(
-                check_consistency::check_consistency(&self.client, self.uri, hashes)
-                    .and_then(|mut resp| resp.json()),
-            )
-            .unwrap();
-        Ok(parsed)
+    /// Checks whether the given tail transactions are consistent,
+    /// meaning they can be approved together. If they are not, the
+    /// `info` field of the response tells why.
+    pub fn check_consistency(&mut self, hashes: &[String]) -> Result<CheckConsistencyResponse> {
+        self.runtime.block_on(self.inner.check_consistency(hashes))
     }
 
     /// Finds transactions the match any of the provided parameters
@@ -162,18 +149,7 @@ impl<'a> Client<'a> {
         &mut self,
         options: FindTransactionsOptions,
     ) -> Result<FindTransactionsResponse> {
-        let parsed_resp: FindTransactionsResponse = self
-            .runtime
-            .block_on(
-                find_transactions::find_transactions(&self.client, self.uri, options)
-                    .and_then(|mut resp| resp.json()),
-            )
-            .unwrap();
-        if let Some(error) = parsed_resp.error() {
-            return Err(format_err!("{}", error));
-        }
-
-        Ok(parsed_resp)
+        self.runtime.block_on(self.inner.find_transactions(options))
     }
 
     /// Returns the balance based on the latest confirmed milestone.
@@ -182,19 +158,7 @@ impl<'a> Client<'a> {
     /// determined. The balances is returned as a list in the same
     /// order as the addresses were provided as input.
     pub fn get_balances(&mut self, options: GetBalancesOptions) -> Result<GetBalancesResponse> {
-        ensure!(
-            input_validator::is_array_of_hashes(&options.addresses),
-            "Provided addresses are not valid: {:?}",
-            options.addresses
-        );
-        let parsed_resp: GetBalancesResponse = self
-            .runtime
-            .block_on(
-                get_balances::get_balances(&self.client, self.uri, options)
-                    .and_then(|mut resp| resp.json()),
-            )
-            .unwrap();
-        Ok(parsed_resp)
+        self.runtime.block_on(self.inner.get_balances(options))
     }
 
     /// Get the inclusion states of a set of transactions. This is
@@ -209,73 +173,38 @@ impl<'a> Client<'a> {
         &mut self,
         options: GetInclusionStatesOptions,
     ) -> Result<GetInclusionStatesResponse> {
-        ensure!(
-            input_validator::is_array_of_hashes(&options.transactions),
-            "Provided transactions are not valid: {:?}",
-            options.transactions
-        );
-        if !options.tips.is_empty() {
-            ensure!(
-                input_validator::is_array_of_hashes(&options.tips),
-                "Provided tips are not valid: {:?}",
-                options.tips
-            );
-        }
-
-        let parsed_resp: GetInclusionStatesResponse = self
-            .runtime
-            .block_on(
-                get_inclusion_states::get_inclusion_states(&self.client, self.uri, options)
-                    .and_then(|mut resp| resp.json()),
-            )
-            .unwrap();
-
-        if let Some(error) = parsed_resp.error() {
-            return Err(format_err!("{}", error));
-        }
+        self.runtime
+            .block_on(self.inner.get_inclusion_states(options))
+    }
 
-        Ok(parsed_resp)
+    /// Returns the hashes of transactions the node knows are
+    /// referenced but has not received yet
+    pub fn get_missing_transactions(&mut self) -> Result<GetMissingTransactionsResponse> {
+        self.runtime.block_on(self.inner.get_missing_transactions())
     }
 
     /// Returns the set of neighbors you are connected with, as
     /// well as their activity count. The activity counter is reset
     /// after restarting IRI.
     pub fn get_neighbors(&mut self) -> Result<GetNeighborsResponse> {
-        let parsed_resp: GetNeighborsResponse = self
-            .runtime
-            .block_on(
-                get_neighbors::get_neighbors(&self.client, self.uri)
-                    .and_then(|mut resp| resp.json()),
-            )
-            .unwrap();
-
-        if let Some(error) = parsed_resp.error() {
-            return Err(format_err!("{}", error));
-        }
+        self.runtime.block_on(self.inner.get_neighbors())
+    }
 
-        Ok(parsed_resp)
+    /// Gets the API limits and settings of the specified node, such as
+    /// the maximum request sizes and the milestone start index
+    pub fn get_node_api_configuration(&mut self) -> Result<GetNodeAPIConfigurationResponse> {
+        self.runtime
+            .block_on(self.inner.get_node_api_configuration())
     }
 
     /// Gets information about the specified node
     pub fn get_node_info(&mut self) -> Result<GetNodeInfoResponse> {
-        let parsed_resp: GetNodeInfoResponse = self
-            .runtime
-            .block_on(
-                get_node_info::get_node_info(&self.client, self.uri)
-                    .and_then(|mut resp| resp.json()),
-            )
-            .unwrap();
-
-        Ok(parsed_resp)
+        self.runtime.block_on(self.inner.get_node_info())
     }
 
     /// Returns the list of tips
     pub fn get_tips(&mut self) -> Result<GetTipsResponse> {
-        let parsed_resp: GetTipsResponse = self
-            .runtime
-            .block_on(get_tips::get_tips(&self.client, self.uri).and_then(|mut resp| resp.json()))
-            .unwrap();
-        Ok(parsed_resp)
+        self.runtime.block_on(self.inner.get_tips())
     }
 
     /// Tip selection which returns `trunkTransaction` and
@@ -292,26 +221,8 @@ impl<'a> Client<'a> {
         &mut self,
         options: GetTransactionsToApproveOptions<'_>,
     ) -> Result<GetTransactionsToApprove> {
-        let parsed_resp: GetTransactionsToApprove = self
-            .runtime
-            .block_on(
-                get_transactions_to_approve::get_transactions_to_approve(
-                    &self.client,
-                    self.uri,
-                    options,
-                )
-                .and_then(|mut resp| resp.json()),
-            )
-            .unwrap();
-
-        if let Some(error) = parsed_resp.error() {
-            return Err(format_err!("{}", error));
-        }
-        if let Some(exception) = parsed_resp.exception() {
-            return Err(format_err!("{}", exception));
-        }
-
-        Ok(parsed_resp)
+        self.runtime
+            .block_on(self.inner.get_transactions_to_approve(options))
     }
 
     /// Returns the raw transaction data (trytes) of a specific
@@ -319,34 +230,13 @@ impl<'a> Client<'a> {
     /// into the actual transaction object. See utility functions
     /// for more details.
     pub fn get_trytes(&mut self, hashes: &[String]) -> Result<GetTrytesResponse> {
-        ensure!(
-            input_validator::is_array_of_hashes(&hashes),
-            "Provided hashes are not valid: {:?}",
-            hashes
-        );
-
-        let parsed_resp: GetTrytesResponse = self
-            .runtime
-            .block_on(
-                get_trytes::get_trytes(&self.client, self.uri, hashes)
-                    .and_then(|mut resp| resp.json()),
-            )
-            .unwrap();
-        Ok(parsed_resp)
+        self.runtime.block_on(self.inner.get_trytes(hashes))
     }
 
     /// Interupts an existing PoW request if you made one
-    pub fn interrupt_attaching_to_tangle(&mut self) -> Result<Response> {
-        let resp = self
-            .runtime
-            .block_on(
-                interrupt_attaching_to_tangle::interrupt_attaching_to_tangle(
-                    &self.client,
-                    self.uri,
-                ),
-            )
-            .unwrap();
-        Ok(resp)
+    pub fn interrupt_attaching_to_tangle(&mut self) -> Result<Value> {
+        self.runtime
+            .block_on(self.inner.interrupt_attaching_to_tangle())
     }
 
     /// Removes a list of neighbors to your node.
@@ -354,34 +244,14 @@ impl<'a> Client<'a> {
     /// added via the command line, they will be retained after
     /// you restart your node.
     pub fn remove_neighbors(&mut self, uris: &[String]) -> Result<RemoveNeighborsResponse> {
-        let parsed_resp: RemoveNeighborsResponse = self
-            .runtime
-            .block_on(
-                remove_neighbors::remove_neighbors(&self.client, self.uri, uris)
-                    .and_then(|mut resp| resp.json()),
-            )
-            .unwrap();
-        Ok(parsed_resp)
+        self.runtime.block_on(self.inner.remove_neighbors(uris))
     }
 
     /// Store transactions into the local storage.
     /// The trytes to be used for this call are
     /// returned by attachToTangle.
     pub fn store_transactions(&mut self, trytes: &[String]) -> Result<StoreTransactionsResponse> {
-        ensure!(
-            input_validator::is_array_of_attached_trytes(&trytes),
-            "Provided trytes are not valid: {:?}",
-            trytes
-        );
-
-        let parsed_resp: StoreTransactionsResponse = self
-            .runtime
-            .block_on(
-                store_transactions::store_transactions(&self.client, self.uri, trytes)
-                    .and_then(|mut resp| resp.json()),
-            )
-            .unwrap();
-        Ok(parsed_resp)
+        self.runtime.block_on(self.inner.store_transactions(trytes))
     }
 
     /// Check if a list of addresses was ever spent from.
@@ -389,24 +259,7 @@ impl<'a> Client<'a> {
         &mut self,
         addresses: &[String],
     ) -> Result<WereAddressesSpentFromResponse> {
-        let addresses: Vec<String> = addresses
-            .iter()
-            .filter(|address| input_validator::is_address(address))
-            .map(|address| iota_signing::checksum::remove_checksum(address))
-            .collect();
-        ensure!(!addresses.is_empty(), "No valid addresses provided.");
-
-        let parsed_resp: WereAddressesSpentFromResponse = self
-            .runtime
-            .block_on(
-                were_addresses_spent_from::were_addresses_spent_from(
-                    &self.client,
-                    self.uri,
-                    &addresses,
-                )
-                .and_then(|mut resp| resp.json()),
-            )
-            .unwrap();
-        Ok(parsed_resp)
+        self.runtime
+            .block_on(self.inner.were_addresses_spent_from(addresses))
     }
 }
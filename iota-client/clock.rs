@@ -0,0 +1,95 @@
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicI64, Ordering};
+
+use chrono::prelude::*;
+
+/// Tells the time to the APIs stamping transactions.
+///
+/// Bundle entries carry a timestamp in seconds, which is part of the
+/// bundle hash, and attached transactions an attachment timestamp in
+/// milliseconds. `prepare_transfers`, `send_trytes` and
+/// `attach_to_tangle_local` read both from the clock of their options, so
+/// a `FixedClock` or a `SteppingClock` makes the same inputs produce the
+/// same trytes, for tests or to re-derive a bundle on an offline signer.
+pub trait Clock: Debug + Send + Sync {
+    /// Milliseconds since the Unix epoch
+    fn now_millis(&self) -> i64;
+
+    /// Seconds since the Unix epoch
+    fn now(&self) -> i64 {
+        self.now_millis().div_euclid(1000)
+    }
+}
+
+/// Reads the system time. This is the default.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_millis(&self) -> i64 {
+        Utc::now().timestamp_millis()
+    }
+}
+
+/// Always tells the same time
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FixedClock {
+    millis: i64,
+}
+
+impl FixedClock {
+    /// Create a clock stopped at `millis` milliseconds since the Unix epoch
+    pub fn new(millis: i64) -> FixedClock {
+        FixedClock { millis }
+    }
+}
+
+impl Clock for FixedClock {
+    fn now_millis(&self) -> i64 {
+        self.millis
+    }
+}
+
+/// Starts at a given time and moves forward by a fixed step every time
+/// it is read, so that consecutive timestamps differ but stay reproducible
+#[derive(Debug, Default)]
+pub struct SteppingClock {
+    next: AtomicI64,
+    step: i64,
+}
+
+impl SteppingClock {
+    /// Create a clock first reading `start` and then moving forward by
+    /// `step`, both in milliseconds
+    pub fn new(start: i64, step: i64) -> SteppingClock {
+        SteppingClock {
+            next: AtomicI64::new(start),
+            step,
+        }
+    }
+}
+
+impl Clock for SteppingClock {
+    fn now_millis(&self) -> i64 {
+        self.next.fetch_add(self.step, Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clocks() {
+        let fixed = FixedClock::new(1_500_000_000_123);
+        assert_eq!(fixed.now_millis(), 1_500_000_000_123);
+        assert_eq!(fixed.now(), 1_500_000_000);
+
+        let stepping = SteppingClock::new(1000, 500);
+        assert_eq!(stepping.now(), 1);
+        assert_eq!(stepping.now_millis(), 1500);
+        assert_eq!(stepping.now(), 2);
+
+        assert!(SystemClock.now() > 1_500_000_000);
+    }
+}
@@ -1,16 +1,15 @@
-use chrono::prelude::*;
-use reqwest::r#async::{Client, Response};
-use reqwest::Error;
-use tokio::prelude::*;
+use serde_json::Value;
 
 use iota_conversion::Trinary;
 use iota_model::*;
 use iota_pow::{PearlDiver, PowOptions};
 use iota_validation::input_validator;
 
+use crate::clock::{Clock, SystemClock};
 use crate::Result;
 
 use std::convert::TryInto;
+use std::sync::Arc;
 
 lazy_static! {
     /// This is a computed constant that represent the maximum allowed timestamp value
@@ -30,6 +29,8 @@ pub struct AttachOptions<'a, 'b, 'c> {
     pub min_weight_magnitude: usize,
     /// Trytes to attach to tangle
     pub trytes: &'c [String],
+    /// Stamps the attachment timestamps of `attach_to_tangle_local`, see `Clock`
+    pub clock: Arc<dyn Clock>,
 }
 
 /// Provides sane defaults for the fields
@@ -38,6 +39,7 @@ pub struct AttachOptions<'a, 'b, 'c> {
 /// * `branch_transaction` - Empty string
 /// * `min_weight_magnitude` - 14
 /// * `trytes` - Empty vector
+/// * `clock` - `SystemClock`
 impl<'a, 'b, 'c> Default for AttachOptions<'a, 'b, 'c> {
     fn default() -> Self {
         AttachOptions {
@@ -46,36 +48,25 @@ impl<'a, 'b, 'c> Default for AttachOptions<'a, 'b, 'c> {
             branch_transaction: "",
             min_weight_magnitude: 14,
             trytes: &[],
+            clock: Arc::new(SystemClock),
         }
     }
 }
 
 /// Performs proof of work
 ///
-/// * `uri` - the uri used to make the request
 /// * `trunk_transaction` - trunk transaction to confirm
 /// * `branch_transaction` - branch transaction to confirm
 /// * `min_weight_magnitude` - Difficulty of PoW
 /// * `trytes` - tryes to use for PoW
-pub fn attach_to_tangle(
-    client: &Client,
-    uri: &str,
-    options: AttachOptions<'_, '_, '_>,
-) -> impl Future<Item = Response, Error = Error> {
-    let body = json!({
+pub fn attach_to_tangle(options: AttachOptions<'_, '_, '_>) -> Value {
+    json!({
         "command": "attachToTangle",
         "trunkTransaction": options.trunk_transaction,
         "branchTransaction": options.branch_transaction,
         "minWeightMagnitude": options.min_weight_magnitude,
         "trytes": options.trytes,
-    });
-
-    client
-        .post(uri)
-        .header("ContentType", "application/json")
-        .header("X-IOTA-API-Version", "1")
-        .body(body.to_string())
-        .send()
+    })
 }
 
 /// Performs proof of work locally
@@ -86,68 +77,92 @@ pub fn attach_to_tangle(
 /// * `branch_transaction` - branch transaction to confirm
 /// * `min_weight_magnitude` - Difficulty of PoW
 /// * `trytes` - tryes to use for PoW
+/// * `clock` - stamps the attachment timestamps
 pub fn attach_to_tangle_local(
     options: AttachOptions<'_, '_, '_>,
 ) -> Result<AttachToTangleResponse> {
-    ensure!(
-        input_validator::is_hash(&options.trunk_transaction),
+    let threads = options.threads;
+    let min_weight_magnitude = options.min_weight_magnitude;
+    let result_trytes = attach_with(
+        options.trunk_transaction,
+        options.branch_transaction,
+        options.trytes,
+        &*options.clock,
+        |tx_trytes| {
+            let result_trits = PearlDiver::default().search(
+                tx_trytes.trits(),
+                PowOptions {
+                    min_weight_magnitude,
+                    threads,
+                },
+            )?;
+            Ok(result_trits.trytes()?)
+        },
+    )?;
+    Ok(AttachToTangleResponse::new(
+        None,
+        None,
+        None,
+        Some(result_trytes),
+    ))
+}
+
+/// Attaches `trytes` the way IRI's attachToTangle does, with `pow` filling in every nonce
+pub(crate) fn attach_with<F>(
+    trunk_transaction: &str,
+    branch_transaction: &str,
+    trytes: &[String],
+    clock: &dyn Clock,
+    mut pow: F,
+) -> Result<Vec<String>>
+where
+    F: FnMut(String) -> Result<String>,
+{
+    ensure_input!(
+        input_validator::is_hash(trunk_transaction),
         "Provided trunk transaction is not valid: {:?}",
-        options.trunk_transaction
+        trunk_transaction
     );
-    ensure!(
-        input_validator::is_hash(&options.branch_transaction),
+    ensure_input!(
+        input_validator::is_hash(branch_transaction),
         "Provided branch transaction is not valid: {:?}",
-        options.branch_transaction
+        branch_transaction
     );
-    ensure!(
-        input_validator::is_array_of_trytes(&options.trytes),
+    ensure_input!(
+        input_validator::is_array_of_trytes(trytes),
         "Provided trytes are not valid: {:?}",
-        options.trytes
+        trytes
     );
 
-    let mut result_trytes: Vec<String> = Vec::with_capacity(options.trytes.len());
+    let mut result_trytes: Vec<String> = Vec::with_capacity(trytes.len());
     let mut previous_transaction = String::new();
-    for i in 0..options.trytes.len() {
-        let mut tx: Transaction = options.trytes[i].parse()?;
+    for (i, tx_trytes) in trytes.iter().enumerate() {
+        let mut tx: Transaction = tx_trytes.parse()?;
 
         tx.trunk_transaction = if previous_transaction.is_empty() {
-            options.trunk_transaction.into()
+            trunk_transaction.into()
         } else {
             previous_transaction.clone()
         };
 
         tx.branch_transaction = if previous_transaction.is_empty() {
-            options.branch_transaction
+            branch_transaction
         } else {
-            options.trunk_transaction
+            trunk_transaction
         }
         .into();
 
         if tx.tag.is_empty() || tx.tag == "9".repeat(27) {
             tx.tag = tx.obsolete_tag.clone();
         }
-        tx.attachment_timestamp = Utc::now().timestamp_millis();
+        tx.attachment_timestamp = clock.now_millis();
         tx.attachment_timestamp_lower_bound = 0;
         tx.attachment_timestamp_upper_bound = *MAX_TIMESTAMP_VALUE;
-        let tx_trytes: String = tx.try_into()?;
-        let tx_trits = tx_trytes.trits();
-        let result_trits = PearlDiver::default().search(
-            tx_trits,
-            PowOptions {
-                min_weight_magnitude: options.min_weight_magnitude,
-                ..PowOptions::default()
-            },
-        )?;
-        result_trytes.push(result_trits.trytes()?);
-        previous_transaction = result_trytes[i].parse::<Transaction>()?.hash.into();
+        result_trytes.push(pow(tx.try_into()?)?);
+        previous_transaction = result_trytes[i].parse::<Transaction>()?.hash;
     }
     result_trytes.reverse();
-    Ok(AttachToTangleResponse::new(
-        None,
-        None,
-        None,
-        Some(result_trytes),
-    ))
+    Ok(result_trytes)
 }
 
 /// This is a typed representation of the JSON response
@@ -1,22 +1,36 @@
-use reqwest::r#async::{Client, Response};
-use reqwest::Error;
-use tokio::prelude::Future;
+use serde_json::Value;
 
 /// Checks for consistency of given hashes, not part of the public api
-pub fn check_consistency(
-    client: &Client,
-    uri: &str,
-    hashes: &[String],
-) -> impl Future<Item = Response, Error = Error> {
-    let body = json!({
+pub fn check_consistency(hashes: &[String]) -> Value {
+    json!({
         "command": "checkConsistency",
         "tails": hashes,
-    });
+    })
+}
+
+/// This is a typed representation of the JSON response
+#[derive(Clone, Serialize, Default, Deserialize, Debug)]
+pub struct CheckConsistencyResponse {
+    /// Any errors that occurred
+    error: Option<String>,
+    /// Whether the tails can be approved together
+    #[serde(default)]
+    state: bool,
+    /// Why the tails are not consistent, if they are not
+    info: Option<String>,
+}
 
-    client
-        .post(uri)
-        .header("ContentType", "application/json")
-        .header("X-IOTA-API-Version", "1")
-        .body(body.to_string())
-        .send()
+impl CheckConsistencyResponse {
+    /// Returns the error attribute
+    pub fn error(&self) -> &Option<String> {
+        &self.error
+    }
+    /// Returns the state attribute
+    pub fn state(&self) -> bool {
+        self.state
+    }
+    /// Returns the info attribute
+    pub fn info(&self) -> &Option<String> {
+        &self.info
+    }
 }
@@ -1,6 +1,6 @@
-use reqwest::r#async::{Client, Response};
-use reqwest::Error;
-use tokio::prelude::Future;
+use std::collections::HashSet;
+
+use serde_json::Value;
 
 /// Struct used to provide named arguments for `find_transactions`
 #[derive(Clone, Default, Debug)]
@@ -16,11 +16,7 @@ pub struct FindTransactionsOptions {
 }
 
 /// Finds transactions the match any of the provided parameters
-pub fn find_transactions(
-    client: &Client,
-    uri: &str,
-    options: FindTransactionsOptions,
-) -> impl Future<Item = Response, Error = Error> {
+pub fn find_transactions(options: FindTransactionsOptions) -> Value {
     let mut body = json!({
         "command": "findTransactions",
     });
@@ -38,12 +34,7 @@ pub fn find_transactions(
         body["approvees"] = json!(options.approvees);
     }
 
-    client
-        .post(uri)
-        .header("ContentType", "application/json")
-        .header("X-IOTA-API-Version", "1")
-        .body(body.to_string())
-        .send()
+    body
 }
 
 /// This is a typed representation of the JSON response
@@ -68,4 +59,25 @@ impl FindTransactionsResponse {
     pub fn take_hashes(self) -> Option<Vec<String>> {
         self.hashes
     }
+    /// Merges the responses to batches of values, in order and
+    /// without duplicates
+    pub(crate) fn merge(responses: Vec<FindTransactionsResponse>) -> FindTransactionsResponse {
+        if responses.len() == 1 {
+            return responses.into_iter().next().unwrap_or_default();
+        }
+        let mut seen = HashSet::new();
+        let mut hashes: Vec<String> = Vec::new();
+        for hash in responses
+            .into_iter()
+            .flat_map(|resp| resp.hashes.unwrap_or_default())
+        {
+            if seen.insert(hash.clone()) {
+                hashes.push(hash);
+            }
+        }
+        FindTransactionsResponse {
+            error: None,
+            hashes: Some(hashes),
+        }
+    }
 }
@@ -1,6 +1,4 @@
-use reqwest::r#async::{Client, Response};
-use reqwest::Error;
-use tokio::prelude::Future;
+use serde_json::Value;
 
 /// Struct used to provide named arguments for `get_balances`
 #[derive(Clone, Debug)]
@@ -32,11 +30,7 @@ impl Default for GetBalancesOptions {
 /// as well as the index with which the confirmed balance was
 /// determined. The balances is returned as a list in the same
 /// order as the addresses were provided as input.
-pub fn get_balances(
-    client: &Client,
-    uri: &str,
-    options: GetBalancesOptions,
-) -> impl Future<Item = Response, Error = Error> {
+pub fn get_balances(options: GetBalancesOptions) -> Value {
     let mut body = json!({
         "command": "getBalances",
         "addresses": options.addresses,
@@ -47,12 +41,7 @@ pub fn get_balances(
         body["tips"] = json!(options.tips);
     }
 
-    client
-        .post(uri)
-        .header("ContentType", "application/json")
-        .header("X-IOTA-API-Version", "1")
-        .body(body.to_string())
-        .send()
+    body
 }
 /// This is a typed representation of the JSON response
 #[derive(Clone, Default, Serialize, Deserialize, Debug)]
@@ -93,4 +82,15 @@ impl GetBalancesResponse {
     pub fn take_references(self) -> Option<Vec<String>> {
         self.references
     }
+    /// Concatenates the responses to batches of addresses, in order.
+    /// The milestone and references are those of the first batch.
+    pub(crate) fn merge(responses: Vec<GetBalancesResponse>) -> GetBalancesResponse {
+        let mut responses = responses.into_iter();
+        let mut merged = responses.next().unwrap_or_default();
+        let balances = merged.balances.get_or_insert_with(Vec::new);
+        for resp in responses {
+            balances.extend(resp.balances.unwrap_or_default());
+        }
+        merged
+    }
 }
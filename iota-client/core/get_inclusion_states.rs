@@ -1,6 +1,4 @@
-use reqwest::r#async::{Client, Response};
-use reqwest::Error;
-use tokio::prelude::Future;
+use serde_json::Value;
 
 /// Struct used to provide named arguments for `get_inclusion_states`
 #[derive(Clone, Debug, Default)]
@@ -19,27 +17,16 @@ pub struct GetInclusionStatesOptions {
 /// This API call simply returns a list of boolean values in the
 /// same order as the transaction list you submitted, thus you get
 /// a true/false whether a transaction is confirmed or not.
-pub fn get_inclusion_states(
-    client: &Client,
-    uri: &str,
-    options: GetInclusionStatesOptions,
-) -> impl Future<Item = Response, Error = Error> {
-    let body = json!({
+pub fn get_inclusion_states(options: GetInclusionStatesOptions) -> Value {
+    json!({
         "command": "getInclusionStates",
         "transactions": options.transactions,
         "tips": options.tips,
-    });
-
-    client
-        .post(uri)
-        .header("ContentType", "application/json")
-        .header("X-IOTA-API-Version", "1")
-        .body(body.to_string())
-        .send()
+    })
 }
 
 /// This is a typed representation of the JSON response
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Default, Serialize, Deserialize, Debug)]
 pub struct GetInclusionStatesResponse {
     /// Any errors that occurred
     error: Option<String>,
@@ -56,4 +43,19 @@ impl GetInclusionStatesResponse {
     pub fn states(&self) -> &Option<Vec<bool>> {
         &self.states
     }
+    /// Concatenates the responses to batches of transactions, in order
+    pub(crate) fn merge(responses: Vec<GetInclusionStatesResponse>) -> GetInclusionStatesResponse {
+        if responses.len() == 1 {
+            return responses.into_iter().next().unwrap_or_default();
+        }
+        GetInclusionStatesResponse {
+            error: None,
+            states: Some(
+                responses
+                    .into_iter()
+                    .flat_map(|resp| resp.states.unwrap_or_default())
+                    .collect(),
+            ),
+        }
+    }
 }
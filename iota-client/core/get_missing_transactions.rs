@@ -0,0 +1,33 @@
+use serde_json::Value;
+
+/// Returns the transactions the node knows are referenced
+/// but has not received yet
+pub fn get_missing_transactions() -> Value {
+    json!({
+        "command": "getMissingTransactions",
+    })
+}
+
+/// This is a typed representation of the JSON response
+#[derive(Clone, Serialize, Default, Deserialize, Debug)]
+pub struct GetMissingTransactionsResponse {
+    /// Any errors that occurred
+    error: Option<String>,
+    /// Hashes of the missing transactions
+    hashes: Option<Vec<String>>,
+}
+
+impl GetMissingTransactionsResponse {
+    /// Returns the error attribute
+    pub fn error(&self) -> &Option<String> {
+        &self.error
+    }
+    /// Returns the hashes attribute
+    pub fn hashes(&self) -> &Option<Vec<String>> {
+        &self.hashes
+    }
+    /// Takes ownership the hashes attribute
+    pub fn take_hashes(self) -> Option<Vec<String>> {
+        self.hashes
+    }
+}
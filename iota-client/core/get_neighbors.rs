@@ -1,25 +1,13 @@
 use iota_model::Neighbor;
-use reqwest::r#async::{Client, Response};
-use reqwest::Error;
-use tokio::prelude::Future;
+use serde_json::Value;
 
 /// Returns the set of neighbors you are connected with, as
 /// well as their activity count. The activity counter is reset
 /// after restarting IRI.
-pub fn get_neighbors(
-    client: &Client,
-    uri: &str,
-) -> impl Future<Item = Response, Error = Error> {
-    let body = json!({
+pub fn get_neighbors() -> Value {
+    json!({
         "command": "getNeighbors",
-    });
-
-    client
-        .post(uri)
-        .header("ContentType", "application/json")
-        .header("X-IOTA-API-Version", "1")
-        .body(body.to_string())
-        .send()
+    })
 }
 
 /// This is a typed representation of the JSON response
@@ -0,0 +1,85 @@
+use serde_json::Value;
+
+use crate::batch::BatchSizes;
+
+/// Gets the API limits and settings of the specified node
+pub fn get_node_api_configuration() -> Value {
+    json!({
+        "command": "getNodeAPIConfiguration",
+    })
+}
+
+/// This is a typed representation of the JSON response
+#[derive(Clone, Serialize, Default, Deserialize, Debug)]
+pub struct GetNodeAPIConfigurationResponse {
+    /// Any errors that occurred
+    error: Option<String>,
+    /// Maximum number of transactions findTransactions returns
+    #[serde(rename = "maxFindTransactions")]
+    max_find_transactions: Option<usize>,
+    /// Maximum number of values in a list parameter
+    #[serde(rename = "maxRequestsList")]
+    max_requests_list: Option<usize>,
+    /// Maximum number of hashes getTrytes accepts
+    #[serde(rename = "maxGetTrytes")]
+    max_get_trytes: Option<usize>,
+    /// Maximum length of a request body, in characters
+    #[serde(rename = "maxBodyLength")]
+    max_body_length: Option<usize>,
+    /// Minimum weight magnitude required by the node
+    mwm: Option<usize>,
+    /// Whether the node runs on a test network
+    #[serde(rename = "testNet")]
+    test_net: Option<bool>,
+    /// Index of the first milestone the node knows of
+    #[serde(rename = "milestoneStartIndex")]
+    milestone_start_index: Option<u32>,
+}
+
+impl GetNodeAPIConfigurationResponse {
+    /// Returns the error attribute
+    pub fn error(&self) -> &Option<String> {
+        &self.error
+    }
+    /// Returns the max_find_transactions attribute
+    pub fn max_find_transactions(&self) -> Option<usize> {
+        self.max_find_transactions
+    }
+    /// Returns the max_requests_list attribute
+    pub fn max_requests_list(&self) -> Option<usize> {
+        self.max_requests_list
+    }
+    /// Returns the max_get_trytes attribute
+    pub fn max_get_trytes(&self) -> Option<usize> {
+        self.max_get_trytes
+    }
+    /// Returns the max_body_length attribute
+    pub fn max_body_length(&self) -> Option<usize> {
+        self.max_body_length
+    }
+    /// Returns the mwm attribute
+    pub fn mwm(&self) -> Option<usize> {
+        self.mwm
+    }
+    /// Returns the test_net attribute
+    pub fn test_net(&self) -> Option<bool> {
+        self.test_net
+    }
+    /// Returns the milestone_start_index attribute
+    pub fn milestone_start_index(&self) -> Option<u32> {
+        self.milestone_start_index
+    }
+    /// Returns batch sizes within the limits of the node, using the
+    /// defaults for limits the node didn't report. findTransactions
+    /// inputs are bound by `maxRequestsList`, the node's
+    /// `maxFindTransactions` only limits the number of results.
+    pub fn batch_sizes(&self) -> BatchSizes {
+        let defaults = BatchSizes::default();
+        let max_requests_list = self.max_requests_list.unwrap_or(defaults.max_requests_list);
+        BatchSizes {
+            max_find_transactions: defaults.max_find_transactions.min(max_requests_list),
+            max_get_trytes: self.max_get_trytes.unwrap_or(defaults.max_get_trytes),
+            max_requests_list,
+        }
+    }
+}
@@ -1,22 +1,10 @@
-use reqwest::r#async::{Client, Response};
-use reqwest::Error;
-use tokio::prelude::Future;
+use serde_json::Value;
 
 /// Gets information about the specified node
-pub fn get_node_info(
-    client: &Client,
-    uri: &str,
-) -> impl Future<Item = Response, Error = Error> {
-    let body = json!({
+pub fn get_node_info() -> Value {
+    json!({
         "command": "getNodeInfo",
-    });
-
-    client
-        .post(uri)
-        .header("ContentType", "application/json")
-        .header("X-IOTA-API-Version", "1")
-        .body(body.to_string())
-        .send()
+    })
 }
 
 /// This is a typed representation of the JSON response
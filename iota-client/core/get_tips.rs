@@ -1,19 +1,10 @@
-use reqwest::r#async::{Client, Response};
-use reqwest::Error;
-use tokio::prelude::Future;
+use serde_json::Value;
 
 /// Returns the list of tips
-pub fn get_tips(client: &Client, uri: &str) -> impl Future<Item = Response, Error = Error> {
-    let body = json!({
+pub fn get_tips() -> Value {
+    json!({
         "command": "getTips",
-    });
-
-    client
-        .post(uri)
-        .header("ContentType", "application/json")
-        .header("X-IOTA-API-Version", "1")
-        .body(body.to_string())
-        .send()
+    })
 }
 
 /// This is a typed representation of the JSON response
@@ -1,6 +1,4 @@
-use reqwest::r#async::{Client, Response};
-use reqwest::Error;
-use tokio::prelude::Future;
+use serde_json::Value;
 
 /// Struct used to provide named arguments for `get_transactions_to_approve`
 #[derive(Clone, Debug)]
@@ -33,11 +31,7 @@ impl<'a> Default for GetTransactionsToApproveOptions<'a> {
 /// returned. The reference is an optional hash of a transaction
 /// you want to approve. If it can't be found at the specified
 /// depth then an error will be returned.
-pub fn get_transactions_to_approve(
-    client: &Client,
-    uri: &str,
-    options: GetTransactionsToApproveOptions<'_>,
-) -> impl Future<Item = Response, Error = Error> {
+pub fn get_transactions_to_approve(options: GetTransactionsToApproveOptions<'_>) -> Value {
     let mut body = json!({
         "command": "getTransactionsToApprove",
         "depth": options.depth,
@@ -47,12 +41,7 @@ pub fn get_transactions_to_approve(
         body["reference"] = json!(reference);
     }
 
-    client
-        .post(uri)
-        .header("ContentType", "application/json")
-        .header("X-IOTA-API-Version", "1")
-        .body(body.to_string())
-        .send()
+    body
 }
 
 /// This is a typed representation of the JSON response
@@ -1,27 +1,14 @@
-use reqwest::r#async::{Client, Response};
-use reqwest::Error;
-use tokio::prelude::Future;
+use serde_json::Value;
 
 /// Returns the raw transaction data (trytes) of a specific
 /// transaction. These trytes can then be easily converted
 /// into the actual transaction object. See utility functions
 /// for more details.
-pub fn get_trytes(
-    client: &Client,
-    uri: &str,
-    hashes: &[String],
-) -> impl Future<Item = Response, Error = Error> {
-    let body = json!({
+pub fn get_trytes(hashes: &[String]) -> Value {
+    json!({
         "command": "getTrytes",
         "hashes": hashes,
-    });
-
-    client
-        .post(uri)
-        .header("ContentType", "application/json")
-        .header("X-IOTA-API-Version", "1")
-        .body(body.to_string())
-        .send()
+    })
 }
 
 /// This is a typed representation of the JSON response
@@ -46,4 +33,19 @@ impl GetTrytesResponse {
     pub fn take_trytes(self) -> Option<Vec<String>> {
         self.trytes
     }
+    /// Concatenates the responses to batches of hashes, in order
+    pub(crate) fn merge(responses: Vec<GetTrytesResponse>) -> GetTrytesResponse {
+        if responses.len() == 1 {
+            return responses.into_iter().next().unwrap_or_default();
+        }
+        GetTrytesResponse {
+            error: None,
+            trytes: Some(
+                responses
+                    .into_iter()
+                    .flat_map(|resp| resp.trytes.unwrap_or_default())
+                    .collect(),
+            ),
+        }
+    }
 }
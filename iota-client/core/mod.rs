@@ -7,7 +7,9 @@ pub mod check_consistency;
 pub mod find_transactions;
 pub mod get_balances;
 pub mod get_inclusion_states;
+pub mod get_missing_transactions;
 pub mod get_neighbors;
+pub mod get_node_api_configuration;
 pub mod get_node_info;
 pub mod get_tips;
 pub mod get_transactions_to_approve;
@@ -20,10 +22,13 @@ pub mod were_addresses_spent_from;
 pub use add_neighbors::AddNeighborsResponse;
 pub use attach_to_tangle::AttachToTangleResponse;
 pub use broadcast_transactions::BroadcastTransactionsResponse;
+pub use check_consistency::CheckConsistencyResponse;
 pub use find_transactions::FindTransactionsResponse;
 pub use get_balances::GetBalancesResponse;
 pub use get_inclusion_states::GetInclusionStatesResponse;
+pub use get_missing_transactions::GetMissingTransactionsResponse;
 pub use get_neighbors::GetNeighborsResponse;
+pub use get_node_api_configuration::GetNodeAPIConfigurationResponse;
 pub use get_node_info::GetNodeInfoResponse;
 pub use get_tips::GetTipsResponse;
 pub use get_transactions_to_approve::GetTransactionsToApprove;
@@ -1,26 +1,13 @@
-use reqwest::r#async::{Client, Response};
-use reqwest::Error;
-use tokio::prelude::Future;
+use serde_json::Value;
 
 /// Store transactions into the local storage.
 /// The trytes to be used for this call are
 /// returned by attachToTangle.
-pub fn store_transactions(
-    client: &Client,
-    uri: &str,
-    trytes: &[String],
-) -> impl Future<Item = Response, Error = Error> {
-    let body = json!({
+pub fn store_transactions(trytes: &[String]) -> Value {
+    json!({
         "command": "storeTransactions",
         "trytes": trytes,
-    });
-
-    client
-        .post(uri)
-        .header("ContentType", "application/json")
-        .header("X-IOTA-API-Version", "1")
-        .body(body.to_string())
-        .send()
+    })
 }
 
 /// This is a typed representation of the JSON response
@@ -1,24 +1,11 @@
-use reqwest::r#async::{Client, Response};
-use reqwest::Error;
-use tokio::prelude::Future;
+use serde_json::Value;
 
 /// Check if a list of addresses was ever spent from.
-pub fn were_addresses_spent_from(
-    client: &Client,
-    uri: &str,
-    addresses: &[String],
-) -> impl Future<Item = Response, Error = Error> {
-    let body = json!({
+pub fn were_addresses_spent_from(addresses: &[String]) -> Value {
+    json!({
         "command": "wereAddressesSpentFrom",
         "addresses": addresses,
-    });
-
-    client
-        .post(uri)
-        .header("ContentType", "application/json")
-        .header("X-IOTA-API-Version", "1")
-        .body(body.to_string())
-        .send()
+    })
 }
 
 /// This is a typed representation of the JSON response
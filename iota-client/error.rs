@@ -0,0 +1,219 @@
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// This type represents every error that can occur while talking to an IRI node
+#[derive(Debug, Fail)]
+pub enum ClientError {
+    /// The node could not be reached, the connection was dropped or
+    /// the request timed out before a response was received
+    #[fail(display = "Transport error: {}", _0)]
+    Transport(failure::Error),
+    /// The node responded with a non-success HTTP status
+    /// that carries no IRI error message
+    #[fail(display = "Node responded with HTTP {}: {}", status, body)]
+    Http {
+        /// HTTP status code of the response
+        status: u16,
+        /// Raw body of the response
+        body: String,
+    },
+    /// The node rejected the request, as reported in the `error` field
+    #[fail(display = "{}", _0)]
+    Iri(String),
+    /// The node failed to process the request, as reported in the `exception` field
+    #[fail(display = "{}", _0)]
+    IriException(String),
+    /// The response body could not be parsed into the expected type
+    #[fail(display = "Failed to parse response: {}", error)]
+    Deserialization {
+        /// The underlying serde error
+        #[cause]
+        error: serde_json::Error,
+        /// Raw body of the response
+        body: String,
+    },
+    /// Nodes queried in quorum mode did not return enough identical answers
+    #[fail(
+        display = "Quorum of {} not reached for {}: {:?}",
+        threshold, command, answers
+    )]
+    QuorumNotReached {
+        /// The IRI command that was sent
+        command: String,
+        /// Number of identical answers that were required
+        threshold: usize,
+        /// URI of every queried node, paired with its answer or error
+        answers: Vec<(String, String)>,
+    },
+    /// The provided input is not valid, nothing was sent to the node
+    #[fail(display = "{}", _0)]
+    InvalidInput(String),
+    /// Any other error, e.g. while parsing or signing transactions
+    #[fail(display = "{}", _0)]
+    Other(failure::Error),
+}
+
+/// The kind of a `ClientError`, without its details
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// See `ClientError::Transport`
+    Transport,
+    /// See `ClientError::Http`
+    Http,
+    /// See `ClientError::Iri`
+    Iri,
+    /// See `ClientError::IriException`
+    IriException,
+    /// See `ClientError::Deserialization`
+    Deserialization,
+    /// See `ClientError::QuorumNotReached`
+    QuorumNotReached,
+    /// See `ClientError::InvalidInput`
+    InvalidInput,
+    /// See `ClientError::Other`
+    Other,
+}
+
+impl ClientError {
+    /// Returns the kind of this error
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ClientError::Transport(_) => ErrorKind::Transport,
+            ClientError::Http { .. } => ErrorKind::Http,
+            ClientError::Iri(_) => ErrorKind::Iri,
+            ClientError::IriException(_) => ErrorKind::IriException,
+            ClientError::Deserialization { .. } => ErrorKind::Deserialization,
+            ClientError::QuorumNotReached { .. } => ErrorKind::QuorumNotReached,
+            ClientError::InvalidInput(_) => ErrorKind::InvalidInput,
+            ClientError::Other(_) => ErrorKind::Other,
+        }
+    }
+
+    /// Returns true if the node could not be reached at all
+    pub fn is_transport(&self) -> bool {
+        matches!(self, ClientError::Transport(_))
+    }
+
+    /// Returns true if the node was reached, but refused or failed to process the request
+    pub fn is_rejected(&self) -> bool {
+        matches!(
+            self,
+            ClientError::Http { .. } | ClientError::Iri(_) | ClientError::IriException(_)
+        )
+    }
+}
+
+impl From<failure::Error> for ClientError {
+    fn from(error: failure::Error) -> Self {
+        ClientError::Other(error)
+    }
+}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(error: reqwest::Error) -> Self {
+        ClientError::Transport(error.into())
+    }
+}
+
+impl From<std::num::ParseIntError> for ClientError {
+    fn from(error: std::num::ParseIntError) -> Self {
+        ClientError::Other(error.into())
+    }
+}
+
+/// Returns early with `ClientError::InvalidInput` if the condition is not satisfied
+macro_rules! ensure_input {
+    ($cond:expr, $($arg:tt)+) => {
+        if !($cond) {
+            return Err($crate::error::ClientError::InvalidInput(format!($($arg)+)));
+        }
+    };
+}
+
+/// Turns the status and body of an HTTP response into the JSON object
+/// IRI answered with. IRI `error` and `exception` fields are kept, any
+/// other non-success status is returned as `ClientError::Http`
+pub(crate) fn parse_body(status: StatusCode, body: String) -> Result<Value, ClientError> {
+    let value: Value = match serde_json::from_str(&body) {
+        Ok(value) => value,
+        Err(_) if !status.is_success() => {
+            return Err(ClientError::Http {
+                status: status.as_u16(),
+                body,
+            })
+        }
+        Err(error) => return Err(ClientError::Deserialization { error, body }),
+    };
+    if !status.is_success() && value.get("error").is_none() && value.get("exception").is_none() {
+        return Err(ClientError::Http {
+            status: status.as_u16(),
+            body,
+        });
+    }
+    Ok(value)
+}
+
+/// Turns the JSON object IRI answered with into the expected type,
+/// surfacing the `error` and `exception` fields as errors
+pub(crate) fn decode<T: DeserializeOwned>(value: Value) -> Result<T, ClientError> {
+    if let Some(error) = value.get("error").and_then(Value::as_str) {
+        return Err(ClientError::Iri(error.to_string()));
+    }
+    if let Some(exception) = value.get("exception").and_then(Value::as_str) {
+        return Err(ClientError::IriException(exception.to_string()));
+    }
+    let body = value.to_string();
+    serde_json::from_value(value).map_err(|error| ClientError::Deserialization { error, body })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::GetBalancesResponse;
+
+    #[test]
+    fn test_parse_iri_error() {
+        let value = parse_body(
+            StatusCode::BAD_REQUEST,
+            r#"{"error": "Invalid depth input", "duration": 0}"#.into(),
+        )
+        .unwrap();
+        let err = decode::<GetBalancesResponse>(value).unwrap_err();
+        assert!(err.is_rejected());
+        assert_eq!(err.to_string(), "Invalid depth input");
+    }
+
+    #[test]
+    fn test_parse_http_error() {
+        let err = parse_body(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "<html>Bad gateway</html>".into(),
+        )
+        .unwrap_err();
+        match err {
+            ClientError::Http { status, .. } => assert_eq!(status, 503),
+            e => panic!("unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_parse_malformed_body() {
+        let err = parse_body(StatusCode::OK, "not json".into()).unwrap_err();
+        match err {
+            ClientError::Deserialization { body, .. } => assert_eq!(body, "not json"),
+            e => panic!("unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_parse_success() {
+        let value = parse_body(
+            StatusCode::OK,
+            r#"{"balances": ["10"], "milestoneIndex": 42, "references": []}"#.into(),
+        )
+        .unwrap();
+        let resp = decode::<GetBalancesResponse>(value).unwrap();
+        assert_eq!(resp.milestone_index(), Some(42));
+    }
+}
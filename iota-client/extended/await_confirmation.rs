@@ -0,0 +1,245 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::prelude::future::Loop;
+use tokio::prelude::*;
+use tokio::timer::Delay;
+
+use crate::async_client::AsyncClient;
+use crate::client::Client;
+use crate::clock::{Clock, SystemClock};
+use crate::error::ClientError;
+use crate::options::{GetInclusionStatesOptions, SendTrytesOptions};
+use crate::pow::{LocalPow, PowProvider};
+use crate::{BoxFuture, Result};
+
+/// How `await_confirmation` waits for a bundle to be confirmed.
+///
+/// Every `poll_interval`, the inclusion states of all attachments of the
+/// bundle are checked against the latest solid milestone. While none is
+/// confirmed, the most recent attachment is promoted if it is promotable
+/// and `promote` is set, or reattached if it is not and `reattach` is set.
+/// The wait fails once `timeout` has elapsed.
+#[derive(Clone, Debug)]
+pub struct ConfirmationPolicy {
+    /// Time between two checks of the inclusion states
+    pub poll_interval: Duration,
+    /// Time after which waiting fails
+    pub timeout: Duration,
+    /// Promote the bundle while it is promotable
+    pub promote: bool,
+    /// Reattach the bundle once it is no longer promotable
+    pub reattach: bool,
+    /// The depth for getting transactions to approve
+    pub depth: usize,
+    /// The minimum weight magnitude for doing proof of work
+    pub min_weight_magnitude: usize,
+    /// Does the proof of work of promotions and reattachments
+    pub pow: Arc<dyn PowProvider>,
+    /// Stamps promotions and reattachments, see `Clock`
+    pub clock: Arc<dyn Clock>,
+}
+
+impl Default for ConfirmationPolicy {
+    fn default() -> Self {
+        ConfirmationPolicy {
+            poll_interval: Duration::from_secs(10),
+            timeout: Duration::from_secs(30 * 60),
+            promote: true,
+            reattach: true,
+            depth: 3,
+            min_weight_magnitude: 14,
+            pow: Arc::new(LocalPow::default()),
+            clock: Arc::new(SystemClock),
+        }
+    }
+}
+
+impl ConfirmationPolicy {
+    /// Create a policy that only polls, without promoting or reattaching
+    pub fn poll_only(poll_interval: Duration, timeout: Duration) -> ConfirmationPolicy {
+        ConfirmationPolicy {
+            poll_interval,
+            timeout,
+            promote: false,
+            reattach: false,
+            ..ConfirmationPolicy::default()
+        }
+    }
+
+    fn send_options(&self) -> SendTrytesOptions<'static> {
+        SendTrytesOptions {
+            depth: self.depth,
+            min_weight_magnitude: self.min_weight_magnitude,
+            pow: self.pow.clone(),
+            reference: None,
+            clock: self.clock.clone(),
+        }
+    }
+}
+
+/// The outcome of `await_confirmation`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Confirmation {
+    /// Tail transaction hash of the confirmed attachment
+    pub tail: String,
+    /// Tail transaction hashes of the reattachments made while waiting,
+    /// oldest first
+    pub reattachments: Vec<String>,
+    /// Tail transaction hashes of the promotions made while waiting,
+    /// oldest first
+    pub promotions: Vec<String>,
+}
+
+pub(crate) fn tail_of(transactions: &[iota_model::Transaction]) -> Result<String> {
+    transactions
+        .iter()
+        .find(|tx| tx.current_index == 0)
+        .map(|tx| tx.hash.clone())
+        .ok_or_else(|| format_err!("No tail transaction was sent").into())
+}
+
+impl AsyncClient {
+    /// Waits until the bundle of `tail` is confirmed, promoting or
+    /// reattaching it according to `policy`. Resolves to the tail of the
+    /// confirmed attachment, which may be a reattachment, together with
+    /// every promotion and reattachment made.
+    ///
+    /// * `tail` - The tail transaction hash of the bundle to wait for
+    /// * `policy` - See `ConfirmationPolicy`
+    pub fn await_confirmation(
+        &self,
+        tail: &str,
+        policy: ConfirmationPolicy,
+    ) -> BoxFuture<Confirmation> {
+        if !iota_validation::is_hash(tail) {
+            return Box::new(future::err(ClientError::InvalidInput(format!(
+                "Provided tail is not valid: {:?}",
+                tail
+            ))));
+        }
+        let client = self.clone();
+        let deadline = Instant::now() + policy.timeout;
+        let progress = Confirmation {
+            tail: tail.to_string(),
+            ..Confirmation::default()
+        };
+        Box::new(future::loop_fn(progress, move |progress| {
+            let client = client.clone();
+            let policy = policy.clone();
+            client.confirmed_attachment(&progress).and_then(
+                move |confirmed| -> BoxFuture<Loop<Confirmation, Confirmation>> {
+                    if let Some(tail) = confirmed {
+                        return Box::new(future::ok(Loop::Break(Confirmation {
+                            tail,
+                            ..progress
+                        })));
+                    }
+                    if Instant::now() >= deadline {
+                        return Box::new(future::err(ClientError::Other(format_err!(
+                            "Bundle of {} was not confirmed after {:?}",
+                            progress.tail,
+                            policy.timeout
+                        ))));
+                    }
+                    let delay = Delay::new(Instant::now() + policy.poll_interval)
+                        .map_err(|e| ClientError::Other(e.into()));
+                    Box::new(
+                        client
+                            .push_bundle(progress, &policy)
+                            .and_then(move |progress| delay.map(move |_| Loop::Continue(progress))),
+                    )
+                },
+            )
+        }))
+    }
+
+    /// Returns the tail of the first confirmed attachment, if any
+    fn confirmed_attachment(&self, progress: &Confirmation) -> BoxFuture<Option<String>> {
+        let client = self.clone();
+        let mut tails = vec![progress.tail.clone()];
+        tails.extend(progress.reattachments.iter().cloned());
+        Box::new(self.get_node_info().and_then(move |info| {
+            client
+                .get_inclusion_states(GetInclusionStatesOptions {
+                    transactions: tails.clone(),
+                    tips: vec![info.latest_solid_subtangle_milestone().to_string()],
+                })
+                .and_then(move |resp| {
+                    let states = resp.states().clone().unwrap_or_default();
+                    if states.len() != tails.len() {
+                        return Err(
+                            format_err!("Inclusion states of the attachments are missing").into(),
+                        );
+                    }
+                    Ok(tails
+                        .into_iter()
+                        .zip(states)
+                        .find(|(_, confirmed)| *confirmed)
+                        .map(|(tail, _)| tail))
+                })
+        }))
+    }
+
+    /// Promotes or reattaches the most recent attachment, as allowed by `policy`
+    fn push_bundle(
+        &self,
+        mut progress: Confirmation,
+        policy: &ConfirmationPolicy,
+    ) -> BoxFuture<Confirmation> {
+        if !policy.promote && !policy.reattach {
+            return Box::new(future::ok(progress));
+        }
+        let client = self.clone();
+        let policy = policy.clone();
+        let latest = progress
+            .reattachments
+            .last()
+            .unwrap_or(&progress.tail)
+            .clone();
+        let promotable = self.is_promotable_at(&latest, policy.clock.clone());
+        Box::new(
+            promotable.and_then(move |promotable| -> BoxFuture<Confirmation> {
+                if promotable && policy.promote {
+                    Box::new(
+                        client
+                            .promote_transaction(&latest, policy.send_options())
+                            .and_then(move |sent| {
+                                progress.promotions.push(tail_of(&sent)?);
+                                Ok(progress)
+                            }),
+                    )
+                } else if !promotable && policy.reattach {
+                    Box::new(
+                        client
+                            .replay_bundle(&latest, policy.send_options())
+                            .and_then(move |sent| {
+                                progress.reattachments.push(tail_of(&sent)?);
+                                Ok(progress)
+                            }),
+                    )
+                } else {
+                    Box::new(future::ok(progress))
+                }
+            }),
+        )
+    }
+}
+
+impl Client {
+    /// Waits until the bundle of `tail` is confirmed, promoting or
+    /// reattaching it according to `policy`. Returns the tail of the
+    /// confirmed attachment, which may be a reattachment, together with
+    /// every promotion and reattachment made.
+    ///
+    /// * `tail` - The tail transaction hash of the bundle to wait for
+    /// * `policy` - See `ConfirmationPolicy`
+    pub fn await_confirmation(
+        &mut self,
+        tail: &str,
+        policy: ConfirmationPolicy,
+    ) -> Result<Confirmation> {
+        self.runtime
+            .block_on(self.inner.await_confirmation(tail, policy))
+    }
+}
@@ -0,0 +1,39 @@
+use iota_model::Transaction;
+use tokio::prelude::*;
+
+use crate::async_client::AsyncClient;
+use crate::client::Client;
+use crate::options::FindTransactionsOptions;
+use crate::{BoxFuture, Result};
+
+impl AsyncClient {
+    /// Finds the transactions matching the provided parameters, like
+    /// `find_transactions`, and returns them parsed, in the order their
+    /// hashes were returned by the node
+    ///
+    /// * `options` - See `FindTransactionsOptions`
+    pub fn find_transaction_objects(
+        &self,
+        options: FindTransactionsOptions,
+    ) -> BoxFuture<Vec<Transaction>> {
+        let client = self.clone();
+        Box::new(self.find_transactions(options).and_then(move |resp| {
+            client.get_transaction_objects(&resp.take_hashes().unwrap_or_default())
+        }))
+    }
+}
+
+impl Client {
+    /// Finds the transactions matching the provided parameters, like
+    /// `find_transactions`, and returns them parsed, in the order their
+    /// hashes were returned by the node
+    ///
+    /// * `options` - See `FindTransactionsOptions`
+    pub fn find_transaction_objects(
+        &mut self,
+        options: FindTransactionsOptions,
+    ) -> Result<Vec<Transaction>> {
+        self.runtime
+            .block_on(self.inner.find_transaction_objects(options))
+    }
+}
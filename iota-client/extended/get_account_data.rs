@@ -0,0 +1,214 @@
+use std::collections::HashSet;
+
+use iota_model::{Input, Inputs, Transaction};
+use tokio::prelude::*;
+
+use crate::async_client::AsyncClient;
+use crate::client::Client;
+use crate::error::ClientError;
+use crate::options::{FindTransactionsOptions, GetBalancesOptions};
+use crate::signer::IntoSigner;
+use crate::{BoxFuture, Result};
+
+/// Number of addresses checked per request by default while looking
+/// for the first unused address
+const DISCOVERY_WINDOW: usize = 20;
+
+/// GetAccountDataOptions
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GetAccountDataOptions {
+    /// The index of the first address to scan, 0 by default
+    pub start: Option<usize>,
+    /// The security to use for address generation, 2 by default
+    pub security: Option<usize>,
+    /// Number of addresses checked together, 20 by default
+    pub window: Option<usize>,
+    /// Number of consecutive unused addresses after which the scan stops,
+    /// 1 by default
+    pub gap_limit: Option<usize>,
+}
+
+/// Everything a wallet needs to know about a seed
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AccountData {
+    /// Every used address with its balance, key index and security,
+    /// in index order. An address is used if it was spent from or if
+    /// any transaction references it.
+    pub addresses: Vec<Input>,
+    /// Tail transaction hashes of every bundle touching the used addresses,
+    /// including reattachments
+    pub transfers: Vec<String>,
+    /// Hashes of every bundle touching the used addresses
+    pub bundles: Vec<String>,
+    /// The first unused address after the last used one
+    pub latest_address: String,
+    /// Total balance of the used addresses
+    pub balance: i64,
+}
+
+impl AccountData {
+    /// Returns the used addresses holding funds, ready to be used as
+    /// inputs of `prepare_transfers`
+    pub fn inputs(&self) -> Inputs {
+        let mut inputs = Inputs::default();
+        for input in self.addresses.iter().filter(|input| input.balance > 0) {
+            inputs.add(input.clone());
+            *inputs.total_balance_mut() += input.balance;
+        }
+        inputs
+    }
+}
+
+impl AsyncClient {
+    /// Scans a seed for its used addresses, their balances and the
+    /// bundles touching them, and finds the first unused address.
+    /// Addresses are checked in windows, with one `were_addresses_spent_from`
+    /// and one `find_transactions` call per window, plus one
+    /// `find_transactions` call per address of the windows referenced by
+    /// any transaction, until `gap_limit` consecutive addresses are unused.
+    /// The transactions touching the used addresses are then fetched to
+    /// find their bundles, along with the balances of the used addresses.
+    ///
+    /// * `seed` - The wallet seed to use, or a `Signer`
+    /// * `options` - See `GetAccountDataOptions`
+    pub fn get_account_data(
+        &self,
+        seed: impl IntoSigner,
+        options: GetAccountDataOptions,
+    ) -> BoxFuture<AccountData> {
+        let signer = match seed.into_signer() {
+            Ok(signer) => signer,
+            Err(e) => return Box::new(future::err(e)),
+        };
+        let start = options.start.unwrap_or(0);
+        let security = options.security.unwrap_or(2);
+        if !(1..=3).contains(&security) {
+            return Box::new(future::err(ClientError::InvalidInput(
+                "Invalid security.".into(),
+            )));
+        }
+
+        let discovery = self.discover_addresses(
+            signer,
+            security,
+            start,
+            options.window.unwrap_or(DISCOVERY_WINDOW),
+            options.gap_limit.unwrap_or(1),
+        );
+
+        let client = self.clone();
+        Box::new(discovery.and_then(move |mut discovery| {
+            let latest_address = discovery.addresses.pop().unwrap_or_default();
+            let used: Vec<(usize, String)> = discovery
+                .addresses
+                .into_iter()
+                .zip(discovery.used)
+                .enumerate()
+                .filter(|(_, (_, used))| *used)
+                .map(|(i, (address, _))| (start + i, address))
+                .collect();
+            client
+                .balances_of(used, security)
+                .join(client.bundles_of(&discovery.hashes))
+                .map(
+                    move |(addresses, (bundles, bundle_transactions))| AccountData {
+                        balance: addresses.iter().map(|input| input.balance).sum(),
+                        addresses,
+                        transfers: bundle_transactions
+                            .into_iter()
+                            .filter(|tx| tx.current_index == 0)
+                            .map(|tx| tx.hash)
+                            .collect(),
+                        bundles,
+                        latest_address,
+                    },
+                )
+        }))
+    }
+
+    /// Fetches the transactions of `hashes` to find their bundles, then
+    /// every transaction of those bundles, including reattachments
+    fn bundles_of(&self, hashes: &[String]) -> BoxFuture<(Vec<String>, Vec<Transaction>)> {
+        let client = self.clone();
+        Box::new(
+            self.get_transaction_objects(hashes)
+                .and_then(move |transactions| {
+                    let mut seen = HashSet::new();
+                    let bundles: Vec<String> = transactions
+                        .into_iter()
+                        .map(|tx| tx.bundle)
+                        .filter(|bundle| seen.insert(bundle.clone()))
+                        .collect();
+                    let bundle_transactions: BoxFuture<Vec<Transaction>> = if bundles.is_empty() {
+                        Box::new(future::ok(Vec::new()))
+                    } else {
+                        client.find_transaction_objects(FindTransactionsOptions {
+                            bundles: bundles.clone(),
+                            ..FindTransactionsOptions::default()
+                        })
+                    };
+                    bundle_transactions.map(move |transactions| (bundles, transactions))
+                }),
+        )
+    }
+
+    /// Fetches the balances of addresses given with their key index
+    fn balances_of(
+        &self,
+        addresses: Vec<(usize, String)>,
+        security: usize,
+    ) -> BoxFuture<Vec<Input>> {
+        if addresses.is_empty() {
+            return Box::new(future::ok(Vec::new()));
+        }
+        Box::new(
+            self.get_balances(GetBalancesOptions {
+                addresses: addresses
+                    .iter()
+                    .map(|(_, address)| address.clone())
+                    .collect(),
+                ..GetBalancesOptions::default()
+            })
+            .and_then(move |resp| {
+                let balances = resp.take_balances().unwrap_or_default();
+                if balances.len() != addresses.len() {
+                    return Err(format_err!("Balances of the used addresses are missing").into());
+                }
+                addresses
+                    .into_iter()
+                    .zip(balances)
+                    .map(|((key_index, address), balance)| {
+                        Ok(Input {
+                            address,
+                            balance: balance.parse()?,
+                            key_index,
+                            security,
+                        })
+                    })
+                    .collect::<Result<Vec<Input>>>()
+            }),
+        )
+    }
+}
+
+impl Client {
+    /// Scans a seed for its used addresses, their balances and the
+    /// bundles touching them, and finds the first unused address.
+    /// Addresses are checked in windows, with one `were_addresses_spent_from`
+    /// and one `find_transactions` call per window, plus one
+    /// `find_transactions` call per address of the windows referenced by
+    /// any transaction, until `gap_limit` consecutive addresses are unused.
+    /// The transactions touching the used addresses are then fetched to
+    /// find their bundles, along with the balances of the used addresses.
+    ///
+    /// * `seed` - The wallet seed to use, or a `Signer`
+    /// * `options` - See `GetAccountDataOptions`
+    pub fn get_account_data(
+        &mut self,
+        seed: impl IntoSigner,
+        options: GetAccountDataOptions,
+    ) -> Result<AccountData> {
+        self.runtime
+            .block_on(self.inner.get_account_data(seed, options))
+    }
+}
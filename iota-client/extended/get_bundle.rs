@@ -1,23 +1,40 @@
 use iota_model::Transaction;
+use tokio::prelude::*;
 
+use crate::async_client::AsyncClient;
 use crate::client::Client;
-use crate::Result;
+use crate::error::ClientError;
+use crate::{BoxFuture, Result};
 
-impl Client<'_> {
+impl AsyncClient {
+    /// Gets the associated bundle transactions of a transaction
+    /// Validates the signatures, total sum, and bundle order
+    ///
+    /// * `transaction` - The transaction hash to search for
+    pub fn get_bundle(&self, transaction: &str) -> BoxFuture<Vec<Transaction>> {
+        if !iota_validation::is_hash(transaction) {
+            return Box::new(future::err(ClientError::InvalidInput(
+                "Invalid transaction.".into(),
+            )));
+        }
+        Box::new(
+            self.traverse_bundle(transaction, None, vec![])
+                .and_then(|bundle| {
+                    if !iota_validation::is_bundle(&bundle)? {
+                        return Err(format_err!("Invalid bundle provided.").into());
+                    }
+                    Ok(bundle)
+                }),
+        )
+    }
+}
+
+impl Client {
     /// Gets the associated bundle transactions of a transaction
     /// Validates the signatures, total sum, and bundle order
     ///
     /// * `transaction` - The transaction hash to search for
     pub fn get_bundle(&mut self, transaction: &str) -> Result<Vec<Transaction>> {
-        ensure!(
-            iota_validation::is_hash(&transaction),
-            "Invalid transaction."
-        );
-        let bundle = self.traverse_bundle(&transaction, None, vec![])?;
-        ensure!(
-            iota_validation::is_bundle(&bundle)?,
-            "Invalid bundle provided."
-        );
-        Ok(bundle)
+        self.runtime.block_on(self.inner.get_bundle(transaction))
     }
 }
@@ -1,8 +1,12 @@
+use iota_model::{Input, Inputs};
+use tokio::prelude::*;
+
+use crate::async_client::AsyncClient;
 use crate::client::Client;
-use crate::extended::get_new_address::new_address;
+use crate::error::ClientError;
 use crate::options::{GetBalancesOptions, GetNewAddressOptions};
-use crate::Result;
-use iota_model::{Input, Inputs};
+use crate::signer::{self, IntoSigner};
+use crate::{BoxFuture, Result};
 
 /// GetInputsOptions
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -17,80 +21,115 @@ pub struct GetInputsOptions {
     pub security: Option<usize>,
 }
 
-impl<'a> Client<'a> {
+impl AsyncClient {
     /// Given a seed, iterates through addresses looking for
     /// enough funds to meet specified threshold
     ///
-    /// * `seed` - The wallet seed to use
+    /// * `seed` - The wallet seed to use, or a `Signer`
     /// * `options` - See `GetInputsOptions`
-    pub fn get_inputs(&mut self, seed: &str, options: GetInputsOptions) -> Result<Inputs> {
-        ensure!(iota_validation::is_trytes(&seed), "Invalid seed.");
+    pub fn get_inputs(
+        &self,
+        seed: impl IntoSigner,
+        options: GetInputsOptions,
+    ) -> BoxFuture<Inputs> {
+        let signer = match seed.into_signer() {
+            Ok(signer) => signer,
+            Err(e) => return Box::new(future::err(e)),
+        };
         let start = options.start.unwrap_or(0);
         let security = options.security.unwrap_or(2);
+        let threshold = options.threshold;
+        let client = self.clone();
 
         if let Some(end) = options.end {
-            ensure!(
-                start <= end && end <= start + 500,
-                "Invalid inputs provided."
-            );
-            let mut all_addresses: Vec<String> = vec![];
-            for i in start..end {
-                all_addresses.push((new_address(&seed, security, i, false))?);
+            if start > end || end > start + 500 {
+                return Box::new(future::err(ClientError::InvalidInput(
+                    "Invalid inputs provided.".into(),
+                )));
             }
-            self.get_balance_and_format(&all_addresses, start, options.threshold, security)
+            Box::new(
+                signer::spawn_addresses(signer, security, start..end, false).and_then(
+                    move |all_addresses| {
+                        client.get_balance_and_format(all_addresses, start, threshold, security)
+                    },
+                ),
+            )
         } else {
-            let new_address = self.get_new_address(
-                seed,
-                false,
-                true,
-                GetNewAddressOptions {
-                    security: Some(security),
-                    index: Some(start),
-                    total: None,
-                },
-            )?;
-            self.get_balance_and_format(&new_address, start, options.threshold, security)
+            Box::new(
+                self.get_new_address(
+                    signer,
+                    false,
+                    true,
+                    GetNewAddressOptions {
+                        security: Some(security),
+                        index: Some(start),
+                        ..GetNewAddressOptions::default()
+                    },
+                )
+                .and_then(move |new_address| {
+                    client.get_balance_and_format(new_address, start, threshold, security)
+                }),
+            )
         }
     }
 
     fn get_balance_and_format(
-        &mut self,
-        addresses: &[String],
+        &self,
+        addresses: Vec<String>,
         start: usize,
         threshold: Option<i64>,
         security: usize,
-    ) -> Result<Inputs> {
-        let resp = self.get_balances(GetBalancesOptions {
-            addresses: addresses.to_owned(),
-            ..GetBalancesOptions::default()
-        })?;
-        let mut inputs = Inputs::default();
+    ) -> BoxFuture<Inputs> {
+        Box::new(
+            self.get_balances(GetBalancesOptions {
+                addresses: addresses.clone(),
+                ..GetBalancesOptions::default()
+            })
+            .and_then(move |resp| {
+                let mut inputs = Inputs::default();
 
-        let mut threshold_reached = threshold.is_none();
+                let mut threshold_reached = threshold.is_none();
 
-        let balances = resp.take_balances().unwrap_or_default();
-        for (i, address) in addresses.iter().enumerate() {
-            let balance: i64 = balances[i].clone().parse()?;
-            if balance > 0 {
-                let new_entry = Input {
-                    address: address.clone(),
-                    balance,
-                    key_index: start + i,
-                    security,
-                };
-                inputs.add(new_entry);
-                *inputs.total_balance_mut() += balance;
-                if let Some(threshold) = threshold {
-                    if inputs.total_balance() >= threshold {
-                        threshold_reached = true;
+                let balances = resp.take_balances().unwrap_or_default();
+                for (i, address) in addresses.iter().enumerate() {
+                    let balance: i64 = balances[i].clone().parse()?;
+                    if balance > 0 {
+                        let new_entry = Input {
+                            address: address.clone(),
+                            balance,
+                            key_index: start + i,
+                            security,
+                        };
+                        inputs.add(new_entry);
+                        *inputs.total_balance_mut() += balance;
+                        if let Some(threshold) = threshold {
+                            if inputs.total_balance() >= threshold {
+                                threshold_reached = true;
+                            }
+                        }
                     }
                 }
-            }
-        }
-        if threshold_reached {
-            Ok(inputs)
-        } else {
-            Err(format_err!("Not enough balance."))
-        }
+                if threshold_reached {
+                    Ok(inputs)
+                } else {
+                    Err(ClientError::Other(format_err!("Not enough balance.")))
+                }
+            }),
+        )
+    }
+}
+
+impl Client {
+    /// Given a seed, iterates through addresses looking for
+    /// enough funds to meet specified threshold
+    ///
+    /// * `seed` - The wallet seed to use, or a `Signer`
+    /// * `options` - See `GetInputsOptions`
+    pub fn get_inputs(
+        &mut self,
+        seed: impl IntoSigner,
+        options: GetInputsOptions,
+    ) -> Result<Inputs> {
+        self.runtime.block_on(self.inner.get_inputs(seed, options))
     }
 }
@@ -1,7 +1,15 @@
+use std::ops::Range;
+use std::sync::Arc;
+
+use tokio::prelude::future::Loop;
+use tokio::prelude::*;
+
+use crate::async_client::AsyncClient;
 use crate::client::Client;
+use crate::error::ClientError;
 use crate::options::FindTransactionsOptions;
-use crate::Result;
-use iota_conversion::Trinary;
+use crate::signer::{self, IntoSigner, SeedSigner, Signer};
+use crate::{BoxFuture, Result};
 
 /// GetNewAddressOptions
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -12,61 +20,189 @@ pub struct GetNewAddressOptions {
     pub index: Option<usize>,
     /// Number of addresses to generate. If total isn't provided, we generate until we find an unused address
     pub total: Option<usize>,
+    /// Number of addresses generated and checked together while looking for
+    /// an unused address, with one `were_addresses_spent_from` and one
+    /// `find_transactions` call, plus one `find_transactions` call per
+    /// address if any transaction references the window. Defaults to 1,
+    /// one address at a time.
+    pub window: Option<usize>,
+    /// Number of consecutive unused addresses after which the search stops.
+    /// The new address is the first one following the last used address.
+    /// Defaults to 1, stopping at the first unused address.
+    pub gap_limit: Option<usize>,
+}
+
+/// Addresses of a seed scanned while looking for an unused address
+#[derive(Debug, Default)]
+pub(crate) struct Discovery {
+    /// Every scanned address, from the start index up to and including
+    /// the first unused address following the last used one
+    pub(crate) addresses: Vec<String>,
+    /// Whether each address of `addresses` is used
+    pub(crate) used: Vec<bool>,
+    /// Hashes of the transactions touching the used addresses
+    pub(crate) hashes: Vec<String>,
 }
 
-impl<'a> Client<'a> {
+impl AsyncClient {
     /// Generates a new address
     ///
-    /// * `seed` - Seed used to generate new address
+    /// * `seed` - Seed used to generate new address, or a `Signer`
     /// * `checksum` - Whether or not to checksum address
     /// * `return_all` - Whether to return all generated addresses, or just the last one
     /// * `options` - See `GetNewAddressOptions`
     pub fn get_new_address(
-        &mut self,
-        seed: &str,
+        &self,
+        seed: impl IntoSigner,
         checksum: bool,
         return_all: bool,
         options: GetNewAddressOptions,
-    ) -> Result<Vec<String>> {
-        let mut index = options.index.unwrap_or_default();
+    ) -> BoxFuture<Vec<String>> {
+        let index = options.index.unwrap_or_default();
         let security = options.security.unwrap_or(2);
-        ensure!(iota_validation::is_trytes(&seed), "Invalid seed.");
-        ensure!(security > 0 && security < 4, "Invalid security.");
-
-        let mut all_addresses: Vec<String> = Vec::new();
+        let signer = match seed.into_signer() {
+            Ok(signer) => signer,
+            Err(e) => return Box::new(future::err(e)),
+        };
+        if !(1..=3).contains(&security) {
+            return Box::new(future::err(ClientError::InvalidInput(
+                "Invalid security.".into(),
+            )));
+        }
 
-        match options.total {
-            Some(total) => {
-                ensure!(total > 0, "Invalid total.");
-                for i in index..total {
-                    let address = new_address(&seed, security, i, checksum)?;
-                    all_addresses.push(address);
-                }
-                Ok(all_addresses)
+        if let Some(total) = options.total {
+            if total == 0 {
+                return Box::new(future::err(ClientError::InvalidInput(
+                    "Invalid total.".into(),
+                )));
             }
-            None => loop {
-                let new_address = new_address(&seed, security, index, checksum)?;
-                if return_all {
-                    all_addresses.push(new_address.clone());
-                }
-                index += 1;
-                let new_address_vec = vec![new_address];
-                let were_addr_spent = self.were_addresses_spent_from(&new_address_vec)?;
-                if !were_addr_spent.state(0) {
-                    let resp = self.find_transactions(FindTransactionsOptions {
-                        addresses: new_address_vec.clone(),
-                        ..FindTransactionsOptions::default()
-                    })?;
-                    if resp.take_hashes().unwrap_or_default().is_empty() {
-                        if return_all {
-                            return Ok(all_addresses);
-                        } else {
-                            return Ok(new_address_vec);
-                        }
+            return signer::spawn_addresses(signer, security, index..total, checksum);
+        }
+
+        Box::new(
+            self.discover_addresses(
+                signer,
+                security,
+                index,
+                options.window.unwrap_or(1),
+                options.gap_limit.unwrap_or(1),
+            )
+            .and_then(move |mut discovery| {
+                let mut addresses = if return_all {
+                    discovery.addresses
+                } else {
+                    discovery.addresses.split_off(discovery.addresses.len() - 1)
+                };
+                if checksum {
+                    for address in &mut addresses {
+                        *address = iota_signing::checksum::add_checksum(address)?;
                     }
                 }
+                Ok(addresses)
+            }),
+        )
+    }
+
+    /// Scans the addresses of a seed from key index `start`, `window`
+    /// addresses at a time, until `gap_limit` consecutive addresses are
+    /// unused. An address is used if it was spent from or if any
+    /// transaction references it.
+    pub(crate) fn discover_addresses(
+        &self,
+        signer: Arc<dyn Signer>,
+        security: usize,
+        start: usize,
+        window: usize,
+        gap_limit: usize,
+    ) -> BoxFuture<Discovery> {
+        let window = window.max(1);
+        let gap_limit = gap_limit.max(1);
+        let client = self.clone();
+        Box::new(future::loop_fn(
+            (start, 0, Discovery::default()),
+            move |(index, mut gap, mut discovery)| {
+                let client = client.clone();
+                signer::spawn_addresses(signer.clone(), security, index..index + window, false)
+                    .and_then(move |addresses| {
+                        client
+                            .were_addresses_spent_from(&addresses)
+                            .join(client.hashes_by_address(&addresses))
+                            .map(move |(spent, hashes)| (addresses, spent, hashes))
+                    })
+                    .map(move |(addresses, spent, hashes)| {
+                        let spent = spent.states().unwrap_or_default();
+                        for (i, (address, hashes)) in addresses.into_iter().zip(hashes).enumerate()
+                        {
+                            let used = spent.get(i).cloned().unwrap_or(false) || !hashes.is_empty();
+                            discovery.addresses.push(address);
+                            discovery.used.push(used);
+                            discovery.hashes.extend(hashes);
+                            gap = if used { 0 } else { gap + 1 };
+                            if gap == gap_limit {
+                                let keep = discovery.addresses.len() - gap_limit + 1;
+                                discovery.addresses.truncate(keep);
+                                discovery.used.truncate(keep);
+                                return Loop::Break(discovery);
+                            }
+                        }
+                        Loop::Continue((index + window, gap, discovery))
+                    })
             },
-        }
+        ))
+    }
+
+    /// Returns the hashes of the transactions referencing each address.
+    /// The addresses are looked up together first, and one at a time
+    /// only if any transaction references one of them.
+    fn hashes_by_address(&self, addresses: &[String]) -> BoxFuture<Vec<Vec<String>>> {
+        let client = self.clone();
+        let addresses = addresses.to_vec();
+        Box::new(
+            self.find_transactions(FindTransactionsOptions {
+                addresses: addresses.clone(),
+                ..FindTransactionsOptions::default()
+            })
+            .and_then(move |resp| -> BoxFuture<_> {
+                let hashes = resp.take_hashes().unwrap_or_default();
+                if hashes.is_empty() {
+                    return Box::new(future::ok(vec![Vec::new(); addresses.len()]));
+                }
+                if addresses.len() == 1 {
+                    return Box::new(future::ok(vec![hashes]));
+                }
+                Box::new(future::join_all(addresses.into_iter().map(
+                    move |address| {
+                        client
+                            .find_transactions(FindTransactionsOptions {
+                                addresses: vec![address],
+                                ..FindTransactionsOptions::default()
+                            })
+                            .map(|resp| resp.take_hashes().unwrap_or_default())
+                    },
+                )))
+            }),
+        )
+    }
+}
+
+impl Client {
+    /// Generates a new address
+    ///
+    /// * `seed` - Seed used to generate new address, or a `Signer`
+    /// * `checksum` - Whether or not to checksum address
+    /// * `return_all` - Whether to return all generated addresses, or just the last one
+    /// * `options` - See `GetNewAddressOptions`
+    pub fn get_new_address(
+        &mut self,
+        seed: impl IntoSigner,
+        checksum: bool,
+        return_all: bool,
+        options: GetNewAddressOptions,
+    ) -> Result<Vec<String>> {
+        self.runtime.block_on(
+            self.inner
+                .get_new_address(seed, checksum, return_all, options),
+        )
     }
 }
 
@@ -77,16 +213,29 @@ impl<'a> Client<'a> {
 /// * `index` - How many iterations of generating to skip
 /// * `checksum` - Whether or not to checksum address
 pub fn new_address(seed: &str, security: usize, index: usize, checksum: bool) -> Result<String> {
-    let key = iota_signing::key(&seed.trits(), index, security)?;
-    let digests = iota_signing::digests(&key)?;
-    let address_trits = iota_signing::address(&digests)?;
-    let mut address = address_trits.trytes()?;
+    let mut address = SeedSigner::new(seed)?.address(index, security)?;
     if checksum {
         address = iota_signing::checksum::add_checksum(&address)?;
     }
     Ok(address)
 }
 
+/// Generates the addresses of the key indexes in `indexes`, spread
+/// across the CPU cores
+///
+/// * `seed` - Seed used to generate the addresses
+/// * `security` - Security factor 1-3 with 3 being most secure
+/// * `indexes` - Key indexes of the addresses to generate
+/// * `checksum` - Whether or not to checksum the addresses
+pub fn new_addresses(
+    seed: &str,
+    security: usize,
+    indexes: Range<usize>,
+    checksum: bool,
+) -> Result<Vec<String>> {
+    signer::addresses(&SeedSigner::new(seed)?, security, indexes, checksum)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -128,4 +277,13 @@ mod tests {
             "AGSAAETPMSBCDOSNXFXIOBAE9MVEJCSWVP9PAULQ9VABOTWLDMXID9MXCCWQIWRTJBASWPIJDFUC9ISWD"
         );
     }
+
+    #[test]
+    fn test_new_addresses_in_order() {
+        let addresses = new_addresses(TEST_SEED, 2, 3..20, false).unwrap();
+        assert_eq!(addresses.len(), 17);
+        assert_eq!(addresses[2], new_address(TEST_SEED, 2, 5, false).unwrap());
+        assert_eq!(addresses[16], new_address(TEST_SEED, 2, 19, false).unwrap());
+        assert!(new_addresses(TEST_SEED, 2, 4..4, false).unwrap().is_empty());
+    }
 }
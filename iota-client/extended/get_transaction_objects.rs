@@ -0,0 +1,41 @@
+use iota_model::Transaction;
+use tokio::prelude::*;
+
+use crate::async_client::AsyncClient;
+use crate::client::Client;
+use crate::{BoxFuture, Result};
+
+impl AsyncClient {
+    /// Fetches and parses the transactions of the given hashes, in the
+    /// same order. Transactions the node doesn't know, returned as
+    /// all-9 trytes, are skipped. More hashes than
+    /// `batch_sizes.max_get_trytes` are split into concurrent requests.
+    ///
+    /// * `hashes` - The transaction hashes to fetch
+    pub fn get_transaction_objects(&self, hashes: &[String]) -> BoxFuture<Vec<Transaction>> {
+        if hashes.is_empty() {
+            return Box::new(future::ok(Vec::new()));
+        }
+        Box::new(self.get_trytes(hashes).and_then(|resp| {
+            resp.take_trytes()
+                .unwrap_or_default()
+                .iter()
+                .filter(|trytes| !iota_validation::is_nine_trytes(trytes))
+                .map(|trytes| Ok(trytes.parse()?))
+                .collect::<Result<Vec<Transaction>>>()
+        }))
+    }
+}
+
+impl Client {
+    /// Fetches and parses the transactions of the given hashes, in the
+    /// same order. Transactions the node doesn't know, returned as
+    /// all-9 trytes, are skipped. More hashes than
+    /// `batch_sizes.max_get_trytes` are split into concurrent requests.
+    ///
+    /// * `hashes` - The transaction hashes to fetch
+    pub fn get_transaction_objects(&mut self, hashes: &[String]) -> Result<Vec<Transaction>> {
+        self.runtime
+            .block_on(self.inner.get_transaction_objects(hashes))
+    }
+}
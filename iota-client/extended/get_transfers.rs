@@ -0,0 +1,246 @@
+use iota_model::Transaction;
+use tokio::prelude::*;
+
+use crate::async_client::AsyncClient;
+use crate::client::Client;
+use crate::options::{GetAccountDataOptions, GetInclusionStatesOptions};
+use crate::signer::IntoSigner;
+use crate::{BoxFuture, Result};
+
+/// GetTransfersOptions
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GetTransfersOptions {
+    /// The index of the first address to scan, 0 by default
+    pub start: Option<usize>,
+    /// The security to use for address generation, 2 by default
+    pub security: Option<usize>,
+    /// Number of addresses checked together, see `GetAccountDataOptions`
+    pub window: Option<usize>,
+    /// Number of consecutive unused addresses after which the scan stops,
+    /// see `GetAccountDataOptions`
+    pub gap_limit: Option<usize>,
+}
+
+/// Whether a bundle moved funds out of or into a seed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TransferDirection {
+    /// The bundle spends from at least one address of the seed
+    Sent,
+    /// The bundle only deposits to addresses of the seed
+    Received,
+}
+
+/// A bundle touching the addresses of a seed, with all its attachments
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccountTransfer {
+    /// The bundle hash
+    pub bundle: String,
+    /// Transactions of the confirmed attachment if any, otherwise
+    /// of the first attachment found, in bundle order
+    pub transactions: Vec<Transaction>,
+    /// Tail transaction hashes of every valid attachment of the bundle
+    pub tails: Vec<String>,
+    /// Whether funds were sent or received from the seed's perspective
+    pub direction: TransferDirection,
+    /// Net value the bundle moves to the seed's addresses, negative when sent
+    pub value: i64,
+    /// Whether any attachment of the bundle is confirmed
+    pub confirmed: bool,
+}
+
+impl AsyncClient {
+    /// Returns the transfer history of a seed: every valid bundle touching
+    /// its addresses, with reattachments grouped by bundle hash, marked as
+    /// sent or received and with its confirmation state. Attachments that
+    /// can't be traversed or fail `iota_validation::is_bundle` are left
+    /// out. Transfers are sorted by bundle timestamp, oldest first.
+    ///
+    /// * `seed` - The wallet seed to use, or a `Signer`
+    /// * `options` - See `GetTransfersOptions`
+    pub fn get_transfers(
+        &self,
+        seed: impl IntoSigner,
+        options: GetTransfersOptions,
+    ) -> BoxFuture<Vec<AccountTransfer>> {
+        let client = self.clone();
+        Box::new(
+            self.get_account_data(
+                seed,
+                GetAccountDataOptions {
+                    start: options.start,
+                    security: options.security,
+                    window: options.window,
+                    gap_limit: options.gap_limit,
+                },
+            )
+            .and_then(move |account| {
+                let addresses: Vec<String> = account
+                    .addresses
+                    .into_iter()
+                    .map(|input| input.address)
+                    .collect();
+                let bundles: Vec<_> = account
+                    .transfers
+                    .iter()
+                    .map(|tail| client.traverse_bundle(tail, None, vec![]).then(attachment))
+                    .collect();
+                future::join_all(bundles)
+                    .map(move |bundles| group_attachments(bundles, &addresses))
+                    .and_then(move |transfers| client.with_inclusion_states(transfers))
+            }),
+        )
+    }
+
+    /// Marks transfers with a confirmed attachment as confirmed, and
+    /// keeps the transactions of that attachment
+    fn with_inclusion_states(
+        &self,
+        mut transfers: Vec<AccountTransfer>,
+    ) -> BoxFuture<Vec<AccountTransfer>> {
+        let tails: Vec<String> = transfers
+            .iter()
+            .flat_map(|transfer| transfer.tails.iter().cloned())
+            .collect();
+        if tails.is_empty() {
+            return Box::new(future::ok(transfers));
+        }
+        let client = self.clone();
+        let traverse_client = self.clone();
+        Box::new(
+            self.get_node_info()
+                .and_then(move |info| {
+                    client
+                        .get_inclusion_states(GetInclusionStatesOptions {
+                            transactions: tails.clone(),
+                            tips: vec![info.latest_solid_subtangle_milestone().to_string()],
+                        })
+                        .map(move |resp| {
+                            let states = resp.states().clone().unwrap_or_default();
+                            tails
+                                .into_iter()
+                                .zip(states)
+                                .filter(|(_, confirmed)| *confirmed)
+                                .map(|(tail, _)| tail)
+                                .collect::<Vec<String>>()
+                        })
+                })
+                .and_then(move |confirmed_tails| {
+                    let reloads: Vec<_> = transfers
+                        .iter_mut()
+                        .filter_map(|transfer| {
+                            let tail = transfer
+                                .tails
+                                .iter()
+                                .find(|tail| confirmed_tails.contains(tail))?;
+                            transfer.confirmed = true;
+                            if transfer.transactions[0].hash == *tail {
+                                None
+                            } else {
+                                Some(traverse_client.traverse_bundle(tail, None, vec![]))
+                            }
+                        })
+                        .collect();
+                    future::join_all(reloads).map(move |confirmed_bundles| {
+                        for bundle in confirmed_bundles {
+                            if let Some(transfer) = transfers
+                                .iter_mut()
+                                .find(|transfer| transfer.bundle == bundle[0].bundle)
+                            {
+                                transfer.transactions = bundle;
+                            }
+                        }
+                        transfers
+                    })
+                }),
+        )
+    }
+}
+
+/// Returns the transactions of a traversed attachment, or none if the
+/// attachment is invalid. Only failures of the node itself are errors, so
+/// that junk attached to an address can't break the whole history.
+fn attachment(traversed: Result<Vec<Transaction>>) -> Result<Vec<Transaction>> {
+    match traversed {
+        Err(e) if e.is_transport() || e.is_rejected() => Err(e),
+        traversed => Ok(traversed.unwrap_or_default()),
+    }
+}
+
+/// Groups valid attachments by bundle hash and categorizes them from the
+/// perspective of the owner of `addresses`
+fn group_attachments(bundles: Vec<Vec<Transaction>>, addresses: &[String]) -> Vec<AccountTransfer> {
+    let mut transfers: Vec<AccountTransfer> = Vec::new();
+    for bundle in bundles {
+        if bundle.is_empty() || !iota_validation::is_bundle(&bundle).unwrap_or(false) {
+            continue;
+        }
+        if let Some(transfer) = transfers
+            .iter_mut()
+            .find(|transfer| transfer.bundle == bundle[0].bundle)
+        {
+            transfer.tails.push(bundle[0].hash.clone());
+            continue;
+        }
+        let own: Vec<&Transaction> = bundle
+            .iter()
+            .filter(|tx| addresses.contains(&tx.address))
+            .collect();
+        let direction = if own.iter().any(|tx| tx.value < 0) {
+            TransferDirection::Sent
+        } else {
+            TransferDirection::Received
+        };
+        transfers.push(AccountTransfer {
+            bundle: bundle[0].bundle.clone(),
+            tails: vec![bundle[0].hash.clone()],
+            value: own.iter().map(|tx| tx.value).sum(),
+            direction,
+            confirmed: false,
+            transactions: bundle,
+        });
+    }
+    transfers.sort_by_key(|transfer| transfer.transactions[0].timestamp);
+    transfers
+}
+
+impl Client {
+    /// Returns the transfer history of a seed: every valid bundle touching
+    /// its addresses, with reattachments grouped by bundle hash, marked as
+    /// sent or received and with its confirmation state. Attachments that
+    /// can't be traversed or fail `iota_validation::is_bundle` are left
+    /// out. Transfers are sorted by bundle timestamp, oldest first.
+    ///
+    /// * `seed` - The wallet seed to use, or a `Signer`
+    /// * `options` - See `GetTransfersOptions`
+    pub fn get_transfers(
+        &mut self,
+        seed: impl IntoSigner,
+        options: GetTransfersOptions,
+    ) -> Result<Vec<AccountTransfer>> {
+        self.runtime
+            .block_on(self.inner.get_transfers(seed, options))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ClientError;
+
+    #[test]
+    fn test_invalid_attachments_are_skipped() {
+        let invalid = Err(ClientError::Other(format_err!(
+            "Bundle transactions not visible"
+        )));
+        assert!(attachment(invalid).unwrap().is_empty());
+        let down = Err(ClientError::Transport(format_err!("node is down")));
+        assert!(attachment(down).is_err());
+
+        let junk = Transaction {
+            address: "A".repeat(81),
+            last_index: 1,
+            ..Transaction::default()
+        };
+        assert!(group_attachments(vec![vec![junk], vec![]], &["A".repeat(81)]).is_empty());
+    }
+}
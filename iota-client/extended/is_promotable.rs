@@ -0,0 +1,106 @@
+use std::sync::Arc;
+
+use iota_model::Transaction;
+use tokio::prelude::*;
+
+use crate::async_client::AsyncClient;
+use crate::client::Client;
+use crate::clock::{Clock, SystemClock};
+use crate::error::ClientError;
+use crate::{BoxFuture, Result};
+
+/// Average time between two milestones, in milliseconds
+const MILESTONE_INTERVAL: i64 = 2 * 60 * 1000;
+/// Time for a transaction to reach a milestone issuer, in milliseconds
+const ONE_WAY_DELAY: i64 = 60 * 1000;
+/// Number of milestones below which a transaction can't be referenced anymore
+const MAX_DEPTH: i64 = 6;
+
+/// Returns true if a transaction attached at `attachment_timestamp`, in
+/// milliseconds, is recent enough to still be referenced by a milestone
+pub(crate) fn is_above_max_depth(attachment_timestamp: i64, now: i64) -> bool {
+    attachment_timestamp < now
+        && now - attachment_timestamp < MAX_DEPTH * MILESTONE_INTERVAL - ONE_WAY_DELAY
+}
+
+impl AsyncClient {
+    /// Checks if a tail transaction can be promoted: its bundle must be
+    /// consistent with the ledger, and it must have been attached recently
+    /// enough to still be referenced by a milestone. Stuck transfers that
+    /// are not promotable should be reattached with `replay_bundle`.
+    ///
+    /// * `tail` - The tail transaction hash of the bundle to check
+    pub fn is_promotable(&self, tail: &str) -> BoxFuture<bool> {
+        self.is_promotable_at(tail, Arc::new(SystemClock))
+    }
+
+    /// Checks if a tail transaction can be promoted at the time of `clock`,
+    /// see `is_promotable`
+    ///
+    /// * `tail` - The tail transaction hash of the bundle to check
+    /// * `clock` - Tells the current time, see `Clock`
+    pub fn is_promotable_at(&self, tail: &str, clock: Arc<dyn Clock>) -> BoxFuture<bool> {
+        if !iota_validation::is_hash(tail) {
+            return Box::new(future::err(ClientError::InvalidInput(format!(
+                "Provided tail is not valid: {:?}",
+                tail
+            ))));
+        }
+        let client = self.clone();
+        let tail = tail.to_string();
+        Box::new(
+            self.check_consistency(std::slice::from_ref(&tail))
+                .and_then(move |consistency| -> BoxFuture<bool> {
+                    if !consistency.state() {
+                        return Box::new(future::ok(false));
+                    }
+                    Box::new(client.get_trytes(&[tail]).and_then(move |resp| {
+                        let trytes = resp.take_trytes().unwrap_or_default();
+                        let tx: Transaction = match trytes.first() {
+                            Some(trytes) => trytes.parse()?,
+                            None => return Err(format_err!("Tail transaction not found").into()),
+                        };
+                        Ok(is_above_max_depth(
+                            tx.attachment_timestamp,
+                            clock.now_millis(),
+                        ))
+                    }))
+                }),
+        )
+    }
+}
+
+impl Client {
+    /// Checks if a tail transaction can be promoted: its bundle must be
+    /// consistent with the ledger, and it must have been attached recently
+    /// enough to still be referenced by a milestone. Stuck transfers that
+    /// are not promotable should be reattached with `replay_bundle`.
+    ///
+    /// * `tail` - The tail transaction hash of the bundle to check
+    pub fn is_promotable(&mut self, tail: &str) -> Result<bool> {
+        self.runtime.block_on(self.inner.is_promotable(tail))
+    }
+
+    /// Checks if a tail transaction can be promoted at the time of `clock`,
+    /// see `is_promotable`
+    ///
+    /// * `tail` - The tail transaction hash of the bundle to check
+    /// * `clock` - Tells the current time, see `Clock`
+    pub fn is_promotable_at(&mut self, tail: &str, clock: Arc<dyn Clock>) -> Result<bool> {
+        self.runtime
+            .block_on(self.inner.is_promotable_at(tail, clock))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_above_max_depth() {
+        let now = 1_000_000_000;
+        assert!(is_above_max_depth(now - 60 * 1000, now));
+        assert!(!is_above_max_depth(now - 11 * 60 * 1000, now));
+        assert!(!is_above_max_depth(now + 1, now));
+    }
+}
@@ -1,7 +1,17 @@
+pub mod await_confirmation;
+pub mod find_transaction_objects;
+pub mod get_account_data;
 pub mod get_bundle;
 pub mod get_inputs;
 pub mod get_new_address;
+pub mod get_transaction_objects;
+pub mod get_transfers;
+pub mod is_promotable;
 pub mod prepare_transfers;
+pub mod promote_transaction;
+pub mod replay_bundle;
+pub mod send_payouts;
+pub mod send_signed_bundle;
 pub mod send_transfers;
 pub mod send_trytes;
 pub mod store_and_broadcast;
@@ -1,17 +1,20 @@
-use chrono::prelude::*;
+use std::sync::Arc;
 
-use iota_conversion::Trinary;
 use iota_model::{Bundle, BundleEntry, Inputs, Transfer};
+use tokio::prelude::*;
 
-use std::cmp;
-use std::convert::TryInto;
-
+use crate::async_client::AsyncClient;
 use crate::client::Client;
+use crate::clock::{Clock, SystemClock};
+use crate::error::ClientError;
+use crate::ledger::SpentLedger;
 use crate::options::{GetBalancesOptions, GetInputsOptions, GetNewAddressOptions};
-use crate::Result;
+use crate::selector::{ConsolidateAll, InputSelector, OldestFirst};
+use crate::signer::{self, IntoSigner, Signer};
+use crate::{BoxFuture, Result};
 
 /// PrepareTransfersOptions
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug)]
 pub struct PrepareTransfersOptions<'a, 'b> {
     /// Optional inputs to use if you're sending iota
     pub inputs: Option<Inputs>,
@@ -21,6 +24,16 @@ pub struct PrepareTransfersOptions<'a, 'b> {
     pub security: usize,
     /// Optional key to use if you want to hmac the transfers
     pub hmac_key: Option<&'b str>,
+    /// Chooses the inputs to spend, see `InputSelector`
+    pub selector: Arc<dyn InputSelector>,
+    /// Addresses never to spend from, with or without checksum, such as
+    /// addresses with pending outgoing bundles
+    pub exclude: Vec<String>,
+    /// Records every input before it is signed, and refuses inputs and
+    /// remainder addresses already recorded, see `SpentLedger`
+    pub ledger: Option<Arc<dyn SpentLedger>>,
+    /// Stamps the timestamps of the bundle entries, see `Clock`
+    pub clock: Arc<dyn Clock>,
 }
 
 impl<'a, 'b> Default for PrepareTransfersOptions<'a, 'b> {
@@ -30,346 +43,509 @@ impl<'a, 'b> Default for PrepareTransfersOptions<'a, 'b> {
             remainder_address: None,
             security: 3,
             hmac_key: None,
+            selector: Arc::new(OldestFirst),
+            exclude: Vec::new(),
+            ledger: None,
+            clock: Arc::new(SystemClock),
         }
     }
 }
 
-/// AddRemainderOptions
-#[derive(Clone, Debug, PartialEq)]
-pub struct AddRemainderOptions<'a, 'b, 'c, 'd> {
-    /// The tryte-encoded seed. It should be noted that this seed is not transferred.
-    pub seed: &'a str,
-    /// The tag to add to each bundle entry
-    pub tag: &'b str,
-    /// The address used for sending the remainder value (of the last input)
-    pub remainder_address: Option<&'c str>,
-    /// The signature fragments (message), used for signing. Should be 2187 characters long, can be padded with 9s.
-    pub signature_fragments: Vec<String>,
-    /// Check if hmac is added
-    pub added_hmac: bool,
-    /// Optional key to use if you want to hmac the transfers
-    pub hmac_key: Option<&'d str>,
-    /// Security to use when generating addresses (1-3)
-    pub security: usize,
-}
-
-impl<'a> Client<'a> {
+impl AsyncClient {
     /// Prepares a slice of transfers and converts them into a
-    /// slice of tryte-encoded strings
+    /// slice of tryte-encoded strings. Inputs are looked up with
+    /// `get_inputs`, or their balances are refreshed when given, and are
+    /// chosen by the `selector`. A remainder address following every
+    /// input is generated when none is given, before the bundle is built
+    /// and signed with `prepare_transfers_offline`. Fails if the node has
+    /// seen a spend from a selected input or from the remainder address.
     ///
-    /// * `seed` - The wallet seed to use
+    /// * `seed` - The wallet seed to use, or a `Signer`
     /// * `transfers` - A slice of transfers to prepare
     /// * `options` - See `PrepareTransfersOptions`
     pub fn prepare_transfers(
-        &mut self,
-        seed: &str,
+        &self,
+        seed: impl IntoSigner,
         transfers: impl Into<Vec<Transfer>>,
         options: PrepareTransfersOptions<'_, '_>,
-    ) -> Result<Vec<String>> {
-        let mut transfers = transfers.into();
-        let mut add_hmac = false;
-        let mut added_hmac = false;
-
-        ensure!(iota_validation::is_trytes(&seed), "Invalid seed.");
-        if let Some(hmac_key) = &options.hmac_key {
-            ensure!(iota_validation::is_trytes(&hmac_key), "Invalid trytes.");
-            add_hmac = true;
+    ) -> BoxFuture<Vec<String>> {
+        let transfers = transfers.into();
+        let signer = match seed.into_signer() {
+            Ok(signer) => signer,
+            Err(e) => return Box::new(future::err(e)),
+        };
+        let total_value: i64 = transfers.iter().map(|transfer| transfer.value).sum();
+        if total_value <= 0 {
+            return Box::new(future::result(prepare_transfers_offline(
+                signer,
+                transfers,
+                PrepareTransfersOptions {
+                    inputs: None,
+                    ..options
+                },
+            )));
         }
-        for transfer in &mut transfers {
-            if add_hmac && transfer.value > 0 {
-                transfer.message = "9".repeat(243) + &transfer.message;
-                added_hmac = true;
-            }
-            if transfer.address.len() == 90 {
-                ensure!(
-                    iota_signing::checksum::is_valid_checksum(&transfer.address)?,
-                    "Invalid address."
-                );
-            }
-            transfer.address = iota_signing::checksum::remove_checksum(&transfer.address);
-        }
-        ensure!(
-            iota_validation::is_transfers_collection_valid(&transfers),
-            "Invalid transfers."
-        );
+
         let security = options.security;
-        let mut bundle = Bundle::default();
-        let mut total_value = 0;
-        let mut signature_fragments: Vec<String> = Vec::new();
-        let mut tag = String::new();
+        let inputs = self.candidate_inputs(signer.clone(), options.inputs, total_value, security);
 
-        for transfer in transfers {
-            let mut signature_message_length = 1;
-            if transfer.message.len() > iota_constants::MESSAGE_LENGTH {
-                signature_message_length += (transfer.message.len() as f64
-                    / iota_constants::MESSAGE_LENGTH as f64)
-                    .floor() as usize;
-                let mut msg_copy = transfer.message.to_string();
-                while !msg_copy.is_empty() {
-                    let mut fragment = msg_copy
-                        .chars()
-                        .take(iota_constants::MESSAGE_LENGTH)
-                        .collect();
-                    msg_copy = msg_copy
-                        .chars()
-                        .skip(iota_constants::MESSAGE_LENGTH)
-                        .collect();
-                    iota_model::right_pad_string(
-                        &mut fragment,
-                        iota_constants::MESSAGE_LENGTH,
-                        '9',
-                    );
-                    signature_fragments.push(fragment);
-                }
-            } else {
-                let mut fragment = if !transfer.message.is_empty() {
-                    transfer.message.chars().take(2187).collect()
-                } else {
-                    String::new()
-                };
-                iota_model::right_pad_string(&mut fragment, iota_constants::MESSAGE_LENGTH, '9');
-                signature_fragments.push(fragment);
-            }
-            tag = transfer.tag;
-            iota_model::right_pad_string(&mut tag, iota_constants::TAG_LENGTH, '9');
-            bundle.add_entry(BundleEntry {
-                signature_message_length,
-                address: &transfer.address,
-                value: transfer.value,
-                tag: &tag,
-                timestamp: Utc::now().timestamp(),
-            });
-            total_value += transfer.value;
-        }
+        let client = self.clone();
+        let remainder_address = options.remainder_address.map(str::to_string);
+        let hmac_key = options.hmac_key.map(str::to_string);
+        let selector = options.selector;
+        let exclude = options.exclude;
+        let ledger = options.ledger;
+        let clock = options.clock;
+        Box::new(
+            inputs.and_then(move |candidates| -> BoxFuture<Vec<String>> {
+                let (inputs, remainder) =
+                    match select_inputs(&candidates, total_value, &*selector, &exclude) {
+                        Ok(selected) => selected,
+                        Err(e) => return Box::new(future::err(e)),
+                    };
+                let remainder_address: BoxFuture<Option<String>> =
+                    match (remainder, remainder_address) {
+                        (0, _) => Box::new(future::ok(None)),
+                        (_, Some(remainder_address)) => {
+                            Box::new(future::ok(Some(remainder_address)))
+                        }
+                        (_, None) => {
+                            let start_index = candidates
+                                .inputs_list()
+                                .iter()
+                                .map(|input| input.key_index)
+                                .max()
+                                .unwrap_or_default()
+                                + 1;
+                            Box::new(
+                                client
+                                    .get_new_address(
+                                        signer.clone(),
+                                        false,
+                                        false,
+                                        GetNewAddressOptions {
+                                            security: Some(security),
+                                            index: Some(start_index),
+                                            ..GetNewAddressOptions::default()
+                                        },
+                                    )
+                                    .map(|mut addresses| Some(addresses.remove(0))),
+                            )
+                        }
+                    };
+                Box::new(remainder_address.and_then(move |remainder_address| {
+                    let remainder_addresses: Vec<String> =
+                        remainder_address.iter().cloned().collect();
+                    client
+                        .check_unspent(&inputs, &remainder_addresses)
+                        .and_then(move |_| {
+                            prepare_transfers_offline(
+                                signer,
+                                transfers,
+                                PrepareTransfersOptions {
+                                    inputs: Some(inputs),
+                                    remainder_address: remainder_address.as_deref(),
+                                    security,
+                                    hmac_key: hmac_key.as_deref(),
+                                    selector: Arc::new(ConsolidateAll),
+                                    exclude: Vec::new(),
+                                    ledger,
+                                    clock,
+                                },
+                            )
+                        })
+                }))
+            }),
+        )
+    }
 
-        if total_value > 0 {
-            match options.inputs {
-                Some(inputs) => {
-                    let input_addresses: Vec<String> = inputs
-                        .inputs_list()
-                        .iter()
-                        .map(|input| input.address.to_string())
-                        .collect();
-                    let resp = self.get_balances(GetBalancesOptions {
+    /// Looks up the inputs holding funds with `get_inputs`, or refreshes
+    /// the balances of the given `inputs`, failing if they don't cover
+    /// `total_value`
+    pub(crate) fn candidate_inputs(
+        &self,
+        signer: Arc<dyn Signer>,
+        inputs: Option<Inputs>,
+        total_value: i64,
+        security: usize,
+    ) -> BoxFuture<Inputs> {
+        match inputs {
+            Some(inputs) => {
+                let input_addresses: Vec<String> = inputs
+                    .inputs_list()
+                    .iter()
+                    .map(|input| input.address.to_string())
+                    .collect();
+                Box::new(
+                    self.get_balances(GetBalancesOptions {
                         addresses: input_addresses,
                         ..GetBalancesOptions::default()
-                    })?;
-                    let mut confirmed_inputs = Inputs::default();
-                    let balances = resp.take_balances().unwrap_or_default();
-                    for (i, balance) in balances.iter().enumerate() {
-                        let b: i64 = balance.parse()?;
-                        if b > 0 {
-                            *confirmed_inputs.total_balance_mut() += b;
-                            let mut confirmed_input = inputs.inputs_list()[i].clone();
-                            confirmed_input.balance = b;
-                            confirmed_inputs.add(confirmed_input);
-                            if confirmed_inputs.total_balance() >= total_value {
-                                break;
+                    })
+                    .and_then(move |resp| {
+                        let mut confirmed_inputs = Inputs::default();
+                        let balances = resp.take_balances().unwrap_or_default();
+                        for (i, balance) in balances.iter().enumerate() {
+                            let b: i64 = balance.parse()?;
+                            if b > 0 {
+                                *confirmed_inputs.total_balance_mut() += b;
+                                let mut confirmed_input = inputs.inputs_list()[i].clone();
+                                confirmed_input.balance = b;
+                                confirmed_inputs.add(confirmed_input);
                             }
                         }
+                        if total_value > confirmed_inputs.total_balance() {
+                            return Err(format_err!("Not enough balance.").into());
+                        }
+                        Ok(confirmed_inputs)
+                    }),
+                )
+            }
+            None => self.get_inputs(
+                signer,
+                GetInputsOptions {
+                    start: None,
+                    end: None,
+                    threshold: Some(total_value),
+                    security: Some(security),
+                },
+            ),
+        }
+    }
+
+    /// Fails if the node has seen a spend from any of `inputs`, or from
+    /// any of the remainder addresses
+    pub(crate) fn check_unspent(
+        &self,
+        inputs: &Inputs,
+        remainder_addresses: &[String],
+    ) -> BoxFuture<()> {
+        let mut addresses: Vec<String> = inputs
+            .inputs_list()
+            .iter()
+            .map(|input| input.address.clone())
+            .collect();
+        addresses.extend_from_slice(remainder_addresses);
+        if addresses.is_empty() {
+            return Box::new(future::ok(()));
+        }
+        let input_count = inputs.inputs_list().len();
+        Box::new(
+            self.were_addresses_spent_from(&addresses)
+                .and_then(move |resp| {
+                    let spent = resp.states().unwrap_or_default();
+                    if spent.len() != addresses.len() {
+                        return Err(format_err!("Spent states of the inputs are missing").into());
                     }
-                    ensure!(
-                        total_value <= confirmed_inputs.total_balance(),
-                        "Not enough balance."
+                    ensure_input!(
+                        !spent[..input_count].contains(&true),
+                        "{}",
+                        iota_constants::PRIVATE_KEY_REUSE_ERROR
                     );
-                    self.add_remainder(
-                        &confirmed_inputs,
-                        &mut bundle,
-                        AddRemainderOptions {
-                            seed,
-                            tag: &tag,
-                            remainder_address: options.remainder_address,
-                            signature_fragments,
-                            added_hmac,
-                            hmac_key: options.hmac_key,
-                            security,
-                        },
-                    )
-                }
-                None => {
-                    let inputs = self.get_inputs(
-                        &seed,
-                        GetInputsOptions {
-                            start: None,
-                            end: None,
-                            threshold: Some(total_value),
-                            security: Some(security),
-                        },
-                    )?;
-                    self.add_remainder(
-                        &inputs,
-                        &mut bundle,
-                        AddRemainderOptions {
-                            seed,
-                            tag: &tag,
-                            remainder_address: options.remainder_address,
-                            signature_fragments,
-                            added_hmac,
-                            hmac_key: options.hmac_key,
-                            security,
-                        },
-                    )
-                }
+                    ensure_input!(
+                        !spent[input_count..].contains(&true),
+                        "{}",
+                        iota_constants::SENDING_TO_USED_ADDRESS_ERROR
+                    );
+                    Ok(())
+                }),
+        )
+    }
+}
+
+/// Prepares a slice of transfers and converts them into a slice of
+/// tryte-encoded strings without any network access, so that bundles can
+/// be built and signed on an air-gapped machine. The inputs to spend are
+/// chosen among the given ones by the `selector`.
+///
+/// * `seed` - The wallet seed to use, or a `Signer`
+/// * `transfers` - A slice of transfers to prepare
+/// * `options` - See `PrepareTransfersOptions`
+///
+/// For transfers moving value, `inputs` must be given with their
+/// balances, and `remainder_address` is required unless the selected
+/// inputs match the transfers exactly. Sending to an input fails, and so
+/// does spending from an input or sending the remainder to an address
+/// recorded in the `ledger`. Inputs are recorded in the `ledger` before
+/// they are signed.
+pub fn prepare_transfers_offline(
+    seed: impl IntoSigner,
+    transfers: impl Into<Vec<Transfer>>,
+    options: PrepareTransfersOptions<'_, '_>,
+) -> Result<Vec<String>> {
+    let signer = seed.into_signer()?;
+    let Prepared {
+        mut bundle,
+        inputs,
+        remainder_address,
+        added_hmac,
+    } = build_bundle(transfers.into(), &options)?;
+    if let Some(ledger) = &options.ledger {
+        let addresses: Vec<String> = inputs
+            .inputs_list()
+            .iter()
+            .map(|input| input.address.clone())
+            .collect();
+        ledger.record(&addresses, remainder_address.as_deref())?;
+    }
+    signer::sign_bundle(&*signer, inputs.inputs_list(), &mut bundle)?;
+    if added_hmac {
+        let hmac = iota_signing::HMAC::new(options.hmac_key.unwrap_or_default());
+        hmac.add_hmac(&mut bundle)?;
+    }
+    Ok(bundle.trytes()?)
+}
+
+/// Builds the bundle of a slice of transfers without signing it, and
+/// exports it with `Bundle::to_unsigned_json` so that it can be signed on
+/// a cold wallet holding the seed. Works like `prepare_transfers_offline`,
+/// except that HMAC is not supported.
+///
+/// The offline host reads the bundle with `Bundle::from_unsigned_json`,
+/// checks its `outputs`, signs it with `iota_signing::sign_bundle` and
+/// exports it with `Bundle::to_signed_json`. The online host then reads it
+/// with `Bundle::from_signed_json` and broadcasts it with `send_signed_bundle`.
+///
+/// * `transfers` - A slice of transfers to prepare
+/// * `options` - See `PrepareTransfersOptions`
+pub fn prepare_unsigned_bundle(
+    transfers: impl Into<Vec<Transfer>>,
+    options: PrepareTransfersOptions<'_, '_>,
+) -> Result<String> {
+    ensure_input!(
+        options.hmac_key.is_none(),
+        "HMAC is not supported for unsigned bundles."
+    );
+    let prepared = build_bundle(transfers.into(), &options)?;
+    Ok(prepared
+        .bundle
+        .to_unsigned_json(prepared.inputs.inputs_list())?)
+}
+
+/// A finalized bundle waiting for its signatures
+struct Prepared {
+    bundle: Bundle,
+    /// The inputs spent by the bundle, without checksum
+    inputs: Inputs,
+    /// The address receiving the remainder, without checksum
+    remainder_address: Option<String>,
+    /// Whether room was made for an HMAC
+    added_hmac: bool,
+}
+
+/// Builds and finalizes the bundle of `transfers`, leaving the signatures
+/// of its inputs empty
+fn build_bundle(
+    mut transfers: Vec<Transfer>,
+    options: &PrepareTransfersOptions<'_, '_>,
+) -> Result<Prepared> {
+    let mut add_hmac = false;
+    let mut added_hmac = false;
+
+    if let Some(hmac_key) = &options.hmac_key {
+        ensure_input!(iota_validation::is_trytes(hmac_key), "Invalid trytes.");
+        add_hmac = true;
+    }
+    for transfer in &mut transfers {
+        if add_hmac && transfer.value > 0 {
+            transfer.message = "9".repeat(243) + &transfer.message;
+            added_hmac = true;
+        }
+        if transfer.address.len() == 90 {
+            ensure_input!(
+                iota_signing::checksum::is_valid_checksum(&transfer.address)?,
+                "Invalid address."
+            );
+        }
+        transfer.address = iota_signing::checksum::remove_checksum(&transfer.address);
+    }
+    ensure_input!(
+        iota_validation::is_transfers_collection_valid(&transfers),
+        "Invalid transfers."
+    );
+    let mut bundle = Bundle::default();
+    let mut total_value = 0;
+    let mut signature_fragments: Vec<String> = Vec::new();
+    let mut tag = String::new();
+
+    for transfer in transfers {
+        let signature_message_length = message_length(&transfer.message);
+        if transfer.message.len() > iota_constants::MESSAGE_LENGTH {
+            let mut msg_copy = transfer.message.to_string();
+            while !msg_copy.is_empty() {
+                let mut fragment = msg_copy
+                    .chars()
+                    .take(iota_constants::MESSAGE_LENGTH)
+                    .collect();
+                msg_copy = msg_copy
+                    .chars()
+                    .skip(iota_constants::MESSAGE_LENGTH)
+                    .collect();
+                iota_model::right_pad_string(&mut fragment, iota_constants::MESSAGE_LENGTH, '9');
+                signature_fragments.push(fragment);
             }
         } else {
-            bundle.reset_indexes();
-            bundle.finalize()?;
-            bundle.add_trytes(&signature_fragments);
-            let mut bundle_trytes: Vec<String> = Vec::new();
-            for b in bundle.iter().rev() {
-                bundle_trytes.push(b.try_into()?);
-            }
-            Ok(bundle_trytes)
+            let mut fragment = if !transfer.message.is_empty() {
+                transfer.message.chars().take(2187).collect()
+            } else {
+                String::new()
+            };
+            iota_model::right_pad_string(&mut fragment, iota_constants::MESSAGE_LENGTH, '9');
+            signature_fragments.push(fragment);
         }
+        tag = transfer.tag;
+        iota_model::right_pad_string(&mut tag, iota_constants::TAG_LENGTH, '9');
+        bundle.add_entry(BundleEntry {
+            signature_message_length,
+            address: &transfer.address,
+            value: transfer.value,
+            tag: &tag,
+            timestamp: options.clock.now(),
+        });
+        total_value += transfer.value;
     }
 
-    fn add_remainder(
-        &mut self,
-        inputs: &Inputs,
-        bundle: &mut Bundle,
-        options: AddRemainderOptions<'_, '_, '_, '_>,
-    ) -> Result<Vec<String>> {
-        let mut total_transfer_value = inputs.total_balance();
+    let mut remainder_address = None;
+    let inputs = if total_value > 0 {
+        let inputs = match &options.inputs {
+            Some(inputs) => inputs,
+            None => {
+                return Err(ClientError::InvalidInput(
+                    "Inputs with balances are required to send value.".into(),
+                ))
+            }
+        };
+        let (mut inputs, remainder) =
+            select_inputs(inputs, total_value, &*options.selector, &options.exclude)?;
+        for input in inputs.inputs_list_mut() {
+            input.address = iota_signing::checksum::remove_checksum(&input.address);
+        }
+        let is_input = |address: &str| {
+            inputs
+                .inputs_list()
+                .iter()
+                .any(|input| input.address == address)
+        };
+        ensure_input!(
+            !bundle.iter().any(|tx| is_input(&tx.address)),
+            "{}",
+            iota_constants::SEND_TO_INPUTS_ERROR
+        );
+        let timestamp = options.clock.now();
         for input in inputs.inputs_list() {
-            let this_balance = input.balance;
-            let to_subtract = 0 - this_balance;
-            let timestamp = Utc::now().timestamp();
-            let address = iota_signing::checksum::remove_checksum(&input.address);
-
             bundle.add_entry(BundleEntry {
                 signature_message_length: input.security,
-                address: &address,
-                value: to_subtract,
-                tag: &options.tag,
+                address: &input.address,
+                value: -input.balance,
+                tag: &tag,
                 timestamp,
             });
-
-            if this_balance >= total_transfer_value {
-                let remainder = this_balance - total_transfer_value;
-                if let Some(remainder_address) = &options.remainder_address {
-                    if remainder > 0 {
-                        bundle.add_entry(BundleEntry {
-                            signature_message_length: 1,
-                            address: &remainder_address,
-                            value: remainder,
-                            tag: &options.tag,
-                            timestamp,
-                        });
-                        return self.sign_inputs_and_return(
-                            &options.seed,
-                            inputs,
-                            bundle,
-                            &options.signature_fragments,
-                            options.added_hmac,
-                            options.hmac_key,
-                        );
-                    }
-                } else if remainder > 0 {
-                    let mut start_index = 0;
-                    for input in inputs.inputs_list() {
-                        start_index = cmp::max(input.key_index, start_index);
-                    }
-                    start_index += 1;
-                    let new_address = &self.get_new_address(
-                        &options.seed,
-                        false,
-                        false,
-                        GetNewAddressOptions {
-                            security: Some(options.security),
-                            index: Some(start_index),
-                            total: None,
-                        },
-                    )?[0];
-                    bundle.add_entry(BundleEntry {
-                        signature_message_length: 1,
-                        address: &new_address,
-                        value: remainder,
-                        tag: &options.tag,
-                        timestamp: Utc::now().timestamp(),
-                    });
-                    return self.sign_inputs_and_return(
-                        &options.seed,
-                        inputs,
-                        bundle,
-                        &options.signature_fragments,
-                        options.added_hmac,
-                        options.hmac_key,
-                    );
-                } else {
-                    return self.sign_inputs_and_return(
-                        &options.seed,
-                        inputs,
-                        bundle,
-                        &options.signature_fragments,
-                        options.added_hmac,
-                        options.hmac_key,
-                    );
+        }
+        if remainder > 0 {
+            let address = match options.remainder_address {
+                Some(remainder_address) => {
+                    iota_signing::checksum::remove_checksum(remainder_address)
                 }
-            } else {
-                total_transfer_value -= this_balance;
-            }
+                None => {
+                    return Err(ClientError::InvalidInput(
+                        "A remainder address is required.".into(),
+                    ))
+                }
+            };
+            ensure_input!(
+                iota_validation::is_address(&address),
+                "Invalid remainder address."
+            );
+            ensure_input!(
+                !is_input(&address),
+                "{}",
+                iota_constants::SEND_TO_INPUTS_ERROR
+            );
+            bundle.add_entry(BundleEntry {
+                signature_message_length: 1,
+                address: &address,
+                value: remainder,
+                tag: &tag,
+                timestamp,
+            });
+            remainder_address = Some(address);
         }
-        Err(format_err!("Something wen't wrong..."))
+        inputs
+    } else {
+        Inputs::default()
+    };
+
+    bundle.reset_indexes();
+    bundle.finalize()?;
+    bundle.add_trytes(&signature_fragments);
+    Ok(Prepared {
+        bundle,
+        inputs,
+        remainder_address,
+        added_hmac,
+    })
+}
+
+/// The number of transactions carrying `message` in a bundle
+pub(crate) fn message_length(message: &str) -> usize {
+    if message.len() > iota_constants::MESSAGE_LENGTH {
+        1 + (message.len() as f64 / iota_constants::MESSAGE_LENGTH as f64).floor() as usize
+    } else {
+        1
+    }
+}
+
+/// Chooses the inputs covering `total_value` with `selector`, leaving
+/// out empty and excluded addresses. Returns the selected inputs and the
+/// remainder left on them.
+pub(crate) fn select_inputs(
+    inputs: &Inputs,
+    total_value: i64,
+    selector: &dyn InputSelector,
+    exclude: &[String],
+) -> Result<(Inputs, i64)> {
+    let excluded: Vec<String> = exclude
+        .iter()
+        .map(|address| iota_signing::checksum::remove_checksum(address))
+        .collect();
+    let candidates: Vec<_> = inputs
+        .inputs_list()
+        .iter()
+        .filter(|input| {
+            input.balance > 0
+                && !excluded.contains(&iota_signing::checksum::remove_checksum(&input.address))
+        })
+        .cloned()
+        .collect();
+    let mut selected = Inputs::default();
+    for input in selector.select(&candidates, total_value)? {
+        ensure_input!(
+            candidates.contains(&input),
+            "Input selector returned an input that is not a candidate."
+        );
+        *selected.total_balance_mut() += input.balance;
+        selected.add(input);
     }
+    if selected.total_balance() < total_value {
+        return Err(format_err!("Not enough balance.").into());
+    }
+    let remainder = selected.total_balance() - total_value;
+    Ok((selected, remainder))
+}
 
-    fn sign_inputs_and_return<'b>(
+impl Client {
+    /// Prepares a slice of transfers and converts them into a
+    /// slice of tryte-encoded strings
+    ///
+    /// * `seed` - The wallet seed to use, or a `Signer`
+    /// * `transfers` - A slice of transfers to prepare
+    /// * `options` - See `PrepareTransfersOptions`
+    pub fn prepare_transfers(
         &mut self,
-        seed: &str,
-        inputs: &Inputs,
-        bundle: &mut Bundle,
-        signature_fragments: &[String],
-        added_hmac: bool,
-        hmac_key: Option<&'b str>,
+        seed: impl IntoSigner,
+        transfers: impl Into<Vec<Transfer>>,
+        options: PrepareTransfersOptions<'_, '_>,
     ) -> Result<Vec<String>> {
-        bundle.reset_indexes();
-        bundle.finalize()?;
-        bundle.add_trytes(&signature_fragments);
-        for i in 0..bundle.len() {
-            if bundle[i].value < 0 {
-                let this_address = bundle[i].address.clone();
-                let mut key_index = 0;
-                let mut key_security = 0;
-                for input in inputs.inputs_list() {
-                    if input.address == *this_address {
-                        key_index = input.key_index;
-                        key_security = input.security;
-                        break;
-                    }
-                }
-                let bundle_hash = &bundle[i].bundle;
-                let key = iota_signing::key(&seed.trits(), key_index, key_security)?;
-                let normalized_bundle_hash = Bundle::normalized_bundle(&bundle_hash).to_vec();
-                let mut normalized_bundle_fragments = [[0; 27]; 3];
-                for (j, c) in normalized_bundle_hash.chunks(27).enumerate() {
-                    normalized_bundle_fragments[j].copy_from_slice(c);
-                }
-                let first_fragment = key[0..6561].to_vec();
-                let first_bundle_fragment = normalized_bundle_fragments[0];
-                let first_signed_fragment =
-                    iota_signing::signature_fragment(&first_bundle_fragment, &first_fragment)?;
-                bundle[i].signature_fragments = first_signed_fragment.trytes()?;
-                for j in 1..key_security {
-                    if bundle[i + j].address == *this_address && bundle[i + j].value == 0 {
-                        let next_fragment = key[6561 * j..(j + 1) * 6561].to_vec();
-                        let next_bundle_fragment = normalized_bundle_fragments[j];
-                        let next_signed_fragment = iota_signing::signature_fragment(
-                            &next_bundle_fragment,
-                            &next_fragment,
-                        )?;
-                        bundle[i + j].signature_fragments = next_signed_fragment.trytes()?;
-                    }
-                }
-            }
-        }
-        if added_hmac {
-            let hmac = iota_signing::HMAC::new(&hmac_key.unwrap_or_default());
-            hmac.add_hmac(bundle)?;
-        }
-        let mut bundle_trytes: Vec<String> = Vec::new();
-        for tx in bundle.iter().rev() {
-            let tx_trytes: String = tx.try_into()?;
-            bundle_trytes.push(tx_trytes);
-        }
-        Ok(bundle_trytes)
+        self.runtime
+            .block_on(self.inner.prepare_transfers(seed, transfers, options))
     }
 }
@@ -0,0 +1,81 @@
+use iota_model::{Transaction, Transfer};
+use tokio::prelude::*;
+
+use crate::async_client::AsyncClient;
+use crate::client::Client;
+use crate::error::ClientError;
+use crate::options::{PrepareTransfersOptions, SendTrytesOptions};
+use crate::{BoxFuture, Result};
+
+impl AsyncClient {
+    /// Promotes a stuck transfer by sending a zero-value bundle that
+    /// approves its tail transaction, which makes the tail more likely
+    /// to be referenced by the next milestones. Check `is_promotable`
+    /// first: a tail that isn't promotable must be reattached instead.
+    ///
+    /// * `tail` - The tail transaction hash of the bundle to promote
+    /// * `options` - See `SendTrytesOptions`, the tail is used as the reference
+    pub fn promote_transaction(
+        &self,
+        tail: &str,
+        options: SendTrytesOptions<'_>,
+    ) -> BoxFuture<Vec<Transaction>> {
+        if !iota_validation::is_hash(tail) {
+            return Box::new(future::err(ClientError::InvalidInput(format!(
+                "Provided tail is not valid: {:?}",
+                tail
+            ))));
+        }
+        let client = self.clone();
+        let tail = tail.to_string();
+        let depth = options.depth;
+        let min_weight_magnitude = options.min_weight_magnitude;
+        let pow = options.pow;
+        let clock = options.clock;
+        let spam = Transfer {
+            address: "9".repeat(81),
+            value: 0,
+            ..Transfer::default()
+        };
+        Box::new(
+            self.prepare_transfers(
+                &"9".repeat(81),
+                vec![spam],
+                PrepareTransfersOptions {
+                    clock: clock.clone(),
+                    ..PrepareTransfersOptions::default()
+                },
+            )
+            .and_then(move |trytes| {
+                client.send_trytes(
+                    &trytes,
+                    SendTrytesOptions {
+                        depth,
+                        min_weight_magnitude,
+                        pow,
+                        reference: Some(&tail),
+                        clock,
+                    },
+                )
+            }),
+        )
+    }
+}
+
+impl Client {
+    /// Promotes a stuck transfer by sending a zero-value bundle that
+    /// approves its tail transaction, which makes the tail more likely
+    /// to be referenced by the next milestones. Check `is_promotable`
+    /// first: a tail that isn't promotable must be reattached instead.
+    ///
+    /// * `tail` - The tail transaction hash of the bundle to promote
+    /// * `options` - See `SendTrytesOptions`, the tail is used as the reference
+    pub fn promote_transaction(
+        &mut self,
+        tail: &str,
+        options: SendTrytesOptions<'_>,
+    ) -> Result<Vec<Transaction>> {
+        self.runtime
+            .block_on(self.inner.promote_transaction(tail, options))
+    }
+}
@@ -0,0 +1,71 @@
+use std::convert::TryInto;
+
+use iota_model::Transaction;
+use tokio::prelude::*;
+
+use crate::async_client::AsyncClient;
+use crate::client::Client;
+use crate::options::SendTrytesOptions;
+use crate::{BoxFuture, Result};
+
+impl AsyncClient {
+    /// Reattaches a bundle on top of new transactions to approve, for
+    /// transfers that are no longer promotable. The bundle is fetched
+    /// and validated with `get_bundle`, and sent again with `send_trytes`
+    /// so that any of its attachments can be confirmed.
+    ///
+    /// * `tail` - The tail transaction hash of the bundle to reattach
+    /// * `options` - See `SendTrytesOptions`
+    pub fn replay_bundle(
+        &self,
+        tail: &str,
+        options: SendTrytesOptions<'_>,
+    ) -> BoxFuture<Vec<Transaction>> {
+        let client = self.clone();
+        let depth = options.depth;
+        let min_weight_magnitude = options.min_weight_magnitude;
+        let pow = options.pow;
+        let clock = options.clock;
+        let reference = options.reference.map(str::to_string);
+        Box::new(
+            self.get_bundle(tail)
+                .and_then(|bundle| {
+                    bundle
+                        .into_iter()
+                        .rev()
+                        .map(|tx| Ok(tx.try_into()?))
+                        .collect::<Result<Vec<String>>>()
+                })
+                .and_then(move |trytes| {
+                    client.send_trytes(
+                        &trytes,
+                        SendTrytesOptions {
+                            depth,
+                            min_weight_magnitude,
+                            pow,
+                            reference: reference.as_deref(),
+                            clock,
+                        },
+                    )
+                }),
+        )
+    }
+}
+
+impl Client {
+    /// Reattaches a bundle on top of new transactions to approve, for
+    /// transfers that are no longer promotable. The bundle is fetched
+    /// and validated with `get_bundle`, and sent again with `send_trytes`
+    /// so that any of its attachments can be confirmed.
+    ///
+    /// * `tail` - The tail transaction hash of the bundle to reattach
+    /// * `options` - See `SendTrytesOptions`
+    pub fn replay_bundle(
+        &mut self,
+        tail: &str,
+        options: SendTrytesOptions<'_>,
+    ) -> Result<Vec<Transaction>> {
+        self.runtime
+            .block_on(self.inner.replay_bundle(tail, options))
+    }
+}
@@ -0,0 +1,418 @@
+use std::fmt;
+use std::sync::Arc;
+
+use iota_model::{Input, Inputs, Transfer};
+use tokio::prelude::*;
+
+use crate::async_client::AsyncClient;
+use crate::await_confirmation::tail_of;
+use crate::client::Client;
+use crate::clock::{Clock, SystemClock};
+use crate::error::ClientError;
+use crate::ledger::SpentLedger;
+use crate::options::{GetNewAddressOptions, PrepareTransfersOptions, SendTrytesOptions};
+use crate::pow::{LocalPow, PowProvider};
+use crate::prepare_transfers::{message_length, prepare_transfers_offline, select_inputs};
+use crate::selector::{ConsolidateAll, InputSelector, OldestFirst};
+use crate::signer::{self, IntoSigner, Signer};
+use crate::{BoxFuture, Result};
+
+/// SendPayoutsOptions
+#[derive(Clone, Debug)]
+pub struct SendPayoutsOptions {
+    /// The depth for getting transactions to approve
+    pub depth: usize,
+    /// The minimum weight magnitude for doing proof of work
+    pub min_weight_magnitude: usize,
+    /// Does the proof of work, see `PowProvider`
+    pub pow: Arc<dyn PowProvider>,
+    /// Optionally specify which inputs to use when trying to find funds for the payouts
+    pub inputs: Option<Inputs>,
+    /// Security to use when generating addresses (1-3)
+    pub security: usize,
+    /// The maximum number of transactions in each bundle, inputs and
+    /// remainder included
+    pub max_transactions: usize,
+    /// Chooses the inputs funding the first bundle, see `InputSelector`
+    pub selector: Arc<dyn InputSelector>,
+    /// Addresses never to spend from, with or without checksum, such as
+    /// addresses with pending outgoing bundles
+    pub exclude: Vec<String>,
+    /// Records every input before it is signed, and refuses inputs and
+    /// remainder addresses already recorded, see `SpentLedger`
+    pub ledger: Option<Arc<dyn SpentLedger>>,
+    /// Stamps the bundle entries and the attachment timestamps, see `Clock`
+    pub clock: Arc<dyn Clock>,
+}
+
+impl Default for SendPayoutsOptions {
+    fn default() -> Self {
+        SendPayoutsOptions {
+            depth: 3,
+            min_weight_magnitude: 14,
+            pow: Arc::new(LocalPow::default()),
+            inputs: None,
+            security: 3,
+            max_transactions: 30,
+            selector: Arc::new(OldestFirst),
+            exclude: Vec::new(),
+            ledger: None,
+            clock: Arc::new(SystemClock),
+        }
+    }
+}
+
+/// The bundles sent by `send_payouts`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Payouts {
+    /// The tail transaction hash of every bundle, in the order they were sent
+    pub tails: Vec<String>,
+    /// The remainder of the last bundle, spendable once it is confirmed
+    pub remainder: Option<Input>,
+}
+
+/// The error of `send_payouts`, with the bundles sent before it failed
+#[derive(Debug, Fail)]
+pub struct PayoutsError {
+    /// The bundles sent before the failure. Their transfers are not
+    /// retried, and `sent.remainder` funds the payouts left to send.
+    pub sent: Box<Payouts>,
+    /// Why the next bundle could not be sent
+    #[cause]
+    pub cause: ClientError,
+}
+
+impl fmt::Display for PayoutsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (after sending {} bundles)",
+            self.cause,
+            self.sent.tails.len()
+        )
+    }
+}
+
+impl PayoutsError {
+    fn unsent(cause: ClientError) -> PayoutsError {
+        PayoutsError {
+            sent: Box::default(),
+            cause,
+        }
+    }
+}
+
+/// A bundle waiting to be sent by `send_payouts`
+#[derive(Debug)]
+struct Batch {
+    transfers: Vec<Transfer>,
+    /// The key index and address receiving the remainder of the bundle
+    remainder_address: Option<(usize, String)>,
+}
+
+impl AsyncClient {
+    /// Sends transfers to many recipients as a chain of bundles of at most
+    /// `max_transactions` transactions each, so that every bundle stays
+    /// quick to attach and small enough for any node.
+    ///
+    /// The inputs covering all transfers are chosen by the `selector` and
+    /// spent by the first bundle. Every bundle sends its remainder to a new
+    /// address, which is the only input of the next bundle, and approves
+    /// the tail of the previous bundle, so that the bundles confirm in
+    /// order. Bundles are sent one after the other. If one fails, nothing
+    /// after it is sent, and the `PayoutsError` carries the tails of the
+    /// bundles already sent with their remainder, which funds the
+    /// transfers left to send.
+    ///
+    /// * `seed` - The wallet seed to use, or a `Signer`
+    /// * `transfers` - The transfers to send
+    /// * `options` - See `SendPayoutsOptions`
+    pub fn send_payouts(
+        &self,
+        seed: impl IntoSigner,
+        transfers: impl Into<Vec<Transfer>>,
+        options: SendPayoutsOptions,
+    ) -> Box<dyn Future<Item = Payouts, Error = PayoutsError> + Send> {
+        let transfers = transfers.into();
+        let signer = match seed.into_signer() {
+            Ok(signer) => signer,
+            Err(e) => return Box::new(future::err(PayoutsError::unsent(e))),
+        };
+        let total_value: i64 = transfers.iter().map(|transfer| transfer.value).sum();
+        let security = options.security;
+        let inputs: BoxFuture<Inputs> = if total_value > 0 {
+            self.candidate_inputs(
+                signer.clone(),
+                options.inputs.clone(),
+                total_value,
+                security,
+            )
+        } else {
+            Box::new(future::ok(Inputs::default()))
+        };
+
+        let client = self.clone();
+        let split_options = options.clone();
+        let split_signer = signer.clone();
+        let batches = inputs.and_then(move |candidates| -> BoxFuture<(Vec<Batch>, Inputs)> {
+            let options = split_options;
+            let (inputs, _) = match select_inputs(
+                &candidates,
+                total_value,
+                &*options.selector,
+                &options.exclude,
+            ) {
+                Ok(selected) => selected,
+                Err(e) => return Box::new(future::err(e)),
+            };
+            let batches = match split_transfers(transfers, &inputs, &options) {
+                Ok(batches) => batches,
+                Err(e) => return Box::new(future::err(e)),
+            };
+            let remainder_count = batches
+                .iter()
+                .filter(|(_, remainder)| *remainder > 0)
+                .count();
+            let start_index = candidates
+                .inputs_list()
+                .iter()
+                .map(|input| input.key_index)
+                .max()
+                .map_or(0, |index| index + 1);
+            let remainder_addresses =
+                client.remainder_addresses(split_signer, start_index, remainder_count, security);
+
+            Box::new(
+                remainder_addresses
+                    .and_then(move |remainder_addresses| {
+                        let addresses: Vec<String> = remainder_addresses
+                            .iter()
+                            .map(|(_, address)| address.clone())
+                            .collect();
+                        client
+                            .check_unspent(&inputs, &addresses)
+                            .map(move |_| (inputs, remainder_addresses))
+                    })
+                    .map(move |(inputs, remainder_addresses)| {
+                        let mut remainder_addresses = remainder_addresses.into_iter();
+                        let batches = batches
+                            .into_iter()
+                            .map(|(transfers, remainder)| Batch {
+                                transfers,
+                                remainder_address: if remainder > 0 {
+                                    remainder_addresses.next()
+                                } else {
+                                    None
+                                },
+                            })
+                            .collect();
+                        (batches, inputs)
+                    }),
+            )
+        });
+
+        let client = self.clone();
+        Box::new(
+            batches
+                .map_err(PayoutsError::unsent)
+                .and_then(move |(batches, inputs)| {
+                    client.send_batches(signer, batches, inputs, options)
+                }),
+        )
+    }
+
+    /// Generates `count` consecutive addresses starting at the first
+    /// unused address from `start_index`
+    fn remainder_addresses(
+        &self,
+        signer: Arc<dyn Signer>,
+        start_index: usize,
+        count: usize,
+        security: usize,
+    ) -> BoxFuture<Vec<(usize, String)>> {
+        if count == 0 {
+            return Box::new(future::ok(Vec::new()));
+        }
+        Box::new(
+            self.get_new_address(
+                signer.clone(),
+                false,
+                true,
+                GetNewAddressOptions {
+                    security: Some(security),
+                    index: Some(start_index),
+                    ..GetNewAddressOptions::default()
+                },
+            )
+            .and_then(move |scanned| {
+                let first = start_index + scanned.len() - 1;
+                signer::spawn_addresses(signer, security, first..first + count, false)
+                    .map(move |addresses| (first..).zip(addresses).collect())
+            }),
+        )
+    }
+
+    /// Signs and sends `batches` in order, each one spending the
+    /// remainder of the previous one. Fails with the bundles sent so far.
+    fn send_batches(
+        &self,
+        signer: Arc<dyn Signer>,
+        batches: Vec<Batch>,
+        inputs: Inputs,
+        options: SendPayoutsOptions,
+    ) -> Box<dyn Future<Item = Payouts, Error = PayoutsError> + Send> {
+        let client = self.clone();
+        let initial = (batches.into_iter(), inputs, Payouts::default());
+        Box::new(future::loop_fn(
+            initial,
+            move |(mut batches, inputs, payouts)| -> Box<dyn Future<Item = _, Error = _> + Send> {
+                let batch = match batches.next() {
+                    Some(batch) => batch,
+                    None => return Box::new(future::ok(future::Loop::Break(payouts))),
+                };
+                let value: i64 = batch.transfers.iter().map(|transfer| transfer.value).sum();
+                let remainder = inputs.total_balance() - value;
+                let trytes = prepare_transfers_offline(
+                    signer.clone(),
+                    batch.transfers,
+                    PrepareTransfersOptions {
+                        inputs: Some(inputs),
+                        remainder_address: batch
+                            .remainder_address
+                            .as_ref()
+                            .map(|(_, address)| address.as_str()),
+                        security: options.security,
+                        hmac_key: None,
+                        selector: Arc::new(ConsolidateAll),
+                        exclude: Vec::new(),
+                        ledger: options.ledger.clone(),
+                        clock: options.clock.clone(),
+                    },
+                );
+                let trytes = match trytes {
+                    Ok(trytes) => trytes,
+                    Err(cause) => {
+                        return Box::new(future::err(PayoutsError {
+                            sent: Box::new(payouts),
+                            cause,
+                        }))
+                    }
+                };
+                let sent_remainder = batch.remainder_address.map(|(key_index, address)| Input {
+                    address,
+                    balance: remainder,
+                    key_index,
+                    security: options.security,
+                });
+                let sent = client.send_trytes(
+                    &trytes,
+                    SendTrytesOptions {
+                        depth: options.depth,
+                        min_weight_magnitude: options.min_weight_magnitude,
+                        pow: options.pow.clone(),
+                        reference: payouts.tails.last().map(String::as_str),
+                        clock: options.clock.clone(),
+                    },
+                );
+                Box::new(sent.then(move |sent| {
+                    let mut payouts = payouts;
+                    match sent.and_then(|sent| tail_of(&sent)) {
+                        Ok(tail) => {
+                            let mut next_inputs = Inputs::default();
+                            if let Some(input) = &sent_remainder {
+                                next_inputs.add(input.clone());
+                                next_inputs.set_total_balance(remainder);
+                            }
+                            payouts.tails.push(tail);
+                            payouts.remainder = sent_remainder;
+                            Ok(future::Loop::Continue((batches, next_inputs, payouts)))
+                        }
+                        Err(cause) => Err(PayoutsError {
+                            sent: Box::new(payouts),
+                            cause,
+                        }),
+                    }
+                }))
+            },
+        ))
+    }
+}
+
+/// Splits `transfers` into bundles of at most `max_transactions`
+/// transactions, the first one spending `inputs` and every other one the
+/// remainder of the previous bundle. Returns the transfers of every bundle
+/// with the remainder it leaves.
+fn split_transfers(
+    transfers: Vec<Transfer>,
+    inputs: &Inputs,
+    options: &SendPayoutsOptions,
+) -> Result<Vec<(Vec<Transfer>, i64)>> {
+    let value = |transfers: &[Transfer]| -> i64 { transfers.iter().map(|t| t.value).sum() };
+    // Room left for the transfers next to the inputs and the remainder
+    let room = |inputs_length: usize| {
+        options
+            .max_transactions
+            .saturating_sub(inputs_length + usize::from(inputs_length > 0))
+    };
+    let mut funds = inputs.total_balance();
+    let mut capacity = room(
+        inputs
+            .inputs_list()
+            .iter()
+            .map(|input| input.security)
+            .sum(),
+    );
+    let mut batches = Vec::new();
+    let mut batch: Vec<Transfer> = Vec::new();
+    let mut length = 0;
+    for transfer in transfers {
+        let transfer_length = message_length(&transfer.message);
+        if length + transfer_length > capacity && !batch.is_empty() {
+            funds -= value(&batch);
+            batches.push((batch, funds));
+            batch = Vec::new();
+            length = 0;
+            capacity = room(if funds > 0 { options.security } else { 0 });
+        }
+        ensure_input!(
+            transfer_length <= capacity,
+            "A transfer doesn't fit in a bundle of {} transactions.",
+            options.max_transactions
+        );
+        length += transfer_length;
+        batch.push(transfer);
+    }
+    if !batch.is_empty() {
+        funds -= value(&batch);
+        batches.push((batch, funds));
+    }
+    Ok(batches)
+}
+
+impl Client {
+    /// Sends transfers to many recipients as a chain of bundles of at most
+    /// `max_transactions` transactions each, so that every bundle stays
+    /// quick to attach and small enough for any node.
+    ///
+    /// The inputs covering all transfers are chosen by the `selector` and
+    /// spent by the first bundle. Every bundle sends its remainder to a new
+    /// address, which is the only input of the next bundle, and approves
+    /// the tail of the previous bundle, so that the bundles confirm in
+    /// order. Bundles are sent one after the other. If one fails, nothing
+    /// after it is sent, and the `PayoutsError` carries the tails of the
+    /// bundles already sent with their remainder, which funds the
+    /// transfers left to send.
+    ///
+    /// * `seed` - The wallet seed to use, or a `Signer`
+    /// * `transfers` - The transfers to send
+    /// * `options` - See `SendPayoutsOptions`
+    pub fn send_payouts(
+        &mut self,
+        seed: impl IntoSigner,
+        transfers: impl Into<Vec<Transfer>>,
+        options: SendPayoutsOptions,
+    ) -> ::std::result::Result<Payouts, PayoutsError> {
+        self.runtime
+            .block_on(self.inner.send_payouts(seed, transfers, options))
+    }
+}
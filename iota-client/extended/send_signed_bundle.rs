@@ -0,0 +1,62 @@
+use iota_model::{Bundle, Transaction};
+use tokio::prelude::*;
+
+use crate::async_client::AsyncClient;
+use crate::client::Client;
+use crate::options::SendTrytesOptions;
+use crate::{BoxFuture, Result};
+
+impl AsyncClient {
+    /// Sends a bundle signed on another host, as read with
+    /// `Bundle::from_signed_json`. The bundle is checked with
+    /// `iota_validation::is_bundle` before being attached and broadcast
+    /// with `send_trytes`.
+    ///
+    /// * `bundle` - The signed bundle to send
+    /// * `options` - See `SendTrytesOptions`
+    pub fn send_signed_bundle(
+        &self,
+        bundle: &Bundle,
+        options: SendTrytesOptions<'_>,
+    ) -> BoxFuture<Vec<Transaction>> {
+        let trytes = match checked_trytes(bundle) {
+            Ok(trytes) => trytes,
+            Err(e) => return Box::new(future::err(e)),
+        };
+        self.send_trytes(&trytes, options)
+    }
+}
+
+/// Converts a bundle to trytes, validating the transactions parsed back
+/// from them so that their hashes are set
+fn checked_trytes(bundle: &Bundle) -> Result<Vec<String>> {
+    let trytes = bundle.trytes()?;
+    let transactions = trytes
+        .iter()
+        .rev()
+        .map(|trytes| trytes.parse())
+        .collect::<std::result::Result<Vec<Transaction>, _>>()?;
+    ensure_input!(
+        iota_validation::is_bundle(&transactions)?,
+        "Invalid bundle."
+    );
+    Ok(trytes)
+}
+
+impl Client {
+    /// Sends a bundle signed on another host, as read with
+    /// `Bundle::from_signed_json`. The bundle is checked with
+    /// `iota_validation::is_bundle` before being attached and broadcast
+    /// with `send_trytes`.
+    ///
+    /// * `bundle` - The signed bundle to send
+    /// * `options` - See `SendTrytesOptions`
+    pub fn send_signed_bundle(
+        &mut self,
+        bundle: &Bundle,
+        options: SendTrytesOptions<'_>,
+    ) -> Result<Vec<Transaction>> {
+        self.runtime
+            .block_on(self.inner.send_signed_bundle(bundle, options))
+    }
+}
@@ -1,20 +1,27 @@
+use std::sync::Arc;
+
 use iota_model::{Inputs, Transaction, Transfer};
+use tokio::prelude::*;
 
+use crate::async_client::AsyncClient;
 use crate::client::Client;
+use crate::clock::{Clock, SystemClock};
+use crate::ledger::SpentLedger;
 use crate::options::{PrepareTransfersOptions, SendTrytesOptions};
-use crate::Result;
+use crate::pow::{LocalPow, PowProvider};
+use crate::selector::{InputSelector, OldestFirst};
+use crate::signer::IntoSigner;
+use crate::{BoxFuture, Result};
 
 /// SendTransferOptions
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug)]
 pub struct SendTransferOptions<'a, 'b, 'c> {
     /// The depth for getting transactions to approve
     pub depth: usize,
     /// The minimum weight magnitude for doing proof of work
     pub min_weight_magnitude: usize,
-    /// Perform PoW locally
-    pub local_pow: bool,
-    /// Optionally specify the number of threads to use for PoW. This is ignored if `local_pow` is false.
-    pub threads: usize,
+    /// Does the proof of work, see `PowProvider`
+    pub pow: Arc<dyn PowProvider>,
     /// Optionally specify which inputs to use when trying to find funds for transfers
     pub inputs: Option<Inputs>,
     /// Optionally specify where to start searching for transactions to approve
@@ -25,6 +32,16 @@ pub struct SendTransferOptions<'a, 'b, 'c> {
     pub security: usize,
     /// Optionally specify an HMAC key to use for this transaction
     pub hmac_key: Option<&'c str>,
+    /// Chooses the inputs to spend, see `InputSelector`
+    pub selector: Arc<dyn InputSelector>,
+    /// Addresses never to spend from, with or without checksum, such as
+    /// addresses with pending outgoing bundles
+    pub exclude: Vec<String>,
+    /// Records every input before it is signed, and refuses inputs and
+    /// remainder addresses already recorded, see `SpentLedger`
+    pub ledger: Option<Arc<dyn SpentLedger>>,
+    /// Stamps the bundle entries and the attachment timestamps, see `Clock`
+    pub clock: Arc<dyn Clock>,
 }
 
 impl<'a, 'b, 'c> Default for SendTransferOptions<'a, 'b, 'c> {
@@ -32,54 +49,84 @@ impl<'a, 'b, 'c> Default for SendTransferOptions<'a, 'b, 'c> {
         SendTransferOptions {
             depth: 3,
             min_weight_magnitude: 14,
-            local_pow: true,
-            threads: num_cpus::get(),
+            pow: Arc::new(LocalPow::default()),
             inputs: None,
             reference: None,
             remainder_address: None,
             security: 3,
             hmac_key: None,
+            selector: Arc::new(OldestFirst),
+            exclude: Vec::new(),
+            ledger: None,
+            clock: Arc::new(SystemClock),
         }
     }
 }
 
-impl<'a> Client<'a> {
+impl AsyncClient {
+    /// Prepares and sends a slice of transfers
+    /// This helper does everything for you, PoW and such
+    ///
+    /// * `transfers` - A slice of transfers to send
+    /// * `seed` - The wallet seed to use, or a `Signer`
+    /// * `options` - See `SendTransferOptions`
+    pub fn send_transfers(
+        &self,
+        transfers: impl Into<Vec<Transfer>>,
+        seed: impl IntoSigner,
+        options: SendTransferOptions<'_, '_, '_>,
+    ) -> BoxFuture<Vec<Transaction>> {
+        let client = self.clone();
+        let depth = options.depth;
+        let min_weight_magnitude = options.min_weight_magnitude;
+        let pow = options.pow;
+        let reference = options.reference.map(str::to_string);
+        let clock = options.clock;
+        Box::new(
+            self.prepare_transfers(
+                seed,
+                transfers,
+                PrepareTransfersOptions {
+                    inputs: options.inputs,
+                    remainder_address: options.remainder_address,
+                    security: options.security,
+                    hmac_key: options.hmac_key,
+                    selector: options.selector,
+                    exclude: options.exclude,
+                    ledger: options.ledger,
+                    clock: clock.clone(),
+                },
+            )
+            .and_then(move |trytes| {
+                client.send_trytes(
+                    &trytes,
+                    SendTrytesOptions {
+                        depth,
+                        min_weight_magnitude,
+                        pow,
+                        reference: reference.as_deref(),
+                        clock,
+                    },
+                )
+            }),
+        )
+    }
+}
+
+impl Client {
     /// Prepares and sends a slice of transfers
     /// This helper does everything for you, PoW and such
     ///
     /// * `transfers` - A slice of transfers to send
-    /// * `seed` - The wallet seed to use
-    /// * `depth` - The depth to search when looking for transactions to approve
-    /// * `min_weight_magnitude` - The PoW difficulty factor (14 on mainnet, 9 on testnet)
-    /// * `local_pow` - Whether or not to do local PoW
+    /// * `seed` - The wallet seed to use, or a `Signer`
     /// * `options` - See `SendTransferOptions`
     pub fn send_transfers(
         &mut self,
         transfers: impl Into<Vec<Transfer>>,
-        seed: &str,
+        seed: impl IntoSigner,
         options: SendTransferOptions<'_, '_, '_>,
     ) -> Result<Vec<Transaction>> {
-        let transfers = transfers.into();
-        let trytes = self.prepare_transfers(
-            seed,
-            transfers,
-            PrepareTransfersOptions {
-                inputs: options.inputs,
-                remainder_address: options.remainder_address,
-                security: options.security,
-                hmac_key: options.hmac_key,
-            },
-        )?;
-        let t = self.send_trytes(
-            &trytes,
-            SendTrytesOptions {
-                depth: options.depth,
-                min_weight_magnitude: options.min_weight_magnitude,
-                local_pow: options.local_pow,
-                threads: options.threads,
-                reference: options.reference,
-            },
-        )?;
-        Ok(t)
+        self.runtime
+            .block_on(self.inner.send_transfers(transfers, seed, options))
     }
 }
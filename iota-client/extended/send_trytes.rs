@@ -1,23 +1,29 @@
+use std::sync::Arc;
+
 use iota_model::Transaction;
+use tokio::prelude::*;
 
+use crate::async_client::AsyncClient;
 use crate::client::Client;
-use crate::core::attach_to_tangle::attach_to_tangle_local;
-use crate::options::{AttachOptions, GetTransactionsToApproveOptions};
-use crate::Result;
+use crate::clock::{Clock, SystemClock};
+use crate::error::ClientError;
+use crate::options::GetTransactionsToApproveOptions;
+use crate::pow::{LocalPow, PowProvider};
+use crate::{BoxFuture, Result};
 
 /// SendTrytesOptions
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug)]
 pub struct SendTrytesOptions<'a> {
     /// The depth for getting transactions to approve
     pub depth: usize,
     /// The minimum weight magnitude for doing proof of work
     pub min_weight_magnitude: usize,
-    /// Perform PoW locally
-    pub local_pow: bool,
-    /// Optionally specify how many threads to use, defaults to max available
-    pub threads: usize,
+    /// Does the proof of work, see `PowProvider`
+    pub pow: Arc<dyn PowProvider>,
     /// Optionally used as the reference to start searching for transactions to approve
     pub reference: Option<&'a str>,
+    /// Stamps the attachment timestamps, see `Clock`
+    pub clock: Arc<dyn Clock>,
 }
 
 impl<'a> Default for SendTrytesOptions<'a> {
@@ -25,14 +31,81 @@ impl<'a> Default for SendTrytesOptions<'a> {
         SendTrytesOptions {
             depth: 3,
             min_weight_magnitude: 14,
-            local_pow: true,
-            threads: num_cpus::get(),
+            pow: Arc::new(LocalPow::default()),
             reference: None,
+            clock: Arc::new(SystemClock),
         }
     }
 }
 
-impl<'a> Client<'a> {
+impl AsyncClient {
+    /// Send trytes is a helper function that:
+    ///
+    /// 1. Gets transactions to approve
+    /// 2. Does PoW
+    /// 3. Sends your transactions to the IRI
+    ///
+    /// You should probably use `send_transfers`
+    ///
+    /// * `trytes` - A slice of strings that are tryte-encoded transactions
+    /// * `options` - See `SendTrytesOptions`
+    pub fn send_trytes(
+        &self,
+        trytes: &[String],
+        options: SendTrytesOptions<'_>,
+    ) -> BoxFuture<Vec<Transaction>> {
+        let attach_client = self.clone();
+        let broadcast_client = self.clone();
+        let trytes = trytes.to_vec();
+        let pow = options.pow;
+        let min_weight_magnitude = options.min_weight_magnitude;
+        let clock = options.clock;
+        Box::new(
+            self.get_transactions_to_approve(GetTransactionsToApproveOptions {
+                depth: options.depth,
+                reference: options.reference,
+            })
+            .and_then(move |to_approve| -> BoxFuture<Vec<String>> {
+                let (trunk_transaction, branch_transaction) = match (
+                    to_approve.trunk_transaction(),
+                    to_approve.branch_transaction(),
+                ) {
+                    (Some(trunk), Some(branch)) => (trunk.clone(), branch.clone()),
+                    (None, _) => {
+                        return Box::new(future::err(ClientError::Other(format_err!(
+                            "Trunk transaction is empty"
+                        ))))
+                    }
+                    (_, None) => {
+                        return Box::new(future::err(ClientError::Other(format_err!(
+                            "Branch transaction is empty"
+                        ))))
+                    }
+                };
+                pow.attach(
+                    &attach_client,
+                    &trunk_transaction,
+                    &branch_transaction,
+                    min_weight_magnitude,
+                    &trytes,
+                    clock,
+                )
+            })
+            .and_then(move |trytes_list| {
+                broadcast_client
+                    .store_and_broadcast(&trytes_list)
+                    .and_then(move |_| {
+                        trytes_list
+                            .iter()
+                            .map(|trytes| Ok(trytes.parse()?))
+                            .collect::<Result<Vec<Transaction>>>()
+                    })
+            }),
+        )
+    }
+}
+
+impl Client {
     /// Send trytes is a helper function that:
     ///
     /// 1. Gets transactions to approve
@@ -42,43 +115,13 @@ impl<'a> Client<'a> {
     /// You should probably use `send_transfers`
     ///
     /// * `trytes` - A slice of strings that are tryte-encoded transactions
-    /// * `depth` - The depth to search for transactions to approve
-    /// * `min_weight_magnitude` - The PoW difficulty factor (14 on mainnet, 9 on testnet)
-    /// * `local_pow` - Whether or not to do local PoW
     /// * `options` - See `SendTrytesOptions`
     pub fn send_trytes(
         &mut self,
         trytes: &[String],
         options: SendTrytesOptions<'_>,
     ) -> Result<Vec<Transaction>> {
-        let to_approve = self.get_transactions_to_approve(GetTransactionsToApproveOptions {
-            depth: options.depth,
-            reference: options.reference,
-        })?;
-        let attach_options = AttachOptions {
-            threads: options.threads,
-            trunk_transaction: &to_approve
-                .trunk_transaction()
-                .clone()
-                .ok_or_else(|| format_err!("Trunk transaction is empty"))?,
-            branch_transaction: &to_approve
-                .branch_transaction()
-                .clone()
-                .ok_or_else(|| format_err!("Branch transaction is empty"))?,
-            trytes,
-            ..AttachOptions::default()
-        };
-        let trytes_list = if options.local_pow {
-            let res = attach_to_tangle_local(attach_options)?;
-            res.trytes().unwrap()
-        } else {
-            let attached = self.attach_to_tangle(attach_options)?;
-            attached.trytes().unwrap()
-        };
-        self.store_and_broadcast(&trytes_list)?;
-        Ok(trytes_list
-            .iter()
-            .map(|trytes| trytes.parse().unwrap())
-            .collect())
+        self.runtime
+            .block_on(self.inner.send_trytes(trytes, options))
     }
 }
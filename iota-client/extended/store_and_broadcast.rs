@@ -1,14 +1,32 @@
+use tokio::prelude::*;
+
+use crate::async_client::AsyncClient;
 use crate::client::Client;
-use crate::Result;
+use crate::{BoxFuture, Result};
+
+impl AsyncClient {
+    /// Helper function that both stores, and broadcast trytes to
+    /// the IRI. Trytes must have been PoW-ed.
+    ///
+    /// * `trytes` - PoW-ed slice of tryte-encoded transaction strings
+    pub fn store_and_broadcast(&self, trytes: &[String]) -> BoxFuture<()> {
+        let client = self.clone();
+        let trytes = trytes.to_vec();
+        Box::new(
+            self.store_transactions(&trytes)
+                .and_then(move |_| client.broadcast_transactions(&trytes))
+                .map(|_| ()),
+        )
+    }
+}
 
-impl<'a> Client<'a> {
+impl Client {
     /// Helper function that both stores, and broadcast trytes to
     /// the IRI. Trytes must have been PoW-ed.
     ///
     /// * `trytes` - PoW-ed slice of tryte-encoded transaction strings
     pub fn store_and_broadcast(&mut self, trytes: &[String]) -> Result<()> {
-        self.store_transactions(&trytes)?;
-        self.broadcast_transactions(&trytes)?;
-        Ok(())
+        self.runtime
+            .block_on(self.inner.store_and_broadcast(trytes))
     }
 }
@@ -1,9 +1,12 @@
 use iota_model::Transaction;
+use tokio::prelude::future::Loop;
+use tokio::prelude::*;
 
+use crate::async_client::AsyncClient;
 use crate::client::Client;
-use crate::Result;
+use crate::{BoxFuture, Result};
 
-impl<'a> Client<'a> {
+impl AsyncClient {
     /// Traverses a bundle by going through trunk transactions until
     /// the bundle hash of the transaction is no longer the same.
     ///
@@ -11,36 +14,68 @@ impl<'a> Client<'a> {
     /// * `bundle_hash` - The bundle hash to compare against while searching
     /// * `bundle` - The bundle add transactions to, until hash no longer matches
     pub fn traverse_bundle<S, T>(
-        &mut self,
+        &self,
         trunk_tx: &str,
         bundle_hash: S,
         bundle: T,
-    ) -> Result<Vec<Transaction>>
+    ) -> BoxFuture<Vec<Transaction>>
     where
         S: Into<Option<String>>,
         T: Into<Vec<Transaction>>,
     {
-        let mut bundle = bundle.into();
-        let tryte_list = self
-            .get_trytes(&[trunk_tx.into()])?
-            .take_trytes()
-            .unwrap_or_default();
-        ensure!(!tryte_list.is_empty(), "Bundle transactions not visible");
-        let trytes = &tryte_list[0];
-        let tx: Transaction = trytes.parse()?;
-        let tx_bundle = &tx.bundle;
-        ensure!(tx.current_index == 0, "Invalid tail transaction supplied.");
-        let bundle_hash = bundle_hash.into().unwrap_or_else(|| tx_bundle.clone());
-        if bundle_hash != *tx_bundle {
-            return Ok(bundle);
-        }
+        let client = self.clone();
+        Box::new(future::loop_fn(
+            (trunk_tx.to_string(), bundle_hash.into(), bundle.into()),
+            move |(trunk_tx, bundle_hash, mut bundle)| {
+                client.get_trytes(&[trunk_tx]).and_then(move |resp| {
+                    let tryte_list = resp.take_trytes().unwrap_or_default();
+                    if tryte_list.is_empty() {
+                        return Err(format_err!("Bundle transactions not visible").into());
+                    }
+                    let tx: Transaction = tryte_list[0].parse()?;
+                    if bundle_hash.is_none() {
+                        ensure_input!(tx.current_index == 0, "Invalid tail transaction supplied.");
+                    }
+                    let bundle_hash = bundle_hash.unwrap_or_else(|| tx.bundle.clone());
+                    if bundle_hash != tx.bundle {
+                        return Ok(Loop::Break(bundle));
+                    }
+
+                    if tx.last_index == 0 && tx.current_index == 0 {
+                        return Ok(Loop::Break(vec![tx]));
+                    }
 
-        if tx.last_index == 0 && tx.current_index == 0 {
-            return Ok(vec![tx]);
-        }
+                    let trunk_tx = tx.trunk_transaction.clone();
+                    let is_last = tx.current_index == tx.last_index;
+                    bundle.push(tx);
+                    if is_last {
+                        return Ok(Loop::Break(bundle));
+                    }
+                    Ok(Loop::Continue((trunk_tx, Some(bundle_hash), bundle)))
+                })
+            },
+        ))
+    }
+}
 
-        let trunk_tx = &tx.trunk_transaction;
-        bundle.push(tx.clone());
-        self.traverse_bundle(&trunk_tx, Some(bundle_hash), bundle)
+impl Client {
+    /// Traverses a bundle by going through trunk transactions until
+    /// the bundle hash of the transaction is no longer the same.
+    ///
+    /// * `trunk_tx` - The trunk transaction to start searching at
+    /// * `bundle_hash` - The bundle hash to compare against while searching
+    /// * `bundle` - The bundle add transactions to, until hash no longer matches
+    pub fn traverse_bundle<S, T>(
+        &mut self,
+        trunk_tx: &str,
+        bundle_hash: S,
+        bundle: T,
+    ) -> Result<Vec<Transaction>>
+    where
+        S: Into<Option<String>>,
+        T: Into<Vec<Transaction>>,
+    {
+        self.runtime
+            .block_on(self.inner.traverse_bundle(trunk_tx, bundle_hash, bundle))
     }
 }
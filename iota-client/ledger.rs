@@ -0,0 +1,201 @@
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::Result;
+
+/// Remembers every address a bundle was signed for.
+///
+/// Signing a second bundle with the key of an address reveals more of
+/// that key, so an address must never be spent from twice. Nodes only know
+/// about spends they have seen, while a ledger knows about every bundle
+/// signed locally, even before it is broadcast. `prepare_transfers` records
+/// its inputs in the ledger of its options before signing, and refuses to
+/// spend from or to send a remainder to an address already recorded.
+pub trait SpentLedger: Debug + Send + Sync {
+    /// Records that a bundle spending from `inputs` and sending its
+    /// remainder to `remainder` is about to be signed.
+    ///
+    /// Checking and recording are one atomic step, so that concurrent
+    /// calls can't both spend from an address. Fails without recording
+    /// anything if an input or the remainder was already recorded. The
+    /// remainder is only checked, since the next bundle spends from it.
+    fn record(&self, inputs: &[String], remainder: Option<&str>) -> Result<()>;
+}
+
+/// A ledger kept in memory, for the lifetime of the process
+#[derive(Debug, Default)]
+pub struct MemoryLedger {
+    spent: Mutex<HashSet<String>>,
+}
+
+impl MemoryLedger {
+    /// Returns true if a bundle spending from `address` was recorded
+    pub fn is_spent(&self, address: &str) -> Result<bool> {
+        Ok(lock(&self.spent)?.contains(address))
+    }
+}
+
+impl SpentLedger for MemoryLedger {
+    fn record(&self, inputs: &[String], remainder: Option<&str>) -> Result<()> {
+        let mut spent = lock(&self.spent)?;
+        check_unrecorded(&spent, inputs, remainder)?;
+        spent.extend(inputs.iter().cloned());
+        Ok(())
+    }
+}
+
+/// A ledger persisted to a file, one address per line. Every address is
+/// written and synced to disk before `record` returns. The file is locked
+/// while recording, so that processes sharing it see each other's records.
+#[derive(Debug)]
+pub struct FileLedger {
+    path: PathBuf,
+    state: Mutex<(File, HashSet<String>)>,
+}
+
+impl FileLedger {
+    /// Opens the ledger at `path`, creating the file if it doesn't exist
+    pub fn open(path: impl AsRef<Path>) -> Result<FileLedger> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(&path)
+            .map_err(failure::Error::from)?;
+        let mut spent = HashSet::new();
+        read_records(&file, &mut spent)?;
+        Ok(FileLedger {
+            path,
+            state: Mutex::new((file, spent)),
+        })
+    }
+
+    /// The path of the ledger file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns true if a bundle spending from `address` was recorded
+    pub fn is_spent(&self, address: &str) -> Result<bool> {
+        Ok(lock(&self.state)?.1.contains(address))
+    }
+}
+
+impl SpentLedger for FileLedger {
+    fn record(&self, inputs: &[String], remainder: Option<&str>) -> Result<()> {
+        let mut state = lock(&self.state)?;
+        let (file, spent) = &mut *state;
+        file.lock().map_err(failure::Error::from)?;
+        let recorded = read_records(file, spent)
+            .and_then(|_| check_unrecorded(spent, inputs, remainder))
+            .and_then(|_| {
+                let added: Vec<&String> = inputs
+                    .iter()
+                    .filter(|address| !spent.contains(*address))
+                    .collect();
+                if added.is_empty() {
+                    return Ok(());
+                }
+                for address in &added {
+                    writeln!(file, "{}", address).map_err(failure::Error::from)?;
+                }
+                file.sync_data().map_err(failure::Error::from)?;
+                spent.extend(added.into_iter().cloned());
+                Ok(())
+            });
+        file.unlock().map_err(failure::Error::from)?;
+        recorded
+    }
+}
+
+/// Fails if any of `inputs` or the remainder is in `spent`
+fn check_unrecorded(
+    spent: &HashSet<String>,
+    inputs: &[String],
+    remainder: Option<&str>,
+) -> Result<()> {
+    ensure_input!(
+        !inputs.iter().any(|address| spent.contains(address)),
+        "{}",
+        iota_constants::PRIVATE_KEY_REUSE_ERROR
+    );
+    ensure_input!(
+        !remainder.is_some_and(|address| spent.contains(address)),
+        "{}",
+        iota_constants::SENDING_TO_USED_ADDRESS_ERROR
+    );
+    Ok(())
+}
+
+/// Adds the addresses of the ledger file to `spent`, including the ones
+/// appended by other processes
+fn read_records(mut file: &File, spent: &mut HashSet<String>) -> Result<()> {
+    file.seek(SeekFrom::Start(0))
+        .map_err(failure::Error::from)?;
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(failure::Error::from)?;
+        if !line.trim().is_empty() {
+            spent.insert(line.trim().to_string());
+        }
+    }
+    Ok(())
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> Result<std::sync::MutexGuard<'_, T>> {
+    mutex
+        .lock()
+        .map_err(|_| format_err!("Spent address ledger is poisoned").into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_ledger_persists() {
+        let path = std::env::temp_dir().join(format!("iota-ledger-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let ledger = FileLedger::open(&path).unwrap();
+        let other = FileLedger::open(&path).unwrap();
+        assert!(!ledger.is_spent("ADDRESS").unwrap());
+        ledger
+            .record(&["ADDRESS".to_string()], Some("CHANGE"))
+            .unwrap();
+        assert!(ledger.is_spent("ADDRESS").unwrap());
+        assert!(!ledger.is_spent("CHANGE").unwrap());
+        assert!(other.record(&["ADDRESS".to_string()], None).is_err());
+        assert!(other
+            .record(&["CHANGE".to_string()], Some("ADDRESS"))
+            .is_err());
+        assert!(!other.is_spent("CHANGE").unwrap());
+        drop(ledger);
+        drop(other);
+
+        let reopened = FileLedger::open(&path).unwrap();
+        assert!(reopened.is_spent("ADDRESS").unwrap());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "ADDRESS\n");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_concurrent_records_spend_once() {
+        let ledger = MemoryLedger::default();
+        let inputs = ["ADDRESS".to_string()];
+        let recorded = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..8)
+                .map(|_| scope.spawn(|| ledger.record(&inputs, None).is_ok()))
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| worker.join().unwrap())
+                .filter(|recorded| *recorded)
+                .count()
+        });
+        assert_eq!(recorded, 1);
+    }
+}
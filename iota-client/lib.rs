@@ -18,29 +18,99 @@ extern crate serde;
 #[macro_use]
 extern crate serde_json;
 
+#[macro_use]
+mod error;
 mod core;
 mod extended;
 use crate::core::*;
 use crate::extended::*;
 
+/// The asynchronous Client struct returning futures for every API call
+pub mod async_client;
+/// Splitting large requests into batches
+pub mod batch;
+/// Configuration of the HTTP connection to IRI nodes
+pub mod builder;
 /// The Client strcut to connect through IRI with API usage
 pub mod client;
+/// Clocks stamping bundles and attachments
+pub mod clock;
+/// Local records of the addresses spent from
+pub mod ledger;
+/// A pool of IRI nodes with health tracking and failover
+pub mod node_pool;
+/// Proof of work backends for sending bundles
+pub mod pow;
+/// Quorum reads across several IRI nodes
+pub mod quorum;
+/// Retrying commands that failed transiently
+pub mod retry;
+/// Strategies choosing the inputs of value transfers
+pub mod selector;
+/// Signers deriving the addresses and signatures of a seed
+pub mod signer;
+/// Transports sending API commands to IRI nodes
+pub mod transport;
 /// Arguments for IOTA IRI APIs
 pub mod options {
     pub use crate::attach_to_tangle::AttachOptions;
     pub use crate::find_transactions::FindTransactionsOptions;
+    pub use crate::get_account_data::GetAccountDataOptions;
     pub use crate::get_balances::GetBalancesOptions;
     pub use crate::get_inclusion_states::GetInclusionStatesOptions;
     pub use crate::get_inputs::GetInputsOptions;
     pub use crate::get_new_address::GetNewAddressOptions;
     pub use crate::get_transactions_to_approve::GetTransactionsToApproveOptions;
+    pub use crate::get_transfers::GetTransfersOptions;
     pub use crate::prepare_transfers::PrepareTransfersOptions;
+    pub use crate::send_payouts::SendPayoutsOptions;
     pub use crate::send_transfers::SendTransferOptions;
     pub use crate::send_trytes::SendTrytesOptions;
 }
 
+pub use async_client::AsyncClient;
 pub use attach_to_tangle::attach_to_tangle_local;
+pub use await_confirmation::{Confirmation, ConfirmationPolicy};
+pub use batch::BatchSizes;
+pub use builder::ClientBuilder;
 pub use client::Client;
-pub use get_new_address::new_address;
+pub use clock::{Clock, FixedClock, SteppingClock, SystemClock};
+pub use error::{ClientError, ErrorKind};
+pub use get_account_data::AccountData;
+pub use get_new_address::{new_address, new_addresses};
+pub use get_transfers::{AccountTransfer, TransferDirection};
+pub use ledger::{FileLedger, MemoryLedger, SpentLedger};
+pub use node_pool::NodePool;
+pub use pow::{FakePow, LocalPow, PowProvider, RemotePow};
+pub use prepare_transfers::{prepare_transfers_offline, prepare_unsigned_bundle};
+pub use quorum::Quorum;
+pub use retry::RetryPolicy;
+pub use selector::{ConsolidateAll, InputSelector, LargestFirst, OldestFirst, SmallestSufficient};
+pub use send_payouts::{Payouts, PayoutsError};
+pub use signer::{IntoSigner, SeedSigner, Signer};
+pub use transport::Transport;
+
+type Result<T> = ::std::result::Result<T, ClientError>;
+/// A boxed future resolving to `T`, as returned by every `AsyncClient` call
+pub type BoxFuture<T> = Box<dyn tokio::prelude::Future<Item = T, Error = ClientError> + Send>;
 
-type Result<T> = ::std::result::Result<T, failure::Error>;
+/// Runs `work` on a thread of its own and resolves to its result, so that
+/// CPU-heavy work such as proof of work or key derivation doesn't block
+/// the executor polling the returned future
+pub(crate) fn spawn_blocking<T, F>(work: F) -> BoxFuture<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    use tokio::prelude::Future;
+
+    let (sender, receiver) = futures::sync::oneshot::channel();
+    std::thread::spawn(move || {
+        // The receiver is gone if the caller dropped the future
+        let _ = sender.send(work());
+    });
+    Box::new(receiver.then(|result| match result {
+        Ok(result) => result,
+        Err(_) => Err(format_err!("The worker thread panicked").into()),
+    }))
+}
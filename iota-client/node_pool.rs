@@ -0,0 +1,151 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Default time a failing node is skipped before it is tried again
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// A set of IRI nodes sharing the same command surface. Commands are
+/// routed to the first healthy node in the order the nodes were given,
+/// and fail over to the next one on transport errors. Nodes that fail
+/// or report being out of sync are skipped for a cooldown period.
+///
+/// Cloning a pool is cheap, and every clone shares the same health state.
+#[derive(Clone, Debug)]
+pub struct NodePool {
+    nodes: Arc<Mutex<Vec<Node>>>,
+    cooldown: Duration,
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    uri: String,
+    unhealthy_until: Option<Instant>,
+}
+
+impl Default for NodePool {
+    fn default() -> NodePool {
+        NodePool::new(&[])
+    }
+}
+
+impl NodePool {
+    /// Create a new pool from a list of IRI URIs, in order of preference
+    pub fn new(uris: &[&str]) -> NodePool {
+        NodePool {
+            nodes: Arc::new(Mutex::new(
+                uris.iter()
+                    .map(|uri| Node {
+                        uri: uri.to_string(),
+                        unhealthy_until: None,
+                    })
+                    .collect(),
+            )),
+            cooldown: DEFAULT_COOLDOWN,
+        }
+    }
+
+    /// Sets how long a failing node is skipped before it is tried again
+    pub fn with_cooldown(mut self, cooldown: Duration) -> NodePool {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Returns the configured cooldown period
+    pub fn cooldown(&self) -> Duration {
+        self.cooldown
+    }
+
+    /// Returns every URI in the pool, in order of preference
+    pub fn uris(&self) -> Vec<String> {
+        self.lock().iter().map(|node| node.uri.clone()).collect()
+    }
+
+    /// Returns the URIs of the nodes that are not cooling down
+    pub fn healthy_uris(&self) -> Vec<String> {
+        let now = Instant::now();
+        self.lock()
+            .iter()
+            .filter(|node| node.is_healthy(now))
+            .map(|node| node.uri.clone())
+            .collect()
+    }
+
+    /// Returns true if the given node is not cooling down
+    pub fn is_healthy(&self, uri: &str) -> bool {
+        let now = Instant::now();
+        self.lock()
+            .iter()
+            .any(|node| node.uri == uri && node.is_healthy(now))
+    }
+
+    /// Marks a node as unhealthy for the cooldown period
+    pub fn mark_unhealthy(&self, uri: &str) {
+        let until = Instant::now() + self.cooldown;
+        for node in self.lock().iter_mut().filter(|node| node.uri == uri) {
+            node.unhealthy_until = Some(until);
+        }
+    }
+
+    /// Marks a node as healthy again, ending any cooldown
+    pub fn mark_healthy(&self, uri: &str) {
+        for node in self.lock().iter_mut().filter(|node| node.uri == uri) {
+            node.unhealthy_until = None;
+        }
+    }
+
+    /// Returns the order in which nodes should be tried for the next
+    /// command: healthy nodes first, then the ones cooling down, the
+    /// one recovering soonest first. Nodes cooling down are still
+    /// tried as a last resort so that a pool never refuses to work.
+    pub fn candidates(&self) -> Vec<String> {
+        let now = Instant::now();
+        let nodes = self.lock();
+        let (healthy, mut unhealthy): (Vec<&Node>, Vec<&Node>) =
+            nodes.iter().partition(|node| node.is_healthy(now));
+        unhealthy.sort_by_key(|node| node.unhealthy_until);
+        healthy
+            .into_iter()
+            .chain(unhealthy)
+            .map(|node| node.uri.clone())
+            .collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Node>> {
+        self.nodes.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Node {
+    fn is_healthy(&self, now: Instant) -> bool {
+        !matches!(self.unhealthy_until, Some(until) if until > now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidates_skip_unhealthy() {
+        let pool = NodePool::new(&["http://a", "http://b", "http://c"]);
+        pool.mark_unhealthy("http://a");
+        assert_eq!(pool.candidates(), vec!["http://b", "http://c", "http://a"]);
+        assert_eq!(pool.healthy_uris(), vec!["http://b", "http://c"]);
+        pool.mark_healthy("http://a");
+        assert_eq!(pool.candidates(), vec!["http://a", "http://b", "http://c"]);
+    }
+
+    #[test]
+    fn test_cooldown_expires() {
+        let pool = NodePool::new(&["http://a"]).with_cooldown(Duration::from_millis(0));
+        pool.mark_unhealthy("http://a");
+        assert!(pool.is_healthy("http://a"));
+    }
+
+    #[test]
+    fn test_clones_share_state() {
+        let pool = NodePool::new(&["http://a", "http://b"]);
+        pool.clone().mark_unhealthy("http://a");
+        assert!(!pool.is_healthy("http://a"));
+    }
+}
@@ -0,0 +1,273 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use iota_conversion::Trinary;
+use iota_pow::{PearlDiver, PowOptions};
+use tokio::prelude::*;
+
+use crate::async_client::AsyncClient;
+use crate::clock::Clock;
+use crate::core::attach_to_tangle::attach_with;
+use crate::options::AttachOptions;
+use crate::{spawn_blocking, BoxFuture};
+
+/// Length of the nonce at the end of transaction trytes
+const NONCE_TRYTES: usize = 27;
+
+/// Does the proof of work of a bundle before it is broadcast.
+///
+/// A provider takes the bundle trytes, in the order returned by
+/// `prepare_transfers`, together with the trunk and branch transactions
+/// to approve and the minimum weight magnitude, and returns the attached
+/// trytes the way IRI's `attachToTangle` does. `send_trytes` and
+/// `send_transfers` accept any provider through their `pow` option, so
+/// custom backends (a GPU, an FPGA, a PoW service) plug in by
+/// implementing this trait.
+pub trait PowProvider: Debug + Send + Sync {
+    /// Attaches `trytes` onto `trunk_transaction` and `branch_transaction`.
+    /// `client` is the client sending the bundle. Providers setting the
+    /// attachment timestamps themselves read them from `clock`.
+    fn attach(
+        &self,
+        client: &AsyncClient,
+        trunk_transaction: &str,
+        branch_transaction: &str,
+        min_weight_magnitude: usize,
+        trytes: &[String],
+        clock: Arc<dyn Clock>,
+    ) -> BoxFuture<Vec<String>>;
+}
+
+/// Does the proof of work on this machine with `PearlDiver`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LocalPow {
+    /// Number of threads searching for nonces
+    pub threads: usize,
+}
+
+impl Default for LocalPow {
+    fn default() -> Self {
+        LocalPow {
+            threads: num_cpus::get(),
+        }
+    }
+}
+
+impl LocalPow {
+    /// Create a provider searching for nonces on `threads` threads
+    pub fn new(threads: usize) -> LocalPow {
+        LocalPow { threads }
+    }
+}
+
+impl PowProvider for LocalPow {
+    fn attach(
+        &self,
+        _client: &AsyncClient,
+        trunk_transaction: &str,
+        branch_transaction: &str,
+        min_weight_magnitude: usize,
+        trytes: &[String],
+        clock: Arc<dyn Clock>,
+    ) -> BoxFuture<Vec<String>> {
+        let threads = self.threads;
+        let trunk_transaction = trunk_transaction.to_string();
+        let branch_transaction = branch_transaction.to_string();
+        let trytes = trytes.to_vec();
+        spawn_blocking(move || {
+            attach_with(
+                &trunk_transaction,
+                &branch_transaction,
+                &trytes,
+                &*clock,
+                |tx_trytes| {
+                    let result_trits = PearlDiver::default().search(
+                        tx_trytes.trits(),
+                        PowOptions {
+                            min_weight_magnitude,
+                            threads,
+                        },
+                    )?;
+                    Ok(result_trits.trytes()?)
+                },
+            )
+        })
+    }
+}
+
+/// Delegates the proof of work to IRI's `attachToTangle`, either on
+/// the node the bundle is sent to or on a dedicated PoW node
+#[derive(Clone, Debug, Default)]
+pub struct RemotePow {
+    node: Option<AsyncClient>,
+}
+
+impl RemotePow {
+    /// Create a provider calling `attachToTangle` on the client sending the bundle
+    pub fn new() -> RemotePow {
+        RemotePow::default()
+    }
+
+    /// Create a provider calling `attachToTangle` on `node`, while the
+    /// bundle is still broadcast by the client sending it
+    pub fn with_node(node: AsyncClient) -> RemotePow {
+        RemotePow { node: Some(node) }
+    }
+}
+
+impl PowProvider for RemotePow {
+    fn attach(
+        &self,
+        client: &AsyncClient,
+        trunk_transaction: &str,
+        branch_transaction: &str,
+        min_weight_magnitude: usize,
+        trytes: &[String],
+        _clock: Arc<dyn Clock>,
+    ) -> BoxFuture<Vec<String>> {
+        let node = self.node.as_ref().unwrap_or(client);
+        Box::new(
+            node.attach_to_tangle(AttachOptions {
+                threads: 0,
+                trunk_transaction,
+                branch_transaction,
+                min_weight_magnitude,
+                trytes,
+                ..AttachOptions::default()
+            })
+            .and_then(|attached| {
+                attached
+                    .trytes()
+                    .ok_or_else(|| format_err!("attachToTangle returned no trytes").into())
+            }),
+        )
+    }
+}
+
+/// Chains the transactions like a real attachment but writes zero nonces
+/// instead of doing proof of work. Only nodes that don't check the
+/// minimum weight magnitude, such as simulators, accept the result.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FakePow;
+
+impl PowProvider for FakePow {
+    fn attach(
+        &self,
+        _client: &AsyncClient,
+        trunk_transaction: &str,
+        branch_transaction: &str,
+        _min_weight_magnitude: usize,
+        trytes: &[String],
+        clock: Arc<dyn Clock>,
+    ) -> BoxFuture<Vec<String>> {
+        let trunk_transaction = trunk_transaction.to_string();
+        let branch_transaction = branch_transaction.to_string();
+        let trytes = trytes.to_vec();
+        spawn_blocking(move || {
+            attach_with(
+                &trunk_transaction,
+                &branch_transaction,
+                &trytes,
+                &*clock,
+                |mut tx_trytes| {
+                    tx_trytes.truncate(tx_trytes.len() - NONCE_TRYTES);
+                    tx_trytes.push_str(&"9".repeat(NONCE_TRYTES));
+                    Ok(tx_trytes)
+                },
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{FixedClock, SystemClock};
+    use crate::transport::InMemoryTransport;
+    use iota_model::Transaction;
+    use std::convert::TryInto;
+
+    const TRUNK: &str =
+        "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA";
+    const BRANCH: &str =
+        "BBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBB";
+
+    fn bundle_trytes(count: usize) -> Vec<String> {
+        let trytes: String = Transaction {
+            signature_fragments: "9".repeat(2187),
+            address: "9".repeat(81),
+            obsolete_tag: "FAKE9POW9999999999999999999".to_string(),
+            bundle: "9".repeat(81),
+            trunk_transaction: "9".repeat(81),
+            branch_transaction: "9".repeat(81),
+            tag: "9".repeat(27),
+            nonce: "ABC".repeat(9),
+            ..Transaction::default()
+        }
+        .try_into()
+        .unwrap();
+        vec![trytes; count]
+    }
+
+    #[test]
+    fn test_fake_pow_chains_transactions() {
+        let client = AsyncClient::new("http://localhost:14265");
+        let attached = FakePow
+            .attach(
+                &client,
+                TRUNK,
+                BRANCH,
+                14,
+                &bundle_trytes(3),
+                Arc::new(FixedClock::new(42)),
+            )
+            .wait()
+            .unwrap();
+        let txs: Vec<Transaction> = attached.iter().map(|t| t.parse().unwrap()).collect();
+
+        assert_eq!(txs.len(), 3);
+        assert_eq!(txs[2].trunk_transaction, TRUNK);
+        assert_eq!(txs[2].branch_transaction, BRANCH);
+        assert_eq!(txs[1].trunk_transaction, txs[2].hash);
+        assert_eq!(txs[0].trunk_transaction, txs[1].hash);
+        assert_eq!(txs[0].branch_transaction, TRUNK);
+        assert!(txs.iter().all(|tx| tx.nonce == "9".repeat(27)));
+        assert!(txs.iter().all(|tx| tx.attachment_timestamp == 42));
+        assert!(txs.iter().all(|tx| tx.tag == "FAKE9POW9999999999999999999"));
+    }
+
+    #[test]
+    fn test_invalid_trunk_is_rejected() {
+        let client = AsyncClient::new("http://localhost:14265");
+        let res = FakePow
+            .attach(
+                &client,
+                "BAD",
+                BRANCH,
+                14,
+                &bundle_trytes(1),
+                Arc::new(SystemClock),
+            )
+            .wait();
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_remote_pow_requires_trytes() {
+        let transport = InMemoryTransport::new();
+        transport.respond("attachToTangle", json!({ "duration": 1 }));
+        let client = AsyncClient::new("http://localhost:14265").with_transport(transport);
+        let res = RemotePow::new()
+            .attach(
+                &client,
+                TRUNK,
+                BRANCH,
+                14,
+                &bundle_trytes(1),
+                Arc::new(SystemClock),
+            )
+            .wait()
+            .unwrap_err();
+        assert!(res.to_string().contains("returned no trytes"));
+    }
+}
@@ -0,0 +1,172 @@
+use std::fmt::Debug;
+
+use crate::error::ClientError;
+use crate::Result;
+
+/// Quorum settings for security critical reads. When configured on a
+/// client, `get_balances`, `get_inclusion_states` and
+/// `were_addresses_spent_from` are sent to `nodes` nodes of the pool,
+/// and succeed only if at least `threshold` of them return the same answer.
+/// A threshold of half the nodes or less can be reached by two different
+/// answers, in which case the read fails as if no answer reached it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Quorum {
+    /// Number of nodes every quorum read is sent to
+    pub nodes: usize,
+    /// Number of identical answers required
+    pub threshold: usize,
+}
+
+impl Quorum {
+    /// Create new quorum settings, requiring `threshold` identical answers out of `nodes`
+    pub fn new(nodes: usize, threshold: usize) -> Quorum {
+        Quorum { nodes, threshold }
+    }
+
+    /// Checks that the settings can be satisfied at all
+    pub(crate) fn validate(&self) -> Result<()> {
+        ensure_input!(
+            self.threshold > 0 && self.threshold <= self.nodes,
+            "Invalid quorum: {} out of {} nodes",
+            self.threshold,
+            self.nodes
+        );
+        Ok(())
+    }
+}
+
+/// Picks the answer at least `threshold` nodes agree on, comparing answers by
+/// `key`. If no answer or more than one reaches the threshold, every node's
+/// answer is returned in a `QuorumNotReached` error, unless all nodes failed,
+/// in which case the first failure is returned as is.
+pub(crate) fn decide<T, K, F>(
+    command: &str,
+    threshold: usize,
+    answers: Vec<(String, Result<T>)>,
+    key: F,
+) -> Result<T>
+where
+    K: PartialEq + Debug,
+    F: Fn(&T) -> K,
+{
+    let mut groups: Vec<(K, usize)> = Vec::new();
+    for answer in answers
+        .iter()
+        .filter_map(|(_, answer)| answer.as_ref().ok())
+    {
+        let answer = key(answer);
+        match groups.iter_mut().find(|(group, _)| *group == answer) {
+            Some((_, count)) => *count += 1,
+            None => groups.push((answer, 1)),
+        }
+    }
+
+    let mut agreed = groups.into_iter().filter(|(_, count)| *count >= threshold);
+    if let (Some((agreed, _)), None) = (agreed.next(), agreed.next()) {
+        return Ok(answers
+            .into_iter()
+            .filter_map(|(_, answer)| answer.ok())
+            .find(|answer| key(answer) == agreed)
+            .expect("an agreed answer comes from a node"));
+    }
+
+    if answers.iter().all(|(_, answer)| answer.is_err()) {
+        if let Some((_, Err(e))) = answers.into_iter().next() {
+            return Err(e);
+        }
+        return Err(ClientError::Other(format_err!("No IRI node configured")));
+    }
+
+    Err(ClientError::QuorumNotReached {
+        command: command.to_string(),
+        threshold,
+        answers: answers
+            .into_iter()
+            .map(|(uri, answer)| {
+                let answer = match answer {
+                    Ok(answer) => format!("{:?}", key(&answer)),
+                    Err(e) => format!("error: {}", e),
+                };
+                (uri, answer)
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answers(values: &[std::result::Result<u32, &str>]) -> Vec<(String, Result<u32>)> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                (
+                    format!("http://node{}", i),
+                    value.map_err(|e| ClientError::Iri(e.to_string())),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_quorum_reached() {
+        let res = decide("getBalances", 2, answers(&[Ok(1), Ok(2), Ok(2)]), |v| *v);
+        assert_eq!(res.unwrap(), 2);
+    }
+
+    #[test]
+    fn test_quorum_not_reached_lists_answers() {
+        let res = decide(
+            "getBalances",
+            2,
+            answers(&[Ok(1), Ok(2), Err("down")]),
+            |v| *v,
+        );
+        match res.unwrap_err() {
+            ClientError::QuorumNotReached { answers, .. } => {
+                assert_eq!(answers.len(), 3);
+                assert_eq!(answers[0], ("http://node0".to_string(), "1".to_string()));
+                assert_eq!(answers[2].1, "error: down");
+            }
+            e => panic!("unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_quorum_not_reached_when_two_answers_reach_threshold() {
+        let res = decide(
+            "getBalances",
+            2,
+            answers(&[Ok(1), Ok(2), Ok(1), Ok(2)]),
+            |v| *v,
+        );
+        match res.unwrap_err() {
+            ClientError::QuorumNotReached { answers, .. } => assert_eq!(answers.len(), 4),
+            e => panic!("unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_quorum_all_failed() {
+        let res = decide(
+            "getBalances",
+            1,
+            answers(&[Err("first"), Err("second")]),
+            |v| *v,
+        );
+        assert_eq!(res.unwrap_err().to_string(), "first");
+    }
+
+    #[test]
+    fn test_quorum_validate() {
+        assert!(Quorum::new(3, 2).validate().is_ok());
+        assert!(Quorum::new(2, 3).validate().is_err());
+        assert!(Quorum::new(2, 0).validate().is_err());
+        assert!(Quorum::new(1, 1).validate().is_ok());
+        assert!(Quorum::new(4, 2).validate().is_ok());
+        assert!(Quorum::new(5, 2).validate().is_ok());
+        assert!(Quorum::new(4, 3).validate().is_ok());
+    }
+}
@@ -0,0 +1,238 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+use tokio::prelude::future::Loop;
+use tokio::prelude::*;
+use tokio::timer::Delay;
+
+use crate::error::{ClientError, ErrorKind};
+use crate::BoxFuture;
+
+/// Commands that must not be sent twice without an explicit opt-in,
+/// as they are expensive or their outcome differs on every call
+pub const NON_IDEMPOTENT_COMMANDS: &[&str] = &["attachToTangle"];
+
+/// A hook called before every retry
+pub type RetryHook = Arc<dyn Fn(&RetryEvent<'_>) + Send + Sync>;
+
+/// Describes a retry about to happen, as given to the `on_retry` hook
+#[derive(Debug)]
+pub struct RetryEvent<'a> {
+    /// The IRI command being retried
+    pub command: &'a str,
+    /// The attempt that just failed, starting at 1
+    pub attempt: u32,
+    /// How long the client waits before the next attempt
+    pub delay: Duration,
+    /// The error the failed attempt returned
+    pub error: &'a ClientError,
+}
+
+/// How commands failing with a retryable error are retried. An attempt
+/// covers every node of the pool, so a command is only retried once
+/// failing over could not help. Waits between attempts grow exponentially
+/// from `initial_backoff` up to `max_backoff`, and with `jitter` a random
+/// part of each wait is dropped so that clients don't retry in lockstep.
+///
+/// `attachToTangle` is never retried, nor sent to another node of the
+/// pool when one cannot be reached, unless `retry_non_idempotent` is set.
+///
+/// ```
+/// use std::time::Duration;
+/// use iota_client::ErrorKind;
+/// use iota_client::retry::RetryPolicy;
+///
+/// let policy = RetryPolicy::new(5)
+///     .with_backoff(Duration::from_millis(200), Duration::from_secs(10))
+///     .retry_on(&[ErrorKind::Transport, ErrorKind::Http, ErrorKind::IriException])
+///     .on_retry(|event| eprintln!("retrying {} after {:?}: {}", event.command, event.delay, event.error));
+/// let client = iota_client::Client::new("https://nodes.devnet.iota.org").with_retry(policy);
+/// ```
+#[derive(Clone)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one
+    pub max_attempts: u32,
+    /// Wait before the first retry
+    pub initial_backoff: Duration,
+    /// Upper bound of any wait between attempts
+    pub max_backoff: Duration,
+    /// Randomizes waits between half and all of their computed duration
+    pub jitter: bool,
+    /// Kinds of errors worth retrying
+    pub retryable: Vec<ErrorKind>,
+    /// Also retry commands listed in `NON_IDEMPOTENT_COMMANDS`
+    pub retry_non_idempotent: bool,
+    on_retry: Option<RetryHook>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            jitter: true,
+            retryable: vec![ErrorKind::Transport, ErrorKind::Http],
+            retry_non_idempotent: false,
+            on_retry: None,
+        }
+    }
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("initial_backoff", &self.initial_backoff)
+            .field("max_backoff", &self.max_backoff)
+            .field("jitter", &self.jitter)
+            .field("retryable", &self.retryable)
+            .field("retry_non_idempotent", &self.retry_non_idempotent)
+            .field("on_retry", &self.on_retry.is_some())
+            .finish()
+    }
+}
+
+impl RetryPolicy {
+    /// Create a new policy making up to `max_attempts` attempts,
+    /// retrying transport and HTTP errors
+    pub fn new(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// Sets the wait before the first retry, and the upper bound of any wait
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> RetryPolicy {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Enables or disables jitter
+    pub fn with_jitter(mut self, jitter: bool) -> RetryPolicy {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Sets the kinds of errors worth retrying
+    pub fn retry_on(mut self, kinds: &[ErrorKind]) -> RetryPolicy {
+        self.retryable = kinds.to_vec();
+        self
+    }
+
+    /// Allows retrying commands listed in `NON_IDEMPOTENT_COMMANDS`
+    pub fn retry_non_idempotent(mut self, enabled: bool) -> RetryPolicy {
+        self.retry_non_idempotent = enabled;
+        self
+    }
+
+    /// Calls `hook` before every retry, e.g. to log it
+    pub fn on_retry<F>(mut self, hook: F) -> RetryPolicy
+    where
+        F: Fn(&RetryEvent<'_>) + Send + Sync + 'static,
+    {
+        self.on_retry = Some(Arc::new(hook));
+        self
+    }
+
+    /// Returns true if `command` may be retried under this policy
+    pub fn applies_to(&self, command: &str) -> bool {
+        self.retry_non_idempotent || !NON_IDEMPOTENT_COMMANDS.contains(&command)
+    }
+
+    /// Returns true if `error` is worth retrying under this policy
+    pub fn is_retryable(&self, error: &ClientError) -> bool {
+        self.retryable.contains(&error.kind())
+    }
+
+    /// Returns the wait after the given failed attempt, starting at 1
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let delay = self
+            .initial_backoff
+            .checked_mul(factor)
+            .map_or(self.max_backoff, |delay| delay.min(self.max_backoff));
+        if self.jitter && delay > Duration::from_millis(0) {
+            let millis = delay.as_millis() as u64;
+            Duration::from_millis(rand::thread_rng().gen_range(millis / 2, millis + 1))
+        } else {
+            delay
+        }
+    }
+}
+
+/// Runs `attempt` until it succeeds, fails with an error that is not
+/// retryable, or `policy.max_attempts` attempts were made
+pub(crate) fn retry<T, F>(policy: RetryPolicy, command: String, attempt: F) -> BoxFuture<T>
+where
+    T: Send + 'static,
+    F: Fn() -> BoxFuture<T> + Send + 'static,
+{
+    Box::new(future::loop_fn(1, move |n| {
+        let policy = policy.clone();
+        let command = command.clone();
+        attempt().then(move |res| -> BoxFuture<Loop<T, u32>> {
+            match res {
+                Ok(resp) => Box::new(future::ok(Loop::Break(resp))),
+                Err(e) if n < policy.max_attempts && policy.is_retryable(&e) => {
+                    let delay = policy.backoff(n);
+                    if let Some(hook) = &policy.on_retry {
+                        hook(&RetryEvent {
+                            command: &command,
+                            attempt: n,
+                            delay,
+                            error: &e,
+                        });
+                    }
+                    Box::new(
+                        Delay::new(Instant::now() + delay)
+                            .map_err(|e| ClientError::Other(e.into()))
+                            .map(move |_| Loop::Continue(n + 1)),
+                    )
+                }
+                Err(e) => Box::new(future::err(e)),
+            }
+        })
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_grows_until_max() {
+        let policy = RetryPolicy::new(10)
+            .with_backoff(Duration::from_millis(100), Duration::from_millis(500))
+            .with_jitter(false);
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(4), Duration::from_millis(500));
+        assert_eq!(policy.backoff(40), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_backoff_jitter_stays_within_bounds() {
+        let policy =
+            RetryPolicy::new(10).with_backoff(Duration::from_millis(100), Duration::from_secs(1));
+        for _ in 0..100 {
+            let delay = policy.backoff(2);
+            assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(200));
+        }
+    }
+
+    #[test]
+    fn test_non_idempotent_commands_need_opt_in() {
+        let policy = RetryPolicy::default();
+        assert!(policy.applies_to("getTrytes"));
+        assert!(!policy.applies_to("attachToTangle"));
+        assert!(policy
+            .retry_non_idempotent(true)
+            .applies_to("attachToTangle"));
+    }
+}
@@ -0,0 +1,119 @@
+use std::fmt::Debug;
+
+use iota_model::Input;
+
+use crate::Result;
+
+/// Chooses which inputs fund a value transfer.
+///
+/// `prepare_transfers` hands the selector every input holding funds, minus
+/// the excluded addresses, and the total value of the transfers. The
+/// selector returns the inputs to spend, taken from the candidates; their
+/// balance above the total goes to the remainder address. Selectors may
+/// return fewer funds than needed, `prepare_transfers` then fails with
+/// "Not enough balance.".
+pub trait InputSelector: Debug + Send + Sync {
+    /// Chooses inputs among `candidates` to cover `total_value`
+    fn select(&self, candidates: &[Input], total_value: i64) -> Result<Vec<Input>>;
+}
+
+/// Spends inputs by ascending key index, the oldest addresses first,
+/// until the transfers are covered. This is the default.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OldestFirst;
+
+impl InputSelector for OldestFirst {
+    fn select(&self, candidates: &[Input], total_value: i64) -> Result<Vec<Input>> {
+        let mut candidates = candidates.to_vec();
+        candidates.sort_by_key(|input| input.key_index);
+        Ok(take_until_covered(candidates, total_value))
+    }
+}
+
+/// Spends the inputs with the largest balances first, which minimises
+/// the number of inputs and so the size of the bundle
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LargestFirst;
+
+impl InputSelector for LargestFirst {
+    fn select(&self, candidates: &[Input], total_value: i64) -> Result<Vec<Input>> {
+        let mut candidates = candidates.to_vec();
+        candidates.sort_by_key(|input| -input.balance);
+        Ok(take_until_covered(candidates, total_value))
+    }
+}
+
+/// Spends the single input with the smallest balance covering the
+/// transfers, keeping large inputs intact. When no single input is
+/// enough, falls back to `LargestFirst`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SmallestSufficient;
+
+impl InputSelector for SmallestSufficient {
+    fn select(&self, candidates: &[Input], total_value: i64) -> Result<Vec<Input>> {
+        let sufficient = candidates
+            .iter()
+            .filter(|input| input.balance >= total_value)
+            .min_by_key(|input| input.balance);
+        match sufficient {
+            Some(input) => Ok(vec![input.clone()]),
+            None => LargestFirst.select(candidates, total_value),
+        }
+    }
+}
+
+/// Spends every candidate, consolidating all funds, dust included, into
+/// the outputs and the remainder address
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ConsolidateAll;
+
+impl InputSelector for ConsolidateAll {
+    fn select(&self, candidates: &[Input], _total_value: i64) -> Result<Vec<Input>> {
+        Ok(candidates.to_vec())
+    }
+}
+
+/// Takes inputs in order until their balance covers `total_value`
+fn take_until_covered(candidates: Vec<Input>, total_value: i64) -> Vec<Input> {
+    let mut covered = 0;
+    candidates
+        .into_iter()
+        .take_while(|input| {
+            let take = covered < total_value;
+            covered += input.balance;
+            take
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(key_index: usize, balance: i64) -> Input {
+        Input {
+            address: key_index.to_string(),
+            balance,
+            key_index,
+            security: 2,
+        }
+    }
+
+    fn indexes(inputs: Vec<Input>) -> Vec<usize> {
+        inputs.into_iter().map(|input| input.key_index).collect()
+    }
+
+    #[test]
+    fn test_selectors() {
+        let candidates = vec![input(3, 50), input(0, 5), input(1, 100), input(2, 20)];
+        let select = |selector: &dyn InputSelector, total| {
+            indexes(selector.select(&candidates, total).unwrap())
+        };
+        assert_eq!(select(&OldestFirst, 100), vec![0, 1]);
+        assert_eq!(select(&LargestFirst, 120), vec![1, 3]);
+        assert_eq!(select(&SmallestSufficient, 40), vec![3]);
+        assert_eq!(select(&SmallestSufficient, 160), vec![1, 3, 2]);
+        assert_eq!(select(&ConsolidateAll, 1), vec![3, 0, 1, 2]);
+        assert_eq!(select(&OldestFirst, 0), Vec::<usize>::new());
+    }
+}
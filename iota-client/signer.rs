@@ -0,0 +1,198 @@
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+use std::thread;
+
+use iota_conversion::Trinary;
+use iota_model::{Bundle, Input};
+
+use crate::{spawn_blocking, BoxFuture, Result};
+
+/// Derives the addresses of a seed and signs bundles with its keys.
+///
+/// Every extended API taking a seed goes through a signer, so the seed
+/// doesn't have to live in the process of the client: an implementation
+/// can forward both calls to a separate signing process, over a Unix
+/// socket or a pipe, and only addresses and signatures cross the boundary.
+/// `SeedSigner` derives both from a seed held in memory.
+pub trait Signer: fmt::Debug + Send + Sync {
+    /// Returns the address of key `index` at `security`, without checksum
+    fn address(&self, index: usize, security: usize) -> Result<String>;
+
+    /// Signs `bundle_hash` with key `index` at `security`, returning one
+    /// signature fragment per security level
+    fn sign(&self, bundle_hash: &str, index: usize, security: usize) -> Result<Vec<String>>;
+}
+
+/// Signs with the keys of a seed held in memory
+#[derive(Clone)]
+pub struct SeedSigner {
+    seed: Vec<i8>,
+}
+
+impl fmt::Debug for SeedSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SeedSigner").finish()
+    }
+}
+
+impl SeedSigner {
+    /// Create a signer from a tryte-encoded seed
+    pub fn new(seed: &str) -> Result<SeedSigner> {
+        ensure_input!(iota_validation::is_trytes(seed), "Invalid seed.");
+        Ok(SeedSigner { seed: seed.trits() })
+    }
+}
+
+impl Signer for SeedSigner {
+    fn address(&self, index: usize, security: usize) -> Result<String> {
+        let key = iota_signing::key(&self.seed, index, security)?;
+        let digests = iota_signing::digests(&key)?;
+        Ok(iota_signing::address(&digests)?.trytes()?)
+    }
+
+    fn sign(&self, bundle_hash: &str, index: usize, security: usize) -> Result<Vec<String>> {
+        Ok(iota_signing::bundle_signature(
+            &self.seed,
+            index,
+            security,
+            bundle_hash,
+        )?)
+    }
+}
+
+/// Anything the extended APIs accept in place of a seed: a tryte-encoded
+/// seed, a `SeedSigner`, or any other `Signer` behind an `Arc`
+pub trait IntoSigner {
+    /// Converts into a shared signer, failing for an invalid seed
+    fn into_signer(self) -> Result<Arc<dyn Signer>>;
+}
+
+impl IntoSigner for &str {
+    fn into_signer(self) -> Result<Arc<dyn Signer>> {
+        Ok(Arc::new(SeedSigner::new(self)?))
+    }
+}
+
+impl IntoSigner for &String {
+    fn into_signer(self) -> Result<Arc<dyn Signer>> {
+        self.as_str().into_signer()
+    }
+}
+
+impl IntoSigner for SeedSigner {
+    fn into_signer(self) -> Result<Arc<dyn Signer>> {
+        Ok(Arc::new(self))
+    }
+}
+
+impl<S: Signer + 'static> IntoSigner for Arc<S> {
+    fn into_signer(self) -> Result<Arc<dyn Signer>> {
+        Ok(self)
+    }
+}
+
+impl IntoSigner for Arc<dyn Signer> {
+    fn into_signer(self) -> Result<Arc<dyn Signer>> {
+        Ok(self)
+    }
+}
+
+/// Generates the addresses of the key indexes in `indexes`, spread
+/// across the CPU cores
+pub(crate) fn addresses(
+    signer: &dyn Signer,
+    security: usize,
+    indexes: Range<usize>,
+    checksum: bool,
+) -> Result<Vec<String>> {
+    let indexes: Vec<usize> = indexes.collect();
+    if indexes.is_empty() {
+        return Ok(Vec::new());
+    }
+    let chunk_size = indexes.len().div_ceil(num_cpus::get());
+    thread::scope(|scope| {
+        let workers: Vec<_> = indexes
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|&index| {
+                            let address = signer.address(index, security)?;
+                            if checksum {
+                                Ok(iota_signing::checksum::add_checksum(&address)?)
+                            } else {
+                                Ok(address)
+                            }
+                        })
+                        .collect::<Result<Vec<String>>>()
+                })
+            })
+            .collect();
+        let mut addresses = Vec::with_capacity(indexes.len());
+        for worker in workers {
+            match worker.join() {
+                Ok(chunk) => addresses.extend(chunk?),
+                Err(_) => return Err(format_err!("Address generation panicked").into()),
+            }
+        }
+        Ok(addresses)
+    })
+}
+
+/// Generates the addresses of the key indexes in `indexes` like
+/// `addresses`, off the executor polling the returned future
+pub(crate) fn spawn_addresses(
+    signer: Arc<dyn Signer>,
+    security: usize,
+    indexes: Range<usize>,
+    checksum: bool,
+) -> BoxFuture<Vec<String>> {
+    spawn_blocking(move || addresses(&*signer, security, indexes, checksum))
+}
+
+/// Signs every input of a finalized bundle with `signer`
+pub(crate) fn sign_bundle(
+    signer: &dyn Signer,
+    inputs: &[Input],
+    bundle: &mut Bundle,
+) -> Result<()> {
+    let bundle_hash = match bundle.first() {
+        Some(tx) => tx.bundle.clone(),
+        None => return Ok(()),
+    };
+    for input in inputs {
+        let fragments = signer.sign(&bundle_hash, input.key_index, input.security)?;
+        bundle.add_signature(&input.address, &fragments)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_SEED: &str =
+        "IHDEENZYITYVYSPKAURUZAQKGVJEREFDJMYTANNXXGPZ9GJWTEOJJ9IPMXOGZNQLSNMFDSQOTZAEETUEA";
+
+    #[test]
+    fn test_seed_signer() {
+        let signer = SeedSigner::new(TEST_SEED).unwrap();
+        assert_eq!(
+            signer.address(0, 2).unwrap(),
+            "LXQHWNY9CQOHPNMKFJFIJHGEPAENAOVFRDIBF99PPHDTWJDCGHLYETXT9NPUVSNKT9XDTDYNJKJCPQMZC"
+        );
+        let hash = "9".repeat(81);
+        let fragments = signer.sign(&hash, 0, 2).unwrap();
+        assert_eq!(fragments.len(), 2);
+        assert!(iota_signing::validate_signatures(
+            &signer.address(0, 2).unwrap(),
+            &fragments,
+            &hash
+        )
+        .unwrap());
+        assert!(SeedSigner::new("not a seed").is_err());
+        assert_eq!(format!("{:?}", signer), "SeedSigner");
+    }
+}
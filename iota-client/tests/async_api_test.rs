@@ -0,0 +1,451 @@
+use iota_client::options::*;
+use iota_client::transport::InMemoryTransport;
+use iota_client::{AsyncClient, BatchSizes, ConfirmationPolicy, ErrorKind, Quorum, RetryPolicy};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::prelude::*;
+use tokio::runtime::Runtime;
+
+mod common;
+use crate::common::*;
+
+#[test]
+fn test_async_attach_to_tangle_empty() {
+    let client = async_client_init();
+    let mut runtime = Runtime::new().unwrap();
+
+    let opt = AttachOptions::default();
+    let res = runtime.block_on(client.attach_to_tangle(opt)).unwrap_err();
+    assert!(res
+        .to_string()
+        .contains("Provided trunk transaction is not valid"));
+}
+
+#[test]
+fn test_async_get_trytes_empty() {
+    let client = async_client_init();
+    let mut runtime = Runtime::new().unwrap();
+
+    let res = runtime
+        .block_on(client.get_trytes(&["".into()]))
+        .unwrap_err();
+    assert!(res.to_string().contains("Provided hashes are not valid"));
+}
+
+#[test]
+fn test_async_chained_inside_executor() {
+    let client = async_client_init();
+    let mut runtime = Runtime::new().unwrap();
+
+    let chained = future::lazy(move || {
+        client
+            .store_transactions(&["".into()])
+            .map(|_| ())
+            .or_else(|e| {
+                assert!(e.to_string().contains("Provided trytes are not valid"));
+                Ok::<(), iota_client::ClientError>(())
+            })
+    });
+    runtime.block_on(chained).unwrap();
+}
+
+#[test]
+fn test_async_get_new_address_with_total() {
+    let client = async_client_init();
+    let mut runtime = Runtime::new().unwrap();
+
+    let res = runtime
+        .block_on(client.get_new_address(
+            TEST_SEED,
+            false,
+            true,
+            GetNewAddressOptions {
+                security: Some(2),
+                index: Some(0),
+                total: Some(2),
+                ..GetNewAddressOptions::default()
+            },
+        ))
+        .unwrap();
+    assert_eq!(res.len(), 2);
+    assert_eq!(res[0], TEST_SEED_ADDRESS_0);
+}
+
+#[test]
+fn test_async_pool_fails_over_unreachable_nodes() {
+    let transport = InMemoryTransport::new();
+    transport.respond("getTips", json!({"hashes": [], "duration": 0}));
+    transport.set_unreachable("http://node1");
+    let client = AsyncClient::with_nodes(&["http://node1", "http://node2"])
+        .with_transport(transport.clone());
+    let mut runtime = Runtime::new().unwrap();
+
+    runtime.block_on(client.get_tips()).unwrap();
+    assert_eq!(client.nodes.healthy_uris(), vec!["http://node2"]);
+
+    transport.set_unreachable("http://node2");
+    let res = runtime.block_on(client.get_tips()).unwrap_err();
+    assert!(res.is_transport());
+    assert!(client.nodes.healthy_uris().is_empty());
+}
+
+/// A getNodeInfo response of a node synced at milestone 10
+fn node_info() -> Value {
+    json!({
+        "appName": "IRI", "appVersion": "1.8.0", "jreAvailableProcessors": 8,
+        "jreFreeMemory": 0, "jreMaxMemory": 0, "jreTotalMemory": 0, "jreVersion": "1.8",
+        "latestMilestone": TEST_MILESTONE_0, "latestMilestoneIndex": 10,
+        "latestSolidSubtangleMilestone": TEST_MILESTONE_0,
+        "latestSolidSubtangleMilestoneIndex": 10, "milestoneStartIndex": 0,
+        "neighbors": 0, "packetsQueueSize": 0, "time": 0, "tips": 0,
+        "transactionsToRequest": 0
+    })
+}
+
+#[test]
+fn test_async_health_check_marks_unsynced_nodes() {
+    let transport = InMemoryTransport::new();
+    let mut node_info = node_info();
+    transport.respond("getNodeInfo", node_info.clone());
+    node_info["latestSolidSubtangleMilestoneIndex"] = json!(9);
+    transport.respond("getNodeInfo", node_info);
+    let client = AsyncClient::with_nodes(&["http://synced", "http://lagging", "http://down"])
+        .with_transport(transport.clone());
+    transport.set_unreachable("http://down");
+    let mut runtime = Runtime::new().unwrap();
+
+    let healthy = runtime.block_on(client.health_check()).unwrap();
+    assert_eq!(healthy, vec!["http://synced"]);
+    assert!(!client.nodes.is_healthy("http://lagging"));
+    assert!(!client.nodes.is_healthy("http://down"));
+}
+
+#[test]
+fn test_async_quorum_rejects_invalid_settings() {
+    let client = async_client_init().with_quorum(Quorum::new(1, 2));
+    let mut runtime = Runtime::new().unwrap();
+
+    let res = runtime
+        .block_on(client.were_addresses_spent_from(&[TEST_SEED_ADDRESS_0.into()]))
+        .unwrap_err();
+    assert!(res.to_string().contains("Invalid quorum"));
+}
+
+#[test]
+fn test_async_quorum_reports_disagreement() {
+    let transport = InMemoryTransport::new();
+    transport.respond(
+        "getBalances",
+        json!({"balances": ["10"], "milestoneIndex": 5, "references": []}),
+    );
+    transport.respond(
+        "getBalances",
+        json!({"balances": ["10"], "milestoneIndex": 6, "references": []}),
+    );
+    let client = AsyncClient::with_nodes(&["http://node1", "http://node2"])
+        .with_transport(transport)
+        .with_quorum(Quorum::new(2, 2));
+    let mut runtime = Runtime::new().unwrap();
+
+    let res = runtime
+        .block_on(client.get_balances(GetBalancesOptions {
+            addresses: vec![TEST_ADDRESS_0.into()],
+            ..GetBalancesOptions::default()
+        }))
+        .unwrap_err();
+    match res {
+        iota_client::ClientError::QuorumNotReached { answers, .. } => {
+            assert_eq!(answers.len(), 2);
+            assert_ne!(answers[0].1, answers[1].1);
+        }
+        e => panic!("unexpected error: {:?}", e),
+    }
+}
+
+fn retry_policy(retries: Arc<AtomicUsize>) -> RetryPolicy {
+    RetryPolicy::new(3)
+        .with_backoff(Duration::from_millis(1), Duration::from_millis(1))
+        .retry_on(&[ErrorKind::Iri])
+        .on_retry(move |_| {
+            retries.fetch_add(1, Ordering::SeqCst);
+        })
+}
+
+#[test]
+fn test_async_retry_recovers_from_transient_error() {
+    let transport = InMemoryTransport::new();
+    transport.respond("getTips", json!({"error": "Too many requests"}));
+    transport.respond("getTips", json!({"hashes": [], "duration": 0}));
+    let retries = Arc::new(AtomicUsize::new(0));
+    let client = async_client_init()
+        .with_transport(transport.clone())
+        .with_retry(retry_policy(retries.clone()));
+    let mut runtime = Runtime::new().unwrap();
+
+    let res = runtime.block_on(client.get_tips()).unwrap();
+    assert!(res.hashes().is_empty());
+    assert_eq!(retries.load(Ordering::SeqCst), 1);
+    assert_eq!(transport.requests().len(), 2);
+}
+
+#[test]
+fn test_async_retry_gives_up_after_max_attempts() {
+    let (_, transport) = scripted_client("getTips", json!({"error": "Too many requests"}));
+    let retries = Arc::new(AtomicUsize::new(0));
+    let client = async_client_init()
+        .with_transport(transport.clone())
+        .with_retry(retry_policy(retries.clone()));
+    let mut runtime = Runtime::new().unwrap();
+
+    let res = runtime.block_on(client.get_tips()).unwrap_err();
+    assert_eq!(res.kind(), ErrorKind::Iri);
+    assert_eq!(retries.load(Ordering::SeqCst), 2);
+    assert_eq!(transport.requests().len(), 3);
+}
+
+#[test]
+fn test_async_retry_skips_attach_to_tangle_unless_opted_in() {
+    let (_, transport) = scripted_client("attachToTangle", json!({"error": "Too many requests"}));
+    let retries = Arc::new(AtomicUsize::new(0));
+    let trytes = vec![TEST_TX_HASH.to_string()];
+    let opt = AttachOptions {
+        trunk_transaction: TEST_TRUNK_HASH,
+        branch_transaction: TEST_BRANCH_HASH,
+        trytes: &trytes,
+        ..AttachOptions::default()
+    };
+    let mut runtime = Runtime::new().unwrap();
+
+    let client = async_client_init()
+        .with_transport(transport.clone())
+        .with_retry(retry_policy(retries.clone()));
+    runtime
+        .block_on(client.attach_to_tangle(opt.clone()))
+        .unwrap_err();
+    assert_eq!(transport.requests().len(), 1);
+
+    let client = client.with_retry(retry_policy(retries.clone()).retry_non_idempotent(true));
+    runtime.block_on(client.attach_to_tangle(opt)).unwrap_err();
+    assert_eq!(transport.requests().len(), 4);
+    assert_eq!(retries.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_async_pool_fails_over_attach_to_tangle_only_if_opted_in() {
+    let transport = InMemoryTransport::new();
+    transport.set_unreachable("http://node1");
+    transport.set_unreachable("http://node2");
+    let trytes = vec![TEST_TX_HASH.to_string()];
+    let opt = AttachOptions {
+        trunk_transaction: TEST_TRUNK_HASH,
+        branch_transaction: TEST_BRANCH_HASH,
+        trytes: &trytes,
+        ..AttachOptions::default()
+    };
+    let mut runtime = Runtime::new().unwrap();
+
+    let client = AsyncClient::with_nodes(&["http://node1", "http://node2"])
+        .with_transport(transport.clone());
+    let res = runtime
+        .block_on(client.attach_to_tangle(opt.clone()))
+        .unwrap_err();
+    assert!(res.is_transport());
+    assert_eq!(transport.requests().len(), 1);
+
+    let retries = Arc::new(AtomicUsize::new(0));
+    let client = client.with_retry(retry_policy(retries).retry_non_idempotent(true));
+    let res = runtime.block_on(client.attach_to_tangle(opt)).unwrap_err();
+    assert!(res.is_transport());
+    assert_eq!(transport.requests().len(), 3);
+}
+
+fn batched_client(transport: &InMemoryTransport) -> AsyncClient {
+    async_client_init()
+        .with_transport(transport.clone())
+        .with_batch_sizes(BatchSizes {
+            max_find_transactions: 2,
+            max_get_trytes: 2,
+            max_requests_list: 2,
+        })
+}
+
+#[test]
+fn test_async_get_trytes_in_batches() {
+    let transport = InMemoryTransport::new();
+    transport.respond("getTrytes", json!({"trytes": ["A", "B"]}));
+    transport.respond("getTrytes", json!({"trytes": ["C"]}));
+    let client = batched_client(&transport);
+    let mut runtime = Runtime::new().unwrap();
+
+    let hashes = vec![
+        TEST_BUNDLE_TX_0.to_string(),
+        TEST_BUNDLE_TX_1.to_string(),
+        TEST_MILESTONE_0.to_string(),
+    ];
+    let res = runtime.block_on(client.get_trytes(&hashes)).unwrap();
+    assert_eq!(res.take_trytes().unwrap(), vec!["A", "B", "C"]);
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].1["hashes"], json!(hashes[..2]));
+    assert_eq!(requests[1].1["hashes"], json!(hashes[2..]));
+}
+
+#[test]
+fn test_async_find_transactions_in_batches() {
+    let transport = InMemoryTransport::new();
+    transport.respond("findTransactions", json!({"hashes": ["A", "B"]}));
+    transport.respond("findTransactions", json!({"hashes": ["B", "C"]}));
+    let client = batched_client(&transport);
+    let mut runtime = Runtime::new().unwrap();
+
+    let res = runtime
+        .block_on(client.find_transactions(FindTransactionsOptions {
+            addresses: vec![
+                TEST_ADDRESS_0.into(),
+                TEST_SEED_ADDRESS_0.into(),
+                NULL_HASH.into(),
+            ],
+            tags: vec![TEST_TAG_0.into()],
+            ..FindTransactionsOptions::default()
+        }))
+        .unwrap();
+    assert_eq!(res.take_hashes().unwrap(), vec!["A", "B", "C"]);
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[1].1["addresses"], json!([NULL_HASH]));
+    assert_eq!(requests[1].1["tags"], json!([TEST_TAG_0]));
+}
+
+#[test]
+fn test_async_get_balances_in_batches_share_milestone() {
+    let transport = InMemoryTransport::new();
+    transport.respond(
+        "getBalances",
+        json!({"balances": ["1", "2"], "milestoneIndex": 5, "references": [TEST_MILESTONE_0]}),
+    );
+    transport.respond(
+        "getBalances",
+        json!({"balances": ["3"], "milestoneIndex": 5, "references": [TEST_MILESTONE_0]}),
+    );
+    let client = batched_client(&transport);
+    let mut runtime = Runtime::new().unwrap();
+
+    let res = runtime
+        .block_on(client.get_balances(GetBalancesOptions {
+            addresses: vec![
+                TEST_ADDRESS_0.into(),
+                TEST_SEED_ADDRESS_0.into(),
+                NULL_HASH.into(),
+            ],
+            ..GetBalancesOptions::default()
+        }))
+        .unwrap();
+    assert_eq!(res.milestone_index(), Some(5));
+    assert_eq!(res.take_balances().unwrap(), vec!["1", "2", "3"]);
+    let requests = transport.requests();
+    assert!(requests[0].1.get("tips").is_none());
+    assert_eq!(requests[1].1["tips"], json!([TEST_MILESTONE_0]));
+}
+
+#[test]
+fn test_async_get_inclusion_states_in_batches() {
+    let transport = InMemoryTransport::new();
+    transport.respond("getInclusionStates", json!({"states": [true, false]}));
+    transport.respond("getInclusionStates", json!({"states": [true]}));
+    let client = batched_client(&transport);
+    let mut runtime = Runtime::new().unwrap();
+
+    let res = runtime
+        .block_on(client.get_inclusion_states(GetInclusionStatesOptions {
+            transactions: vec![
+                TEST_BUNDLE_TX_0.into(),
+                TEST_BUNDLE_TX_1.into(),
+                TEST_MILESTONE_0.into(),
+            ],
+            ..GetInclusionStatesOptions::default()
+        }))
+        .unwrap();
+    assert_eq!(res.states().clone().unwrap(), vec![true, false, true]);
+    assert_eq!(transport.requests().len(), 2);
+}
+
+#[test]
+fn test_async_await_confirmation_requires_inclusion_states() {
+    let transport = InMemoryTransport::new();
+    transport.respond("getNodeInfo", node_info());
+    transport.respond("getInclusionStates", json!({ "states": [] }));
+    let client = async_client_init().with_transport(transport);
+    let mut runtime = Runtime::new().unwrap();
+
+    let policy = ConfirmationPolicy::poll_only(Duration::from_millis(10), Duration::from_secs(5));
+    let res = runtime
+        .block_on(client.await_confirmation(TEST_BUNDLE_TX_0, policy))
+        .unwrap_err();
+    assert!(res.to_string().contains("Inclusion states"));
+}
+
+#[test]
+fn test_async_get_new_address_checks_addresses_by_hashes() {
+    let transport = InMemoryTransport::new();
+    transport.respond(
+        "wereAddressesSpentFrom",
+        json!({ "states": [false, false, false] }),
+    );
+    transport.respond("findTransactions", json!({ "hashes": ["A"] }));
+    transport.respond("findTransactions", json!({ "hashes": ["A"] }));
+    transport.respond("findTransactions", json!({ "hashes": [] }));
+    transport.respond("findTransactions", json!({ "hashes": [] }));
+    let client = async_client_init().with_transport(transport.clone());
+    let mut runtime = Runtime::new().unwrap();
+
+    let res = runtime
+        .block_on(client.get_new_address(
+            TEST_SEED,
+            false,
+            true,
+            GetNewAddressOptions {
+                window: Some(3),
+                ..GetNewAddressOptions::default()
+            },
+        ))
+        .unwrap();
+    assert_eq!(res.len(), 2);
+    let commands: Vec<_> = transport
+        .requests()
+        .into_iter()
+        .map(|(_, command)| command["command"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(
+        commands.iter().filter(|c| *c == "findTransactions").count(),
+        4
+    );
+    assert!(!commands.iter().any(|c| c == "getTrytes"));
+}
+
+#[test]
+fn test_async_get_account_data_requires_balances() {
+    let transport = InMemoryTransport::new();
+    transport.respond("wereAddressesSpentFrom", json!({ "states": [true] }));
+    transport.respond("wereAddressesSpentFrom", json!({ "states": [false] }));
+    transport.respond("findTransactions", json!({ "hashes": [] }));
+    transport.respond("findTransactions", json!({ "hashes": [] }));
+    transport.respond(
+        "getBalances",
+        json!({"balances": [], "milestoneIndex": 5, "references": [TEST_MILESTONE_0]}),
+    );
+    let client = async_client_init().with_transport(transport);
+    let mut runtime = Runtime::new().unwrap();
+
+    let res = runtime
+        .block_on(client.get_account_data(
+            TEST_SEED,
+            GetAccountDataOptions {
+                window: Some(1),
+                ..GetAccountDataOptions::default()
+            },
+        ))
+        .unwrap_err();
+    assert!(res.to_string().contains("Balances of the used addresses"));
+}
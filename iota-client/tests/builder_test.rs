@@ -0,0 +1,73 @@
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use hyper::header::HeaderMap;
+use hyper::service::service_fn_ok;
+use hyper::{Body, Request, Response, Server};
+use iota_client::ClientBuilder;
+use tokio::prelude::*;
+
+/// Serves `getTips` on a local port after `delay`, recording the headers of every request
+fn serve(delay: Duration) -> (SocketAddr, Arc<Mutex<Vec<HeaderMap>>>) {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let headers = received.clone();
+    let server = Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(move || {
+        let headers = headers.clone();
+        service_fn_ok(move |req: Request<Body>| {
+            headers.lock().unwrap().push(req.headers().clone());
+            thread::sleep(delay);
+            Response::new(Body::from(r#"{"hashes": [], "duration": 0}"#))
+        })
+    });
+    let addr = server.local_addr();
+    thread::spawn(move || tokio::run(server.map_err(|e| panic!("{}", e))));
+    (addr, received)
+}
+
+#[test]
+fn test_builder_sends_configured_headers() {
+    let (addr, received) = serve(Duration::from_millis(0));
+    let mut client = ClientBuilder::new()
+        .node(&format!("http://{}", addr))
+        .basic_auth("user", Some("pass"))
+        .header("X-Request-Source", "wallet")
+        .user_agent("iota-test/1.0")
+        .api_version("2")
+        .build()
+        .unwrap();
+    client.get_tips().unwrap();
+
+    let headers = received.lock().unwrap()[0].clone();
+    assert_eq!(headers["authorization"], "Basic dXNlcjpwYXNz");
+    assert_eq!(headers["x-request-source"], "wallet");
+    assert_eq!(headers["user-agent"], "iota-test/1.0");
+    assert_eq!(headers["x-iota-api-version"], "2");
+}
+
+#[test]
+fn test_builder_default_api_version() {
+    let (addr, received) = serve(Duration::from_millis(0));
+    let mut client = ClientBuilder::new()
+        .node(&format!("http://{}", addr))
+        .bearer_auth("token")
+        .build()
+        .unwrap();
+    client.get_tips().unwrap();
+
+    let headers = received.lock().unwrap()[0].clone();
+    assert_eq!(headers["authorization"], "Bearer token");
+    assert_eq!(headers["x-iota-api-version"], "1");
+}
+
+#[test]
+fn test_builder_request_timeout() {
+    let (addr, _) = serve(Duration::from_millis(500));
+    let mut client = ClientBuilder::new()
+        .node(&format!("http://{}", addr))
+        .timeout(Duration::from_millis(50))
+        .build()
+        .unwrap();
+    assert!(client.get_tips().unwrap_err().is_transport());
+}
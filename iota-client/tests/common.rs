@@ -1,5 +1,8 @@
 #![allow(dead_code)]
 
+use iota_client::transport::InMemoryTransport;
+use serde_json::Value;
+
 pub const STARTING_MILESTONE_INDEX: u32 = 1050000;
 pub const OLDER_TIMESTAMP: u64 = 1562581100;
 pub const NULL_HASH: &str =
@@ -18,6 +21,10 @@ pub const TEST_BUNDLE_TX_1: &str =
     "IITL9EALLVZEGFIFBCCAHUOKHFBIIKQACBCEVVNZUEQLUJTOPXRICFRZKJDQGSVHARJANFDDAHMERS999";
 pub const TEST_MILESTONE_0: &str =
     "FBOCIRYP9IVIUER9URIZVPOMYZJSOJJHVTYLYTKLOPNCRJECEVELQSBHY9ESZLJTBHUNSQHNKWLUVP999";
+pub const TEST_SEED: &str =
+    "IHDEENZYITYVYSPKAURUZAQKGVJEREFDJMYTANNXXGPZ9GJWTEOJJ9IPMXOGZNQLSNMFDSQOTZAEETUEA";
+pub const TEST_SEED_ADDRESS_0: &str =
+    "LXQHWNY9CQOHPNMKFJFIJHGEPAENAOVFRDIBF99PPHDTWJDCGHLYETXT9NPUVSNKT9XDTDYNJKJCPQMZC";
 pub const TEST_TAG_0: &str = "CCLIENT99999999999999999999";
 pub const TEST_TX_HASH: &str =
     "BYSWEAUTWXHXZ9YBZISEK9LUHWGMHXCGEVNZHRLUWQFCUSDXZHOFHWHL9MQPVJXXZLIXPX\
@@ -59,7 +66,23 @@ pub const TEST_TX_HASH: &str =
      99IROUICDOXKSYZTDPEDKOQENTJOWJONDEWROCEJIEWFWLUAACVSJFTMCHHXJBJRKAAPUDXX\
      VXFWP9X9999";
 
-pub fn client_init() -> iota_client::Client<'static> {
-    iota_client::Client::new("https://nodes.devnet.iota.org")
-    //iota_client::Client::new("https://node01.iotatoken.nl")
+pub const TEST_NODE: &str = "https://nodes.devnet.iota.org";
+
+/// Client answering from an in-memory transport without any scripted response
+pub fn client_init() -> iota_client::Client {
+    iota_client::Client::new(TEST_NODE).with_transport(InMemoryTransport::new())
+}
+
+/// Client answering `command` with `response`, and the transport recording its requests
+pub fn scripted_client(command: &str, response: Value) -> (iota_client::Client, InMemoryTransport) {
+    let transport = InMemoryTransport::new();
+    transport.respond(command, response);
+    (
+        iota_client::Client::new(TEST_NODE).with_transport(transport.clone()),
+        transport,
+    )
+}
+
+pub fn async_client_init() -> iota_client::AsyncClient {
+    iota_client::AsyncClient::new(TEST_NODE).with_transport(InMemoryTransport::new())
 }
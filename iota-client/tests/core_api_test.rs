@@ -1,12 +1,15 @@
 use iota_client::options::*;
+use serde_json::json;
 
 mod common;
 use crate::common::*;
 
 #[test]
 fn test_add_neighbors_empty() {
-    let mut client = client_init();
+    let (mut client, transport) =
+        scripted_client("addNeighbors", json!({"addedNeighbors": 0, "duration": 1}));
     let res = client.add_neighbors(&vec!["".into()]).unwrap();
+    assert_eq!(transport.requests()[0].1["uris"], json!([""]));
 
     if let Some(neighbor) = res.added_neighbors() {
         assert_eq!(*neighbor, 0);
@@ -70,25 +73,49 @@ fn test_check_consistency_empty() {
 
 #[test]
 fn test_check_consistency_not_tail() {
-    let mut client = client_init();
+    let (mut client, _) = scripted_client(
+        "checkConsistency",
+        json!({"error": "Invalid transaction, not a tail: IITL9EALL", "duration": 0}),
+    );
     let res = client
         .check_consistency(&[TEST_BUNDLE_TX_1.into()])
-        .unwrap();
+        .unwrap_err();
 
-    assert!(res["error"].is_string());
+    assert!(res.is_rejected());
 }
 
 #[test]
 fn test_check_consistency_empty_tail() {
-    let mut client = client_init();
-    let res = client.check_consistency(&[NULL_HASH.into()]).unwrap();
+    let (mut client, _) = scripted_client(
+        "checkConsistency",
+        json!({"error": "Invalid transaction, missing: 999999999", "duration": 0}),
+    );
+    let res = client.check_consistency(&[NULL_HASH.into()]).unwrap_err();
+
+    assert!(res.is_rejected());
+}
+
+#[test]
+fn test_check_consistency() {
+    let (mut client, transport) = scripted_client(
+        "checkConsistency",
+        json!({"state": false, "info": "tails are not consistent", "duration": 0}),
+    );
+    let res = client
+        .check_consistency(&[TEST_BUNDLE_TX_0.into()])
+        .unwrap();
 
-    assert!(res["error"].is_string());
+    assert!(!res.state());
+    assert_eq!(res.info().as_ref().unwrap(), "tails are not consistent");
+    assert_eq!(
+        transport.requests()[0].1["tails"],
+        json!([TEST_BUNDLE_TX_0])
+    );
 }
 
 #[test]
 fn test_find_tx_empty() {
-    let mut client = client_init();
+    let (mut client, _) = scripted_client("findTransactions", json!({"duration": 2}));
     let opt = FindTransactionsOptions::default();
     let res = client.find_transactions(opt).unwrap();
     assert!(res.hashes().is_none());
@@ -96,101 +123,135 @@ fn test_find_tx_empty() {
 
 #[test]
 fn test_find_tx_by_bundle() {
-    let mut client = client_init();
+    let (mut client, transport) = scripted_client(
+        "findTransactions",
+        json!({"hashes": [TEST_BUNDLE_TX_0], "duration": 2}),
+    );
     let opt = FindTransactionsOptions {
         bundles: vec![TEST_BUNDLE_TX_0.into()],
         ..FindTransactionsOptions::default()
     };
     let res = client.find_transactions(opt).unwrap();
+    assert_eq!(
+        transport.requests()[0].1["bundles"],
+        json!([TEST_BUNDLE_TX_0])
+    );
     assert!(res.hashes().is_some());
 }
 
 #[test]
 fn test_find_tx_by_empty_bundle() {
-    let mut client = client_init();
+    let (mut client, transport) = scripted_client("findTransactions", json!({"duration": 2}));
     let opt = FindTransactionsOptions {
         bundles: vec![NULL_HASH.into()],
         ..FindTransactionsOptions::default()
     };
     let res = client.find_transactions(opt).unwrap();
+    assert_eq!(transport.requests()[0].1["bundles"], json!([NULL_HASH]));
     assert!(res.hashes().is_none());
 }
 
 #[test]
 fn test_find_tx_by_address() {
-    let mut client = client_init();
+    let (mut client, transport) = scripted_client(
+        "findTransactions",
+        json!({"hashes": [TEST_BUNDLE_TX_0], "duration": 2}),
+    );
     let opt = FindTransactionsOptions {
         addresses: vec![TEST_ADDRESS_0.into()],
         ..FindTransactionsOptions::default()
     };
     let res = client.find_transactions(opt).unwrap();
+    assert_eq!(
+        transport.requests()[0].1["addresses"],
+        json!([TEST_ADDRESS_0])
+    );
     assert!(res.hashes().is_some());
 }
 
 #[test]
 fn test_find_tx_by_empty_address() {
-    let mut client = client_init();
+    let (mut client, transport) = scripted_client("findTransactions", json!({"duration": 2}));
     let opt = FindTransactionsOptions {
         addresses: vec![NULL_HASH.into()],
         ..FindTransactionsOptions::default()
     };
     let res = client.find_transactions(opt).unwrap();
+    assert_eq!(transport.requests()[0].1["addresses"], json!([NULL_HASH]));
     assert!(res.hashes().is_none());
 }
 
 #[test]
 fn test_find_tx_by_tag() {
-    let mut client = client_init();
+    let (mut client, transport) = scripted_client(
+        "findTransactions",
+        json!({"hashes": [TEST_BUNDLE_TX_0], "duration": 2}),
+    );
     let opt = FindTransactionsOptions {
         tags: vec![TEST_TAG_0.into()],
         ..FindTransactionsOptions::default()
     };
     let res = client.find_transactions(opt).unwrap();
+    assert_eq!(transport.requests()[0].1["tags"], json!([TEST_TAG_0]));
     assert!(res.hashes().is_some());
 }
 
 #[test]
 fn test_find_tx_by_empty_tag() {
-    let mut client = client_init();
+    let (mut client, transport) = scripted_client("findTransactions", json!({"duration": 2}));
     let opt = FindTransactionsOptions {
         tags: vec![NULL_HASH.into()],
         ..FindTransactionsOptions::default()
     };
     let res = client.find_transactions(opt).unwrap();
+    assert_eq!(transport.requests()[0].1["tags"], json!([NULL_HASH]));
     assert!(res.hashes().is_none());
 }
 
 #[test]
 fn test_find_tx_by_approvee() {
-    let mut client = client_init();
+    let (mut client, transport) = scripted_client(
+        "findTransactions",
+        json!({"hashes": [TEST_BUNDLE_TX_0], "duration": 2}),
+    );
     let opt = FindTransactionsOptions {
         approvees: vec![TEST_BUNDLE_TX_1.into()],
         ..FindTransactionsOptions::default()
     };
     let res = client.find_transactions(opt).unwrap();
+    assert_eq!(
+        transport.requests()[0].1["approvees"],
+        json!([TEST_BUNDLE_TX_1])
+    );
     assert!(res.hashes().is_some());
 }
 
 #[test]
 fn test_find_tx_by_empty_approvee() {
-    let mut client = client_init();
+    let (mut client, transport) = scripted_client("findTransactions", json!({"duration": 2}));
     let opt = FindTransactionsOptions {
         approvees: vec![NULL_HASH.into()],
         ..FindTransactionsOptions::default()
     };
     let res = client.find_transactions(opt).unwrap();
+    assert_eq!(transport.requests()[0].1["approvees"], json!([NULL_HASH]));
     assert!(res.hashes().is_none());
 }
 
 #[test]
 fn test_get_balances() {
-    let mut client = client_init();
+    let (mut client, transport) = scripted_client(
+        "getBalances",
+        json!({"balances": ["0"], "milestoneIndex": 1050001, "references": [TEST_MILESTONE_0], "duration": 3}),
+    );
     let opt = GetBalancesOptions {
         addresses: vec![TEST_ADDRESS_0.into()],
         ..GetBalancesOptions::default()
     };
     let res = client.get_balances(opt).unwrap();
     assert!(res.error().is_none());
+    assert_eq!(res.milestone_index(), Some(1050001));
+    assert!(transport.requests()[0].1.get("tips").is_none());
 }
 
 #[test]
@@ -205,7 +266,10 @@ fn test_get_balances_empty() {
 
 #[test]
 fn test_get_balances_with_tip() {
-    let mut client = client_init();
+    let (mut client, transport) = scripted_client(
+        "getBalances",
+        json!({"balances": ["0"], "milestoneIndex": 1050001, "references": [TEST_MILESTONE_0], "duration": 3}),
+    );
     let opt = GetBalancesOptions {
         addresses: vec![TEST_ADDRESS_0.into()],
         tips: vec![TEST_BUNDLE_TX_0.into()],
@@ -213,29 +277,37 @@ fn test_get_balances_with_tip() {
     };
     let res = client.get_balances(opt).unwrap();
     assert!(res.error().is_none());
+    assert_eq!(transport.requests()[0].1["tips"], json!([TEST_BUNDLE_TX_0]));
 }
 
 #[test]
 fn test_get_balances_invalid_tip() {
-    let mut client = client_init();
+    let (mut client, _) = scripted_client(
+        "getBalances",
+        json!({"error": "Tip not found: MKQKKUKBR", "duration": 0}),
+    );
     let opt = GetBalancesOptions {
         addresses: vec![TEST_ADDRESS_0.into()],
         tips: vec![TEST_BUNDLE_HASH_0.into()],
         ..GetBalancesOptions::default()
     };
-    let res = client.get_balances(opt).unwrap();
-    assert!(res.error().is_some());
+    let res = client.get_balances(opt).unwrap_err();
+    assert!(res.is_rejected());
 }
 
 #[test]
 fn test_get_inclusion_states() {
-    let mut client = client_init();
+    let (mut client, _) = scripted_client(
+        "getInclusionStates",
+        json!({"states": [true], "duration": 4}),
+    );
     let opt = GetInclusionStatesOptions {
         transactions: vec![TEST_BUNDLE_TX_0.into()],
         tips: vec![TEST_MILESTONE_0.into()],
     };
     let res = client.get_inclusion_states(opt).unwrap();
     assert!(res.error().is_none());
+    assert_eq!(res.states(), &Some(vec![true]));
 }
 
 #[test]
@@ -250,7 +322,10 @@ fn test_get_inclusion_states_empty() {
 
 #[test]
 fn test_get_inclusion_states_without_tip() {
-    let mut client = client_init();
+    let (mut client, _) = scripted_client(
+        "getInclusionStates",
+        json!({"states": [true], "duration": 4}),
+    );
     let opt = GetInclusionStatesOptions {
         transactions: vec![TEST_BUNDLE_TX_0.into()],
         tips: vec![],
@@ -261,30 +336,63 @@ fn test_get_inclusion_states_without_tip() {
 
 #[test]
 fn test_get_neighbors() {
-    let mut client = client_init();
-
-    match client.get_neighbors() {
-        Ok(res) => {
-            if let Some(neighbors) = res.clone().neighbors() {
-                assert!(neighbors.iter().all(|x| !x.address.is_empty()));
-            } else if let Some(error) = res.error() {
-                assert!(!error.is_empty());
-            } else {
-                panic!("Invalid GetNeighbors Response.");
-            }
-        }
-        Err(e) => {
-            let error = format!("{}", e);
-            assert!(error.contains("COMMAND getNeighbors is not available on this node"));
-        }
-    }
+    let (mut client, _) = scripted_client(
+        "getNeighbors",
+        json!({
+            "neighbors": [{
+                "address": "tcp://172.18.0.2:15600",
+                "numberOfAllTransactions": 45,
+                "numberOfInvalidTransactions": 0,
+                "numberOfNewTransactions": 40,
+                "numberOfRandomTransactionRequests": 3,
+                "numberOfSentTransactions": 200
+            }],
+            "duration": 0
+        }),
+    );
+    let res = client.get_neighbors().unwrap();
+    let neighbors = res.neighbors().unwrap();
+    assert!(neighbors.iter().all(|x| !x.address.is_empty()));
+}
+
+#[test]
+fn test_get_neighbors_not_available() {
+    let (mut client, _) = scripted_client(
+        "getNeighbors",
+        json!({"error": "COMMAND getNeighbors is not available on this node", "duration": 0}),
+    );
+    let res = client.get_neighbors().unwrap_err();
+    assert!(res
+        .to_string()
+        .contains("COMMAND getNeighbors is not available on this node"));
 }
 
 #[test]
 fn test_get_node_info() {
-    let mut client = client_init();
+    let (mut client, _) = scripted_client(
+        "getNodeInfo",
+        json!({
+            "appName": "IRI Testnet",
+            "appVersion": "1.8.0-RC1",
+            "jreAvailableProcessors": 8,
+            "jreFreeMemory": 1_055_053_760u64,
+            "jreMaxMemory": 22_906_667_008u64,
+            "jreTotalMemory": 4_210_884_608u64,
+            "jreVersion": "1.8.0_191",
+            "latestMilestone": TEST_MILESTONE_0,
+            "latestMilestoneIndex": 1_050_001,
+            "latestSolidSubtangleMilestone": TEST_MILESTONE_0,
+            "latestSolidSubtangleMilestoneIndex": 1_050_001,
+            "milestoneStartIndex": 434_525,
+            "neighbors": 6,
+            "packetsQueueSize": 0,
+            "time": 1_562_581_200u64,
+            "tips": 3_721,
+            "transactionsToRequest": 0,
+            "duration": 0
+        }),
+    );
     let res = client.get_node_info().unwrap();
-    println!("{:#?}", res);
     assert_ne!(res.app_name().len(), 0);
     assert_ne!(res.app_version().len(), 0);
     assert_ne!(res.latest_milestone(), NULL_HASH);
@@ -294,9 +402,43 @@ fn test_get_node_info() {
     assert!(res.time() > OLDER_TIMESTAMP);
 }
 
+#[test]
+fn test_get_missing_transactions() {
+    let (mut client, _) = scripted_client(
+        "getMissingTransactions",
+        json!({"hashes": [TEST_BUNDLE_TX_0], "duration": 0}),
+    );
+    let res = client.get_missing_transactions().unwrap();
+
+    assert_eq!(res.take_hashes().unwrap(), vec![TEST_BUNDLE_TX_0]);
+}
+
+#[test]
+fn test_get_node_api_configuration() {
+    let (mut client, _) = scripted_client(
+        "getNodeAPIConfiguration",
+        json!({
+            "maxFindTransactions": 100000, "maxRequestsList": 500,
+            "maxGetTrytes": 2000, "maxBodyLength": 1000000, "testNet": false,
+            "milestoneStartIndex": STARTING_MILESTONE_INDEX, "duration": 0
+        }),
+    );
+    let res = client.get_node_api_configuration().unwrap();
+
+    assert_eq!(res.max_requests_list(), Some(500));
+    assert_eq!(res.milestone_start_index(), Some(STARTING_MILESTONE_INDEX));
+    assert_eq!(res.test_net(), Some(false));
+    let batch_sizes = res.batch_sizes();
+    assert_eq!(batch_sizes.max_find_transactions, 500);
+    assert_eq!(batch_sizes.max_get_trytes, 2000);
+}
+
 #[test]
 fn test_get_tips() {
-    let mut client = client_init();
+    let (mut client, _) = scripted_client(
+        "getTips",
+        json!({"hashes": [TEST_BUNDLE_TX_0, TEST_BUNDLE_TX_1], "duration": 17}),
+    );
     let res = client.get_tips().unwrap();
 
     assert!(!res.hashes().is_empty());
@@ -304,16 +446,27 @@ fn test_get_tips() {
 
 #[test]
 fn test_get_transactions_to_approve() {
-    let mut client = client_init();
+    let (mut client, transport) = scripted_client(
+        "getTransactionsToApprove",
+        json!({
+            "trunkTransaction": TEST_TRUNK_HASH,
+            "branchTransaction": TEST_BRANCH_HASH,
+            "duration": 936
+        }),
+    );
     let opt = GetTransactionsToApproveOptions::default();
     let res = client.get_transactions_to_approve(opt).unwrap();
     assert!(res.trunk_transaction().is_some());
     assert!(res.branch_transaction().is_some());
+    assert_eq!(transport.requests()[0].1["depth"], 3);
 }
 
 #[test]
 fn test_get_transactions_to_approve_invalid_depth() {
-    let mut client = client_init();
+    let (mut client, _) = scripted_client(
+        "getTransactionsToApprove",
+        json!({"error": "Invalid depth input", "duration": 0}),
+    );
     let opt = GetTransactionsToApproveOptions {
         depth: usize::max_value(),
         ..GetTransactionsToApproveOptions::default()
@@ -322,9 +475,38 @@ fn test_get_transactions_to_approve_invalid_depth() {
     assert!(res.to_string().contains("Invalid depth input"));
 }
 
+#[test]
+fn test_get_transactions_to_approve_with_reference() {
+    let (mut client, transport) = scripted_client(
+        "getTransactionsToApprove",
+        json!({
+            "trunkTransaction": TEST_TRUNK_HASH,
+            "branchTransaction": TEST_BRANCH_HASH,
+            "duration": 936
+        }),
+    );
+    let opt = GetTransactionsToApproveOptions {
+        reference: Some(TEST_BUNDLE_TX_0),
+        ..GetTransactionsToApproveOptions::default()
+    };
+    client.get_transactions_to_approve(opt).unwrap();
+    assert_eq!(transport.requests()[0].1["reference"], TEST_BUNDLE_TX_0);
+
+    let opt = GetTransactionsToApproveOptions {
+        reference: Some("NOT9A9HASH"),
+        ..GetTransactionsToApproveOptions::default()
+    };
+    let res = client.get_transactions_to_approve(opt).unwrap_err();
+    assert!(res.to_string().contains("Provided reference is not valid"));
+    assert_eq!(transport.requests().len(), 1);
+}
+
 #[test]
 fn test_get_trytes() {
-    let mut client = client_init();
+    let (mut client, _) = scripted_client(
+        "getTrytes",
+        json!({"trytes": [TEST_TX_HASH], "duration": 1}),
+    );
     let res = client.get_trytes(&[TEST_BUNDLE_TX_1.into()]).unwrap();
     assert!(res.error().is_none());
 }
@@ -338,7 +520,10 @@ fn test_get_trytes_empty() {
 
 #[test]
 fn test_remove_neighbors_empty() {
-    let mut client = client_init();
+    let (mut client, _) = scripted_client(
+        "removeNeighbors",
+        json!({"removedNeighbors": 0, "duration": 1}),
+    );
     let res = client.remove_neighbors(&vec!["".into()]).unwrap();
 
     if let Some(neighbor) = res.removed_neighbors() {
@@ -355,9 +540,23 @@ fn test_store_transactions_empty() {
 
 #[test]
 fn test_were_addresses_spent_from() {
-    let mut client = client_init();
+    let (mut client, transport) = scripted_client(
+        "wereAddressesSpentFrom",
+        json!({"states": [false], "duration": 1}),
+    );
     let res = client
         .were_addresses_spent_from(&[TEST_ADDRESS_0.into()])
         .unwrap();
     assert!(res.error().is_none());
+    assert_eq!(
+        transport.requests()[0].1["addresses"],
+        json!([TEST_ADDRESS_0])
+    );
+}
+
+#[test]
+fn test_unscripted_node_is_unreachable() {
+    let mut client = client_init();
+    let res = client.get_tips().unwrap_err();
+    assert!(res.is_transport());
 }
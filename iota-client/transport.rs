@@ -0,0 +1,166 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+
+use reqwest::r#async::Client;
+use serde_json::Value;
+use tokio::prelude::*;
+
+use crate::error::{parse_body, ClientError};
+use crate::BoxFuture;
+
+/// Value of the `X-IOTA-API-Version` header unless configured otherwise
+pub const DEFAULT_API_VERSION: &str = "1";
+
+/// Sends IRI API commands to a node. A command is the JSON object
+/// IRI expects (e.g. `{"command": "getNodeInfo"}`), and the future
+/// resolves to the JSON object the node answered with. IRI `error`
+/// and `exception` fields are left in the response for the client
+/// to interpret.
+pub trait Transport: Debug + Send + Sync {
+    /// Sends `command` to the node at `uri`
+    fn request(&self, uri: &str, command: Value) -> BoxFuture<Value>;
+}
+
+/// The default transport, POSTing commands over HTTP with reqwest
+#[derive(Clone, Debug)]
+pub struct HttpTransport {
+    /// A reqwest Client to make Requests with
+    pub client: Client,
+    /// Value of the `X-IOTA-API-Version` header
+    pub api_version: String,
+}
+
+impl Default for HttpTransport {
+    fn default() -> HttpTransport {
+        HttpTransport::new(Client::new())
+    }
+}
+
+impl HttpTransport {
+    /// Create a new HttpTransport using an existing reqwest Client
+    pub fn new(client: Client) -> HttpTransport {
+        HttpTransport {
+            client,
+            api_version: DEFAULT_API_VERSION.to_string(),
+        }
+    }
+
+    /// Sets the `X-IOTA-API-Version` header sent with every command
+    pub fn with_api_version(mut self, api_version: &str) -> HttpTransport {
+        self.api_version = api_version.to_string();
+        self
+    }
+}
+
+impl Transport for HttpTransport {
+    fn request(&self, uri: &str, command: Value) -> BoxFuture<Value> {
+        Box::new(
+            self.client
+                .post(uri)
+                .header("ContentType", "application/json")
+                .header("X-IOTA-API-Version", self.api_version.as_str())
+                .body(command.to_string())
+                .send()
+                .and_then(|mut resp| {
+                    let status = resp.status();
+                    resp.text().map(move |body| (status, body))
+                })
+                .map_err(ClientError::from)
+                .and_then(|(status, body)| parse_body(status, body)),
+        )
+    }
+}
+
+/// A transport answering commands from scripted responses, without
+/// any network access. Responses are queued per command and consumed
+/// in order, the last one being repeated. Every command sent is recorded.
+///
+/// Cloning an InMemoryTransport is cheap, and every clone shares the
+/// same script, so a test can keep a handle after giving one to a client.
+///
+/// ```
+/// use iota_client::transport::InMemoryTransport;
+/// use serde_json::json;
+///
+/// let transport = InMemoryTransport::new();
+/// transport.respond("getTips", json!({"hashes": [], "duration": 0}));
+/// let mut client = iota_client::Client::new("http://localhost:14265").with_transport(transport.clone());
+///
+/// assert!(client.get_tips().unwrap().hashes().is_empty());
+/// assert_eq!(transport.requests()[0].1["command"], "getTips");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryTransport {
+    state: Arc<Mutex<Script>>,
+}
+
+#[derive(Debug, Default)]
+struct Script {
+    responses: HashMap<String, VecDeque<Value>>,
+    unreachable: HashSet<String>,
+    requests: Vec<(String, Value)>,
+}
+
+impl InMemoryTransport {
+    /// Create a new transport without any scripted response
+    pub fn new() -> InMemoryTransport {
+        InMemoryTransport::default()
+    }
+
+    /// Queues `response` as the answer to the next `command`
+    pub fn respond(&self, command: &str, response: Value) {
+        self.lock()
+            .responses
+            .entry(command.to_string())
+            .or_default()
+            .push_back(response);
+    }
+
+    /// Makes every command sent to `uri` fail with a transport error
+    pub fn set_unreachable(&self, uri: &str) {
+        self.lock().unreachable.insert(uri.to_string());
+    }
+
+    /// Makes `uri` reachable again
+    pub fn set_reachable(&self, uri: &str) {
+        self.lock().unreachable.remove(uri);
+    }
+
+    /// Returns every URI and command sent so far, in order
+    pub fn requests(&self) -> Vec<(String, Value)> {
+        self.lock().requests.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Script> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Transport for InMemoryTransport {
+    fn request(&self, uri: &str, command: Value) -> BoxFuture<Value> {
+        let mut script = self.lock();
+        script.requests.push((uri.to_string(), command.clone()));
+        if script.unreachable.contains(uri) {
+            return Box::new(future::err(ClientError::Transport(format_err!(
+                "{} is unreachable",
+                uri
+            ))));
+        }
+        let name = command["command"].as_str().unwrap_or_default();
+        let response = script.responses.get_mut(name).and_then(|queue| {
+            if queue.len() > 1 {
+                queue.pop_front()
+            } else {
+                queue.front().cloned()
+            }
+        });
+        match response {
+            Some(response) => Box::new(future::ok(response)),
+            None => Box::new(future::err(ClientError::Transport(format_err!(
+                "No response scripted for {}",
+                name
+            )))),
+        }
+    }
+}
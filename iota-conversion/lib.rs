@@ -37,3 +37,11 @@ pub fn long_value(trits: &[i8]) -> i64 {
         .rev()
         .fold(0, |acc, trit| acc * 3 + i64::from(*trit))
 }
+
+/// Validates that a string is an address of tryte characters, with or
+/// without checksum
+pub fn is_address(address: &str) -> bool {
+    (address.len() == iota_constants::ADDRESS_LENGTH_WITHOUT_CHECKSUM
+        || address.len() == iota_constants::ADDRESS_LENGTH_WITH_CHECKSUM)
+        && address.chars().all(|c| c == '9' || c.is_ascii_uppercase())
+}
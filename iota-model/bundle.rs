@@ -1,3 +1,4 @@
+use std::convert::TryInto;
 use std::fmt;
 use std::ops::{Deref, DerefMut};
 
@@ -9,11 +10,16 @@ use iota_constants::HASH_TRINARY_SIZE as HASH_LENGTH;
 use iota_conversion::Trinary;
 use iota_crypto::{Kerl, Sponge};
 
+use super::input::Input;
 use super::transaction::Transaction;
 
 const EMPTY_HASH: &str =
     "999999999999999999999999999999999999999999999999999999999999999999999999999999999";
 
+/// Version of the JSON format written by `Bundle::to_unsigned_json` and
+/// `Bundle::to_signed_json`
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
 /// Represents a bundle of transactions
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct Bundle(Vec<Transaction>);
@@ -33,6 +39,24 @@ pub struct BundleEntry<'a, 'b> {
     pub timestamp: i64,
 }
 
+/// Whether a serialised bundle still has to be signed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum BundleState {
+    Unsigned,
+    Signed,
+}
+
+/// The JSON document exchanged between an online and an offline host
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct BundleFile {
+    version: u32,
+    state: BundleState,
+    transactions: Vec<Transaction>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    inputs: Vec<Input>,
+}
+
 impl Deref for Bundle {
     type Target = Vec<Transaction>;
 
@@ -86,10 +110,9 @@ impl Bundle {
         let empty_timestamp = 999_999_999;
 
         for (i, bundle) in self.0.iter_mut().enumerate() {
-            let new_sig = if signature_fragments.is_empty() || signature_fragments[i].is_empty() {
-                &empty_signature_fragment
-            } else {
-                &signature_fragments[i]
+            let new_sig = match signature_fragments.get(i) {
+                Some(fragment) if !fragment.is_empty() => fragment,
+                _ => &empty_signature_fragment,
             };
             bundle.signature_fragments = new_sig.clone();
             bundle.trunk_transaction = empty_hash.into();
@@ -115,35 +138,162 @@ impl Bundle {
 
     /// Finalizes the bundle
     pub fn finalize(&mut self) -> Result<()> {
-        let mut valid_bundle = false;
-        let mut kerl = Kerl::default();
-        while !valid_bundle {
-            kerl.reset();
-            for bundle in &mut self.0 {
-                let value_trits = bundle.value.trits_with_length(81);
-                let timestamp_trits = bundle.timestamp.trits_with_length(27);
-                let current_index_trits = (bundle.current_index as i64).trits_with_length(27);
-                let last_index_trits = (bundle.last_index as i64).trits_with_length(27);
-                let bundle_essence = bundle.address.clone()
-                    + &value_trits.trytes()?
-                    + &bundle.obsolete_tag
-                    + &timestamp_trits.trytes()?
-                    + &current_index_trits.trytes()?
-                    + &last_index_trits.trytes()?;
-                kerl.absorb(&bundle_essence.trits())?;
-            }
-            let mut hash = [0; HASH_LENGTH];
-            kerl.squeeze(&mut hash)?;
-            let hash_trytes = hash.trytes()?;
+        loop {
+            let hash_trytes = self.essence_hash()?;
             for bundle in &mut self.0 {
                 bundle.bundle = hash_trytes.clone();
             }
             let normalized_hash = Bundle::normalized_bundle(&hash_trytes);
-            if normalized_hash.contains(&13) {
-                let increased_tag = crate::trit_adder::add(&self.0[0].obsolete_tag.trits(), &[1]);
-                self.0[0].obsolete_tag = increased_tag.trytes()?;
-            } else {
-                valid_bundle = true;
+            if !normalized_hash.contains(&13) {
+                return Ok(());
+            }
+            let increased_tag = crate::trit_adder::add(&self.0[0].obsolete_tag.trits(), &[1]);
+            self.0[0].obsolete_tag = increased_tag.trytes()?;
+        }
+    }
+
+    /// Computes the bundle hash from the essence of every transaction
+    pub fn essence_hash(&self) -> Result<String> {
+        let mut kerl = Kerl::default();
+        for bundle in &self.0 {
+            let value_trits = bundle.value.trits_with_length(81);
+            let timestamp_trits = bundle.timestamp.trits_with_length(27);
+            let current_index_trits = (bundle.current_index as i64).trits_with_length(27);
+            let last_index_trits = (bundle.last_index as i64).trits_with_length(27);
+            let bundle_essence = bundle.address.clone()
+                + &value_trits.trytes()?
+                + &bundle.obsolete_tag
+                + &timestamp_trits.trytes()?
+                + &current_index_trits.trytes()?
+                + &last_index_trits.trytes()?;
+            kerl.absorb(&bundle_essence.trits())?;
+        }
+        let mut hash = [0; HASH_LENGTH];
+        kerl.squeeze(&mut hash)?;
+        hash.trytes()
+    }
+
+    /// Returns the transactions depositing value, so that the recipients
+    /// can be checked before signing
+    pub fn outputs(&self) -> Vec<&Transaction> {
+        self.0.iter().filter(|tx| tx.value > 0).collect()
+    }
+
+    /// Writes the signature of an input into the signature fragments of
+    /// the transactions of its address, one fragment per security level
+    ///
+    /// * `address` - The input address, without checksum
+    /// * `fragments` - The signature fragments, in order
+    pub fn add_signature(&mut self, address: &str, fragments: &[String]) -> Result<()> {
+        let start = match self
+            .0
+            .iter()
+            .position(|tx| tx.address == address && tx.value < 0)
+        {
+            Some(start) => start,
+            None => return Err(format_err!("No input for address {} in bundle", address)),
+        };
+        for (i, fragment) in fragments.iter().enumerate() {
+            match self.0.get_mut(start + i) {
+                Some(tx) if tx.address == address && (i == 0 || tx.value == 0) => {
+                    tx.signature_fragments = fragment.clone();
+                }
+                _ => {
+                    return Err(format_err!(
+                        "Bundle has fewer transactions than signature fragments for {}",
+                        address
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the trytes of every transaction, last transaction first,
+    /// as expected by `send_trytes`
+    pub fn trytes(&self) -> Result<Vec<String>> {
+        self.0.iter().rev().map(|tx| tx.try_into()).collect()
+    }
+
+    /// Serialises a finalized bundle which still has to be signed, together
+    /// with the key index and security level of each of its inputs
+    ///
+    /// * `inputs` - The inputs spent by the bundle, addresses without checksum
+    pub fn to_unsigned_json(&self, inputs: &[Input]) -> Result<String> {
+        self.check_hash()?;
+        self.check_inputs(inputs)?;
+        let file = BundleFile {
+            version: BUNDLE_FORMAT_VERSION,
+            state: BundleState::Unsigned,
+            transactions: self.0.clone(),
+            inputs: inputs.to_vec(),
+        };
+        Ok(serde_json::to_string_pretty(&file)?)
+    }
+
+    /// Reads a bundle written by `to_unsigned_json`, returning it with the
+    /// inputs to sign. Fails if the bundle hash doesn't match the
+    /// transactions, so the outputs seen by the signer are the ones signed,
+    /// or if it normalizes to 13.
+    pub fn from_unsigned_json(json: &str) -> Result<(Bundle, Vec<Input>)> {
+        let file = BundleFile::parse(json, BundleState::Unsigned)?;
+        let bundle = Bundle(file.transactions);
+        bundle.check_hash()?;
+        bundle.check_inputs(&file.inputs)?;
+        Ok((bundle, file.inputs))
+    }
+
+    /// Serialises a signed bundle, ready to be sent by an online host
+    pub fn to_signed_json(&self) -> Result<String> {
+        self.check_hash()?;
+        let unsigned = self
+            .0
+            .iter()
+            .find(|tx| tx.value < 0 && tx.signature_fragments.chars().all(|c| c == '9'));
+        if let Some(tx) = unsigned {
+            return Err(format_err!("Input {} is not signed", tx.address));
+        }
+        let file = BundleFile {
+            version: BUNDLE_FORMAT_VERSION,
+            state: BundleState::Signed,
+            transactions: self.0.clone(),
+            inputs: Vec::new(),
+        };
+        Ok(serde_json::to_string_pretty(&file)?)
+    }
+
+    /// Reads a bundle written by `to_signed_json`
+    pub fn from_signed_json(json: &str) -> Result<Bundle> {
+        let file = BundleFile::parse(json, BundleState::Signed)?;
+        let bundle = Bundle(file.transactions);
+        bundle.check_hash()?;
+        Ok(bundle)
+    }
+
+    fn check_hash(&self) -> Result<()> {
+        let hash = self.essence_hash()?;
+        if self.0.is_empty() || self.0.iter().any(|tx| tx.bundle != hash) {
+            return Err(format_err!("Bundle hash doesn't match its transactions"));
+        }
+        if Bundle::normalized_bundle(&hash).contains(&13) {
+            return Err(format_err!(
+                "Bundle hash normalizes to 13 and is insecure to sign"
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_inputs(&self, inputs: &[Input]) -> Result<()> {
+        for tx in self.0.iter().filter(|tx| tx.value < 0) {
+            let input = inputs.iter().find(|input| input.address == tx.address);
+            match input {
+                Some(input) if input.balance == -tx.value => {}
+                _ => {
+                    return Err(format_err!(
+                        "Missing or mismatched input for {}",
+                        tx.address
+                    ))
+                }
             }
         }
         Ok(())
@@ -185,3 +335,23 @@ impl Bundle {
         normalized_bundle
     }
 }
+
+impl BundleFile {
+    fn parse(json: &str, state: BundleState) -> Result<BundleFile> {
+        let file: BundleFile = serde_json::from_str(json)?;
+        if file.version != BUNDLE_FORMAT_VERSION {
+            return Err(format_err!(
+                "Unsupported bundle format version {}",
+                file.version
+            ));
+        }
+        if file.state != state {
+            let expected = match state {
+                BundleState::Unsigned => "an unsigned",
+                BundleState::Signed => "a signed",
+            };
+            return Err(format_err!("Expected {} bundle", expected));
+        }
+        Ok(file)
+    }
+}
@@ -1,15 +1,13 @@
 use iota_constants;
 use iota_constants::HASH_TRINARY_SIZE;
-use iota_conversion::Trinary;
+use iota_conversion::{is_address, Trinary};
 use iota_crypto::{Kerl, Sponge};
 
 use crate::Result;
 
-use super::input_validator;
-
 /// Adds a checksum to provided address
 pub fn add_checksum(address: &str) -> Result<String> {
-    assert!(input_validator::is_address(address));
+    assert!(is_address(address));
     let mut address_with_checksum = address.to_string();
     address_with_checksum += &calculate_checksum(address)?;
     Ok(address_with_checksum)
@@ -39,14 +37,12 @@ fn remove_checksum_from_address(address: &str) -> String {
 
 /// Checks if an address has a checksum
 pub fn is_address_with_checksum(address: &str) -> bool {
-    input_validator::is_address(address)
-        && address.len() == iota_constants::ADDRESS_LENGTH_WITH_CHECKSUM
+    is_address(address) && address.len() == iota_constants::ADDRESS_LENGTH_WITH_CHECKSUM
 }
 
 /// Checks if an address does not have a checksum
 pub fn is_address_without_checksum(address: &str) -> bool {
-    input_validator::is_address(address)
-        && address.len() == iota_constants::ADDRESS_LENGTH_WITHOUT_CHECKSUM
+    is_address(address) && address.len() == iota_constants::ADDRESS_LENGTH_WITHOUT_CHECKSUM
 }
 
 fn calculate_checksum(address: &str) -> Result<String> {
@@ -12,8 +12,7 @@ use iota_constants;
 use iota_constants::HASH_TRINARY_SIZE;
 use iota_conversion::Trinary;
 use iota_crypto::{Kerl, Sponge};
-use iota_model::Bundle;
-use iota_validation::input_validator;
+use iota_model::{Bundle, Input};
 
 /// Checksum functions and utilities
 pub mod checksum;
@@ -82,6 +81,72 @@ pub fn signature_fragment(
     Ok(signature_fragment)
 }
 
+/// Signs a bundle hash with the key at `index` of `seed`, returning
+/// one tryte-encoded signature fragment per security level
+pub fn bundle_signature(
+    seed: &[i8],
+    index: usize,
+    security: usize,
+    bundle_hash: &str,
+) -> Result<Vec<String>> {
+    key_signature(&key(seed, index, security)?, security, bundle_hash)
+}
+
+/// Signs a bundle hash with `key`, one fragment per security level
+fn key_signature(key: &[i8], security: usize, bundle_hash: &str) -> Result<Vec<String>> {
+    let normalized_bundle_hash = Bundle::normalized_bundle(bundle_hash);
+    let mut fragments = Vec::with_capacity(security);
+    for i in 0..security {
+        let normalized_bundle_fragment = &normalized_bundle_hash[(i % 3) * 27..(i % 3 + 1) * 27];
+        let key_fragment = &key[i * KEY_LENGTH..(i + 1) * KEY_LENGTH];
+        fragments.push(signature_fragment(normalized_bundle_fragment, key_fragment)?.trytes()?);
+    }
+    Ok(fragments)
+}
+
+/// Signs every input of a finalized bundle with the keys of `seed`
+///
+/// The bundle and inputs may come from an untrusted file, so nothing is
+/// signed unless the bundle hash matches the transactions, the normalized
+/// hash has no 13 (signing it would reveal key fragments), and the key
+/// index and security level of every input derive its address.
+///
+/// * `seed` - The trits of the wallet seed
+/// * `inputs` - The inputs spent by the bundle, addresses without checksum
+/// * `bundle` - The bundle to sign
+pub fn sign_bundle(seed: &[i8], inputs: &[Input], bundle: &mut Bundle) -> Result<()> {
+    let bundle_hash = match bundle.first() {
+        Some(tx) => tx.bundle.clone(),
+        None => return Ok(()),
+    };
+    if bundle.essence_hash()? != bundle_hash || bundle.iter().any(|tx| tx.bundle != bundle_hash) {
+        failure::bail!("Bundle hash doesn't match its transactions");
+    }
+    if Bundle::normalized_bundle(&bundle_hash).contains(&13) {
+        failure::bail!("Bundle hash normalizes to 13 and is insecure to sign");
+    }
+    let mut signatures = Vec::with_capacity(inputs.len());
+    for input in inputs {
+        if input.security < 1 || input.security > 3 {
+            failure::bail!(iota_constants::INVALID_SECURITY_LEVEL_INPUT_ERROR);
+        }
+        let key = key(seed, input.key_index, input.security)?;
+        if address(&digests(&key)?)?.trytes()? != input.address {
+            failure::bail!(
+                "Input {} is not the address of key index {} at security {}",
+                input.address,
+                input.key_index,
+                input.security
+            );
+        }
+        signatures.push(key_signature(&key, input.security, &bundle_hash)?);
+    }
+    for (input, fragments) in inputs.iter().zip(signatures) {
+        bundle.add_signature(&input.address, &fragments)?;
+    }
+    Ok(())
+}
+
 /// Signs an address
 pub fn address(digests: &[i8]) -> Result<[i8; HASH_TRINARY_SIZE]> {
     let mut address = [0; HASH_TRINARY_SIZE];
@@ -146,7 +211,7 @@ pub fn validate_bundle_signatures(signed_bundle: &Bundle, address: &str) -> Resu
         if transaction.address == address {
             bundle_hash = &transaction.bundle;
             let signature_fragment = &transaction.signature_fragments;
-            if input_validator::is_nine_trytes(&signature_fragment) {
+            if signature_fragment.chars().all(|c| c == '9') {
                 break;
             }
             signature_fragments.push(signature_fragment.clone());
@@ -0,0 +1,236 @@
+use std::convert::TryInto;
+
+use chrono::prelude::*;
+use iota_model::Transaction;
+use serde_json::Value;
+
+use crate::tangle::Tangle;
+use crate::Result;
+
+/// Maximum depth accepted by `getTransactionsToApprove`, as in IRI
+const MAX_DEPTH: u64 = 15;
+
+/// Answers an IRI command the way IRI would, with failures
+/// reported in the `error` field
+pub(crate) fn handle(tangle: &mut Tangle, command: &Value) -> Value {
+    let name = command["command"].as_str().unwrap_or_default();
+    let result = match name {
+        "attachToTangle" => attach_to_tangle(command),
+        "broadcastTransactions" | "storeTransactions" => store_transactions(tangle, command),
+        "checkConsistency" => check_consistency(tangle, command),
+        "findTransactions" => Ok(find_transactions(tangle, command)),
+        "getBalances" => get_balances(tangle, command),
+        "getInclusionStates" => Ok(get_inclusion_states(tangle, command)),
+        "getMissingTransactions" => Ok(json!({ "hashes": [] })),
+        "getNeighbors" => Ok(json!({ "neighbors": [] })),
+        "getNodeAPIConfiguration" => Ok(get_node_api_configuration()),
+        "getNodeInfo" => Ok(get_node_info(tangle)),
+        "getTips" => Ok(json!({ "hashes": tangle.tips() })),
+        "getTransactionsToApprove" => get_transactions_to_approve(tangle, command),
+        "getTrytes" => Ok(get_trytes(tangle, command)),
+        "interruptAttachingToTangle" => Ok(json!({})),
+        "wereAddressesSpentFrom" => Ok(were_addresses_spent_from(tangle, command)),
+        _ => Err(format_err!("Command [{}] is unknown", name)),
+    };
+    let mut response = result.unwrap_or_else(|e| json!({ "error": e.to_string() }));
+    response["duration"] = json!(0);
+    response
+}
+
+fn strings(command: &Value, field: &str) -> Vec<String> {
+    command[field]
+        .as_array()
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(Value::as_str)
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Addresses may be given with their checksum, the tangle indexes them without
+fn addresses(command: &Value) -> Vec<String> {
+    strings(command, "addresses")
+        .into_iter()
+        .map(|address| address.chars().take(81).collect())
+        .collect()
+}
+
+fn attach_to_tangle(command: &Value) -> Result<Value> {
+    let trunk = command["trunkTransaction"].as_str().unwrap_or_default();
+    let branch = command["branchTransaction"].as_str().unwrap_or_default();
+    let mut attached: Vec<String> = Vec::new();
+    let mut previous: Option<String> = None;
+    for trytes in strings(command, "trytes") {
+        let mut tx: Transaction = trytes
+            .parse()
+            .map_err(|_| format_err!("Invalid trytes input"))?;
+        match previous {
+            Some(previous) => {
+                tx.trunk_transaction = previous;
+                tx.branch_transaction = trunk.to_string();
+            }
+            None => {
+                tx.trunk_transaction = trunk.to_string();
+                tx.branch_transaction = branch.to_string();
+            }
+        }
+        if tx.tag.is_empty() || tx.tag == "9".repeat(27) {
+            tx.tag = tx.obsolete_tag.clone();
+        }
+        tx.attachment_timestamp = Utc::now().timestamp_millis();
+        tx.attachment_timestamp_lower_bound = 0;
+        tx.attachment_timestamp_upper_bound = (3_i64.pow(27) - 1) / 2;
+        let tx_trytes: String = tx.try_into()?;
+        let tx: Transaction = tx_trytes.parse()?;
+        previous = Some(tx.hash);
+        attached.push(tx_trytes);
+    }
+    attached.reverse();
+    Ok(json!({ "trytes": attached }))
+}
+
+fn store_transactions(tangle: &mut Tangle, command: &Value) -> Result<Value> {
+    tangle.store(&strings(command, "trytes"))?;
+    Ok(json!({}))
+}
+
+fn check_consistency(tangle: &Tangle, command: &Value) -> Result<Value> {
+    let tails = strings(command, "tails");
+    for tail in &tails {
+        match tangle.transaction(tail) {
+            Some(tx) if tx.current_index == 0 => {}
+            Some(_) => bail!("Invalid transaction, not a tail: {}", tail),
+            None => bail!("Invalid transaction, missing: {}", tail),
+        }
+    }
+    for tail in &tails {
+        if let Err(info) = tangle.check_consistency(tail) {
+            return Ok(json!({ "state": false, "info": info }));
+        }
+    }
+    Ok(json!({ "state": true }))
+}
+
+fn find_transactions(tangle: &Tangle, command: &Value) -> Value {
+    let bundles = strings(command, "bundles");
+    let addresses = addresses(command);
+    let tags: Vec<String> = strings(command, "tags")
+        .into_iter()
+        .map(|tag| format!("{:9<27}", tag))
+        .collect();
+    let approvees = strings(command, "approvees");
+    let approvers: Vec<&String> = approvees
+        .iter()
+        .flat_map(|approvee| tangle.approvers(approvee))
+        .collect();
+
+    let hashes = tangle.find(|tx| {
+        (bundles.is_empty() || bundles.contains(&tx.bundle))
+            && (addresses.is_empty() || addresses.contains(&tx.address))
+            && (tags.is_empty() || tags.contains(&tx.tag))
+            && (approvees.is_empty() || approvers.contains(&&tx.hash))
+    });
+    json!({ "hashes": hashes })
+}
+
+fn get_balances(tangle: &Tangle, command: &Value) -> Result<Value> {
+    for tip in strings(command, "tips") {
+        ensure!(tangle.transaction(&tip).is_some(), "Tip not found: {}", tip);
+    }
+    let balances: Vec<String> = addresses(command)
+        .iter()
+        .map(|address| tangle.balance(address).to_string())
+        .collect();
+    Ok(json!({
+        "balances": balances,
+        "milestoneIndex": tangle.latest_milestone_index(),
+        "references": [tangle.latest_milestone()],
+    }))
+}
+
+fn get_inclusion_states(tangle: &Tangle, command: &Value) -> Value {
+    let states: Vec<bool> = strings(command, "transactions")
+        .iter()
+        .map(|hash| tangle.is_confirmed(hash))
+        .collect();
+    json!({ "states": states })
+}
+
+fn get_node_api_configuration() -> Value {
+    json!({
+        "maxFindTransactions": 100_000,
+        "maxRequestsList": 1_000,
+        "maxGetTrytes": 10_000,
+        "maxBodyLength": 1_000_000,
+        "testNet": true,
+        "milestoneStartIndex": crate::tangle::START_MILESTONE_INDEX,
+    })
+}
+
+fn get_node_info(tangle: &Tangle) -> Value {
+    json!({
+        "appName": "IRI Simulator",
+        "appVersion": env!("CARGO_PKG_VERSION"),
+        "jreAvailableProcessors": 1,
+        "jreFreeMemory": 0,
+        "jreMaxMemory": 0,
+        "jreTotalMemory": 0,
+        "jreVersion": "none",
+        "latestMilestone": tangle.latest_milestone(),
+        "latestMilestoneIndex": tangle.latest_milestone_index(),
+        "latestSolidSubtangleMilestone": tangle.latest_milestone(),
+        "latestSolidSubtangleMilestoneIndex": tangle.latest_milestone_index(),
+        "milestoneStartIndex": crate::tangle::START_MILESTONE_INDEX,
+        "neighbors": 0,
+        "packetsQueueSize": 0,
+        "time": Utc::now().timestamp_millis(),
+        "tips": tangle.tips().len(),
+        "transactionsToRequest": 0,
+    })
+}
+
+fn get_transactions_to_approve(tangle: &Tangle, command: &Value) -> Result<Value> {
+    let depth = command["depth"].as_u64().unwrap_or(0);
+    ensure!(depth <= MAX_DEPTH, "Invalid depth input");
+    let tips = tangle.tips();
+    ensure!(!tips.is_empty(), "The tangle has no tips to approve");
+    let trunk = match command["reference"].as_str() {
+        Some(reference) => {
+            ensure!(
+                tangle.transaction(reference).is_some(),
+                "Reference transaction not found: {}",
+                reference
+            );
+            reference.to_string()
+        }
+        None => tips[0].clone(),
+    };
+    let branch = tips.get(1).unwrap_or(&tips[0]).clone();
+    Ok(json!({
+        "trunkTransaction": trunk,
+        "branchTransaction": branch,
+    }))
+}
+
+fn get_trytes(tangle: &Tangle, command: &Value) -> Value {
+    let trytes: Vec<String> = strings(command, "hashes")
+        .iter()
+        .map(|hash| {
+            tangle
+                .trytes(hash)
+                .map_or_else(|| "9".repeat(2673), String::from)
+        })
+        .collect();
+    json!({ "trytes": trytes })
+}
+
+fn were_addresses_spent_from(tangle: &Tangle, command: &Value) -> Value {
+    let states: Vec<bool> = addresses(command)
+        .iter()
+        .map(|address| tangle.is_spent(address))
+        .collect();
+    json!({ "states": states })
+}
@@ -0,0 +1,40 @@
+#![deny(unused_extern_crates)]
+#![warn(
+    missing_debug_implementations,
+    missing_docs,
+    rust_2018_idioms,
+    unreachable_pub
+)]
+
+//! An in-memory tangle implementing the IRI API, to test IOTA
+//! applications without a network. A `Simulator` can be given to a
+//! client as its transport, or served over HTTP for clients in other
+//! processes. Milestones are issued on demand, confirming the valid
+//! bundles they reference.
+//!
+//! ```
+//! use iota_client::Client;
+//! use iota_simulator::Simulator;
+//!
+//! let simulator = Simulator::new();
+//! let mut client = Client::new("http://simulator").with_transport(simulator.clone());
+//!
+//! let info = client.get_node_info().unwrap();
+//! assert_eq!(info.latest_milestone_index(), simulator.latest_milestone_index());
+//! ```
+
+#[macro_use]
+extern crate failure;
+#[macro_use]
+extern crate serde_json;
+
+mod api;
+/// Serves a simulator over HTTP
+pub mod server;
+mod simulator;
+mod tangle;
+
+pub use simulator::Simulator;
+pub use tangle::START_MILESTONE_INDEX;
+
+type Result<T> = ::std::result::Result<T, failure::Error>;
@@ -0,0 +1,60 @@
+use std::net::SocketAddr;
+use std::thread;
+
+use hyper::service::service_fn;
+use hyper::{Body, Request, Response, Server, StatusCode};
+use serde_json::Value;
+use tokio::prelude::*;
+
+use crate::{Result, Simulator};
+
+/// Returns a future serving the IRI API of `simulator` on `addr`, to be
+/// run on a tokio runtime, along with the address it listens on. Every
+/// command is answered with status 200, or 400 if it failed, as IRI does.
+/// The future fails if the server stops on an error.
+pub fn serve(
+    simulator: Simulator,
+    addr: &SocketAddr,
+) -> Result<(SocketAddr, impl Future<Item = (), Error = failure::Error>)> {
+    let server = Server::try_bind(addr)?.serve(move || {
+        let simulator = simulator.clone();
+        service_fn(move |req: Request<Body>| {
+            let simulator = simulator.clone();
+            req.into_body()
+                .concat2()
+                .map(move |body| answer(&simulator, &body))
+        })
+    });
+    let local_addr = server.local_addr();
+    let serving = server.map_err(failure::Error::from);
+    Ok((local_addr, serving))
+}
+
+fn answer(simulator: &Simulator, body: &[u8]) -> Response<Body> {
+    let response = match serde_json::from_slice::<Value>(body) {
+        Ok(command) => simulator.handle(&command),
+        Err(e) => json!({ "error": format!("Invalid JSON input: {}", e) }),
+    };
+    let status = if response.get("error").is_some() {
+        StatusCode::BAD_REQUEST
+    } else {
+        StatusCode::OK
+    };
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json")
+        .body(Body::from(response.to_string()))
+        .expect("a JSON response is always valid")
+}
+
+impl Simulator {
+    /// Serves this simulator over HTTP on a background thread, and returns
+    /// the address it listens on. Binding port 0 picks a free port. The
+    /// server stops on the first error it can't recover from; run the
+    /// future of `serve` instead to handle it.
+    pub fn spawn_server(&self, addr: &SocketAddr) -> Result<SocketAddr> {
+        let (local_addr, serving) = serve(self.clone(), addr)?;
+        thread::spawn(move || tokio::run(serving.map_err(drop)));
+        Ok(local_addr)
+    }
+}
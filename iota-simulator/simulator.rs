@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use iota_client::{BoxFuture, Transport};
+use serde_json::Value;
+use tokio::prelude::*;
+
+use crate::api;
+use crate::tangle::Tangle;
+use crate::Result;
+
+/// An in-memory tangle answering IRI API commands. Transactions are
+/// stored as they are broadcast, and confirmed when a milestone
+/// referencing them is issued with `issue_milestone`. Bundles are only
+/// confirmed if they are valid and the confirmed ledger covers them,
+/// otherwise they are rejected and never confirmed.
+///
+/// Cloning a Simulator is cheap, and every clone shares the same tangle.
+#[derive(Clone, Debug)]
+pub struct Simulator {
+    tangle: Arc<Mutex<Tangle>>,
+}
+
+impl Default for Simulator {
+    fn default() -> Simulator {
+        Simulator::with_snapshot(&[])
+    }
+}
+
+impl Simulator {
+    /// Create a new simulator with an empty ledger
+    pub fn new() -> Simulator {
+        Simulator::default()
+    }
+
+    /// Create a new simulator whose ledger starts with the given
+    /// balances, keyed by address without checksum
+    pub fn with_snapshot(balances: &[(&str, i64)]) -> Simulator {
+        let snapshot: HashMap<String, i64> = balances
+            .iter()
+            .map(|(address, balance)| (address.chars().take(81).collect(), *balance))
+            .collect();
+        Simulator {
+            tangle: Arc::new(Mutex::new(Tangle::new(snapshot))),
+        }
+    }
+
+    /// Index of the latest milestone
+    pub fn latest_milestone_index(&self) -> u32 {
+        self.lock().latest_milestone_index()
+    }
+
+    /// Issues a milestone approving the two most recent tips, confirming
+    /// every consistent bundle they reference. Returns its index.
+    pub fn issue_milestone(&self) -> u32 {
+        self.lock()
+            .issue_milestone(&[])
+            .expect("tips are always stored transactions")
+    }
+
+    /// Issues a milestone approving the given transactions, confirming
+    /// every consistent bundle they reference. Returns its index.
+    pub fn issue_milestone_referencing(&self, references: &[String]) -> Result<u32> {
+        self.lock().issue_milestone(references)
+    }
+
+    /// Answers an IRI API command, as a node would over HTTP
+    pub fn handle(&self, command: &Value) -> Value {
+        api::handle(&mut self.lock(), command)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Tangle> {
+        self.tangle.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Transport for Simulator {
+    fn request(&self, _uri: &str, command: Value) -> BoxFuture<Value> {
+        Box::new(future::ok(self.handle(&command)))
+    }
+}
@@ -0,0 +1,340 @@
+use std::collections::{HashMap, HashSet};
+use std::convert::TryInto;
+
+use chrono::prelude::*;
+use iota_model::Transaction;
+
+use crate::Result;
+
+/// Index of the milestone issued when a simulator is created
+pub const START_MILESTONE_INDEX: u32 = 1;
+
+const NULL_HASH: &str =
+    "999999999999999999999999999999999999999999999999999999999999999999999999999999999";
+
+#[derive(Clone, Debug)]
+struct Stored {
+    trytes: String,
+    tx: Transaction,
+    seq: usize,
+}
+
+/// Transactions, approvals and the confirmed ledger of a simulated tangle
+#[derive(Debug)]
+pub(crate) struct Tangle {
+    transactions: HashMap<String, Stored>,
+    approvers: HashMap<String, Vec<String>>,
+    balances: HashMap<String, i64>,
+    confirmed: HashSet<String>,
+    confirmed_bundles: HashSet<String>,
+    inconsistent: HashSet<String>,
+    milestones: Vec<String>,
+}
+
+impl Tangle {
+    /// Creates a tangle whose ledger starts with the given balances,
+    /// and issues the first milestone
+    pub(crate) fn new(snapshot: HashMap<String, i64>) -> Tangle {
+        let mut tangle = Tangle {
+            transactions: HashMap::new(),
+            approvers: HashMap::new(),
+            balances: snapshot,
+            confirmed: HashSet::new(),
+            confirmed_bundles: HashSet::new(),
+            inconsistent: HashSet::new(),
+            milestones: Vec::new(),
+        };
+        tangle
+            .issue_milestone(&[])
+            .expect("the genesis milestone is a valid transaction");
+        tangle
+    }
+
+    /// Index of the latest milestone
+    pub(crate) fn latest_milestone_index(&self) -> u32 {
+        START_MILESTONE_INDEX + self.milestones.len() as u32 - 1
+    }
+
+    /// Hash of the latest milestone
+    pub(crate) fn latest_milestone(&self) -> &str {
+        self.milestones.last().map_or(NULL_HASH, String::as_str)
+    }
+
+    /// Parses and stores transaction trytes. Every bundle that is
+    /// complete within `trytes` must pass `iota_validation::is_bundle`,
+    /// otherwise nothing is stored.
+    pub(crate) fn store(&mut self, trytes: &[String]) -> Result<()> {
+        let mut parsed = Vec::with_capacity(trytes.len());
+        for tx_trytes in trytes {
+            let tx: Transaction = tx_trytes
+                .parse()
+                .map_err(|_| format_err!("Invalid trytes input"))?;
+            parsed.push((tx_trytes.clone(), tx));
+        }
+
+        let mut bundles: HashMap<&str, Vec<&Transaction>> = HashMap::new();
+        for (_, tx) in &parsed {
+            bundles.entry(&tx.bundle).or_default().push(tx);
+        }
+        for (hash, txs) in bundles {
+            let last_index = txs[0].last_index;
+            let mut bundle: Vec<Transaction> = txs.into_iter().cloned().collect();
+            bundle.sort_by_key(|tx| tx.current_index);
+            bundle.dedup_by(|a, b| a.current_index == b.current_index);
+            if bundle.len() == last_index + 1 && !iota_validation::is_bundle(&bundle)? {
+                bail!("Invalid bundle: {}", hash);
+            }
+        }
+
+        for (tx_trytes, tx) in parsed {
+            self.insert(tx_trytes, tx);
+        }
+        Ok(())
+    }
+
+    fn insert(&mut self, trytes: String, tx: Transaction) {
+        if self.transactions.contains_key(&tx.hash) {
+            return;
+        }
+        for approvee in &[&tx.trunk_transaction, &tx.branch_transaction] {
+            let approvers = self.approvers.entry(approvee.to_string()).or_default();
+            if !approvers.contains(&tx.hash) {
+                approvers.push(tx.hash.clone());
+            }
+        }
+        let seq = self.transactions.len();
+        self.transactions
+            .insert(tx.hash.clone(), Stored { trytes, tx, seq });
+    }
+
+    /// Returns a stored transaction
+    pub(crate) fn transaction(&self, hash: &str) -> Option<&Transaction> {
+        self.transactions.get(hash).map(|stored| &stored.tx)
+    }
+
+    /// Returns the trytes of a stored transaction
+    pub(crate) fn trytes(&self, hash: &str) -> Option<&str> {
+        self.transactions
+            .get(hash)
+            .map(|stored| stored.trytes.as_str())
+    }
+
+    /// Returns the hashes of stored transactions matching a predicate, oldest first
+    pub(crate) fn find<F>(&self, predicate: F) -> Vec<String>
+    where
+        F: Fn(&Transaction) -> bool,
+    {
+        let mut found: Vec<&Stored> = self
+            .transactions
+            .values()
+            .filter(|stored| predicate(&stored.tx))
+            .collect();
+        found.sort_by_key(|stored| stored.seq);
+        found
+            .into_iter()
+            .map(|stored| stored.tx.hash.clone())
+            .collect()
+    }
+
+    /// Returns the transactions directly approving `hash`
+    pub(crate) fn approvers(&self, hash: &str) -> &[String] {
+        self.approvers.get(hash).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns true if the transaction is referenced by a milestone
+    pub(crate) fn is_confirmed(&self, hash: &str) -> bool {
+        self.confirmed.contains(hash)
+    }
+
+    /// Returns the confirmed balance of an address
+    pub(crate) fn balance(&self, address: &str) -> i64 {
+        self.balances.get(address).cloned().unwrap_or(0)
+    }
+
+    /// Returns true if any stored transaction, confirmed or not,
+    /// withdraws from the address
+    pub(crate) fn is_spent(&self, address: &str) -> bool {
+        self.transactions
+            .values()
+            .any(|stored| stored.tx.address == address && stored.tx.value < 0)
+    }
+
+    /// Returns the transactions without approvers that can be built
+    /// upon, most recent first
+    pub(crate) fn tips(&self) -> Vec<String> {
+        let mut tips: Vec<&Stored> = self
+            .transactions
+            .values()
+            .filter(|stored| self.approvers(&stored.tx.hash).is_empty())
+            .filter(|stored| !self.inconsistent.contains(&stored.tx.bundle))
+            .collect();
+        tips.sort_by_key(|stored| std::cmp::Reverse(stored.seq));
+        tips.into_iter()
+            .map(|stored| stored.tx.hash.clone())
+            .collect()
+    }
+
+    /// Collects the bundle starting at `tail` by following trunk transactions
+    pub(crate) fn bundle(&self, tail: &str) -> Option<Vec<Transaction>> {
+        let mut tx = self.transaction(tail)?;
+        if tx.current_index != 0 {
+            return None;
+        }
+        let mut bundle = vec![tx.clone()];
+        while tx.current_index < tx.last_index {
+            let next = self.transaction(&tx.trunk_transaction)?;
+            if next.bundle != tx.bundle || next.current_index != tx.current_index + 1 {
+                return None;
+            }
+            bundle.push(next.clone());
+            tx = next;
+        }
+        Some(bundle)
+    }
+
+    /// Checks that the bundle of `tail` is complete, valid and would not
+    /// overdraw any address given the confirmed ledger. Returns a reason
+    /// when it is not.
+    pub(crate) fn check_consistency(&self, tail: &str) -> std::result::Result<(), String> {
+        let bundle = match self.bundle(tail) {
+            Some(bundle) => bundle,
+            None => return Err(format!("tail {} is missing parts of its bundle", tail)),
+        };
+        if self.inconsistent.contains(&bundle[0].bundle) {
+            return Err(format!(
+                "bundle {} was rejected by a milestone",
+                bundle[0].bundle
+            ));
+        }
+        if self.is_confirmed(tail) {
+            return Ok(());
+        }
+        match iota_validation::is_bundle(&bundle) {
+            Ok(true) => {}
+            _ => return Err(format!("bundle {} is invalid", bundle[0].bundle)),
+        }
+        if self.is_reattachment_of_confirmed(tail) && bundle.iter().any(|tx| tx.value != 0) {
+            return Err(format!(
+                "bundle {} is already confirmed through another attachment",
+                bundle[0].bundle
+            ));
+        }
+        if self.overdraws(&bundle) {
+            return Err(format!(
+                "bundle {} spends more than the confirmed balance",
+                bundle[0].bundle
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns true if another attachment of the same bundle is
+    /// already confirmed, so this one can never be
+    pub(crate) fn is_reattachment_of_confirmed(&self, tail: &str) -> bool {
+        !self.is_confirmed(tail)
+            && matches!(self.transaction(tail), Some(tx) if self.confirmed_bundles.contains(&tx.bundle))
+    }
+
+    fn overdraws(&self, bundle: &[Transaction]) -> bool {
+        let mut deltas: HashMap<&str, i64> = HashMap::new();
+        for tx in bundle {
+            *deltas.entry(&tx.address).or_insert(0) += tx.value;
+        }
+        deltas
+            .into_iter()
+            .any(|(address, delta)| self.balance(address) + delta < 0)
+    }
+
+    /// Issues a milestone approving `references` (or the two most recent
+    /// tips if empty), confirming every bundle in their past cone that is
+    /// valid and covered by the confirmed ledger, in the order they arrived.
+    /// Returns the index of the new milestone.
+    pub(crate) fn issue_milestone(&mut self, references: &[String]) -> Result<u32> {
+        let references = if references.is_empty() {
+            self.tips().into_iter().take(2).collect()
+        } else {
+            references.to_vec()
+        };
+        for reference in &references {
+            ensure!(
+                self.transactions.contains_key(reference),
+                "Unknown reference: {}",
+                reference
+            );
+        }
+
+        let mut cone: Vec<&Stored> = Vec::new();
+        let mut visited: HashSet<&str> = HashSet::new();
+        let mut pending: Vec<&str> = references.iter().map(String::as_str).collect();
+        while let Some(hash) = pending.pop() {
+            if !visited.insert(hash) || self.confirmed.contains(hash) {
+                continue;
+            }
+            if let Some(stored) = self.transactions.get(hash) {
+                cone.push(stored);
+                pending.push(&stored.tx.trunk_transaction);
+                pending.push(&stored.tx.branch_transaction);
+            }
+        }
+        cone.sort_by_key(|stored| stored.seq);
+        let tails: Vec<String> = cone
+            .into_iter()
+            .filter(|stored| stored.tx.current_index == 0)
+            .map(|stored| stored.tx.hash.clone())
+            .collect();
+
+        for tail in tails {
+            if self.is_confirmed(&tail) || self.is_reattachment_of_confirmed(&tail) {
+                continue;
+            }
+            match self.check_consistency(&tail) {
+                Ok(()) => {
+                    let bundle = self.bundle(&tail).unwrap_or_default();
+                    for tx in &bundle {
+                        *self.balances.entry(tx.address.clone()).or_insert(0) += tx.value;
+                        self.confirmed.insert(tx.hash.clone());
+                    }
+                    self.confirmed_bundles.insert(bundle[0].bundle.clone());
+                }
+                Err(_) => {
+                    if let Some(tx) = self.transaction(&tail) {
+                        let bundle = tx.bundle.clone();
+                        self.inconsistent.insert(bundle);
+                    }
+                }
+            }
+        }
+
+        let milestone = self.milestone_transaction(&references)?;
+        let hash = milestone.hash.clone();
+        self.insert((&milestone).try_into()?, milestone);
+        self.confirmed.insert(hash.clone());
+        self.milestones.push(hash);
+        Ok(self.latest_milestone_index())
+    }
+
+    fn milestone_transaction(&self, references: &[String]) -> Result<Transaction> {
+        let index = START_MILESTONE_INDEX as i64 + self.milestones.len() as i64;
+        let trunk = references.first().map_or(NULL_HASH, String::as_str);
+        let branch = references.get(1).map_or(trunk, String::as_str);
+        let now = Utc::now();
+        let tx = Transaction {
+            signature_fragments: "9".repeat(2187),
+            address: NULL_HASH.to_string(),
+            obsolete_tag: "MILESTONE9SIMULATOR99999999".to_string(),
+            timestamp: now.timestamp(),
+            current_index: 0,
+            last_index: 0,
+            bundle: NULL_HASH.to_string(),
+            trunk_transaction: trunk.to_string(),
+            branch_transaction: branch.to_string(),
+            tag: "MILESTONE9SIMULATOR99999999".to_string(),
+            attachment_timestamp: now.timestamp_millis(),
+            attachment_timestamp_lower_bound: index,
+            nonce: "9".repeat(27),
+            ..Transaction::default()
+        };
+        let trytes: String = tx.try_into()?;
+        trytes.parse()
+    }
+}
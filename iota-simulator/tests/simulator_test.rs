@@ -0,0 +1,1117 @@
+use iota_client::options::{
+    FindTransactionsOptions, GetAccountDataOptions, GetBalancesOptions, GetInclusionStatesOptions,
+    GetNewAddressOptions, GetTransfersOptions, PrepareTransfersOptions, SendPayoutsOptions,
+    SendTransferOptions, SendTrytesOptions,
+};
+use iota_client::{
+    AsyncClient, BoxFuture, Client, ClientError, Clock, ConfirmationPolicy, ErrorKind, FakePow,
+    FixedClock, LargestFirst, MemoryLedger, PowProvider, SeedSigner, Signer, SmallestSufficient,
+    SpentLedger, SteppingClock, SystemClock, TransferDirection,
+};
+use iota_conversion::Trinary;
+use iota_model::{Bundle, Input, Inputs, Transaction, Transfer};
+use iota_simulator::Simulator;
+use serde_json::json;
+use std::convert::TryInto;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tokio::prelude::future;
+
+const SEED: &str =
+    "IHDEENZYITYVYSPKAURUZAQKGVJEREFDJMYTANNXXGPZ9GJWTEOJJ9IPMXOGZNQLSNMFDSQOTZAEETUEA";
+const RECIPIENT: &str =
+    "ZLNM9EPFBLDLGKGQPYLBHMYXRYVMOWXKQLVCCUHVMITKYZATAKKQKHLKLIWQTTTCPXGKXZOCIZXTEIHFD";
+
+fn funded() -> (Simulator, Client, String) {
+    let address = iota_client::new_address(SEED, 2, 0, false).unwrap();
+    let simulator = Simulator::with_snapshot(&[(&address, 1000)]);
+    let client = Client::new("http://simulator").with_transport(simulator.clone());
+    (simulator, client, address)
+}
+
+fn send(client: &mut Client, value: i64) -> Vec<Transaction> {
+    send_to(client, RECIPIENT, value)
+}
+
+fn send_to(client: &mut Client, address: &str, value: i64) -> Vec<Transaction> {
+    let transfer = Transfer {
+        address: address.to_string(),
+        value,
+        ..Transfer::default()
+    };
+    client
+        .send_transfers(
+            vec![transfer],
+            SEED,
+            SendTransferOptions {
+                pow: Arc::new(FakePow),
+                security: 2,
+                ..SendTransferOptions::default()
+            },
+        )
+        .unwrap()
+}
+
+fn balance(client: &mut Client, address: &str) -> i64 {
+    client
+        .get_balances(GetBalancesOptions {
+            addresses: vec![address.to_string()],
+            ..GetBalancesOptions::default()
+        })
+        .unwrap()
+        .balances()
+        .as_ref()
+        .unwrap()[0]
+        .parse()
+        .unwrap()
+}
+
+fn confirmed(client: &mut Client, hash: &str) -> bool {
+    client
+        .get_inclusion_states(GetInclusionStatesOptions {
+            transactions: vec![hash.to_string()],
+            ..GetInclusionStatesOptions::default()
+        })
+        .unwrap()
+        .states()
+        .as_ref()
+        .unwrap()[0]
+}
+
+#[test]
+fn test_transfer_confirmed_by_milestone() {
+    let (simulator, mut client, address) = funded();
+    let sent = send(&mut client, 400);
+    let tail = sent.iter().find(|tx| tx.current_index == 0).unwrap();
+
+    assert!(!confirmed(&mut client, &tail.hash));
+    assert_eq!(balance(&mut client, RECIPIENT), 0);
+    assert!(client
+        .were_addresses_spent_from(std::slice::from_ref(&address))
+        .unwrap()
+        .state(0));
+
+    assert_eq!(simulator.issue_milestone(), 2);
+    assert!(confirmed(&mut client, &tail.hash));
+    assert_eq!(balance(&mut client, RECIPIENT), 400);
+    assert_eq!(balance(&mut client, &address), 0);
+    assert_eq!(client.get_bundle(&tail.hash).unwrap().len(), sent.len());
+}
+
+#[test]
+fn test_double_spend_is_never_confirmed() {
+    let (simulator, mut client, address) = funded();
+    let first = send(&mut client, 1000);
+    let refused = client.send_transfers(
+        vec![Transfer {
+            address: RECIPIENT.to_string(),
+            value: 1000,
+            ..Transfer::default()
+        }],
+        SEED,
+        SendTransferOptions {
+            pow: Arc::new(FakePow),
+            security: 2,
+            ..SendTransferOptions::default()
+        },
+    );
+    assert_eq!(
+        refused.unwrap_err().to_string(),
+        iota_constants::PRIVATE_KEY_REUSE_ERROR
+    );
+
+    // Bypass the protection by signing offline
+    let mut inputs = Inputs::default();
+    inputs.add(Input {
+        address,
+        balance: 1000,
+        key_index: 0,
+        security: 2,
+    });
+    inputs.set_total_balance(1000);
+    let trytes = iota_client::prepare_transfers_offline(
+        SEED,
+        vec![Transfer {
+            address: RECIPIENT.to_string(),
+            value: 1000,
+            ..Transfer::default()
+        }],
+        PrepareTransfersOptions {
+            inputs: Some(inputs),
+            security: 2,
+            ..PrepareTransfersOptions::default()
+        },
+    )
+    .unwrap();
+    let second = client.send_trytes(&trytes, fake_pow()).unwrap();
+    let first_tail = first.iter().find(|tx| tx.current_index == 0).unwrap();
+    let second_tail = second.iter().find(|tx| tx.current_index == 0).unwrap();
+
+    simulator.issue_milestone();
+    assert!(confirmed(&mut client, &first_tail.hash));
+    assert!(!confirmed(&mut client, &second_tail.hash));
+    assert_eq!(balance(&mut client, RECIPIENT), 1000);
+
+    let consistency = client
+        .check_consistency(std::slice::from_ref(&second_tail.hash))
+        .unwrap();
+    assert!(!consistency.state());
+    assert!(consistency.info().is_some());
+}
+
+fn fake_pow() -> SendTrytesOptions<'static> {
+    SendTrytesOptions {
+        pow: Arc::new(FakePow),
+        ..SendTrytesOptions::default()
+    }
+}
+
+#[test]
+fn test_promote_stuck_transfer() {
+    let (simulator, mut client, _) = funded();
+    let sent = send(&mut client, 400);
+    let tail = sent.iter().find(|tx| tx.current_index == 0).unwrap();
+    assert!(client.is_promotable(&tail.hash).unwrap());
+    let later = FixedClock::new(tail.attachment_timestamp + 11 * 60 * 1000);
+    assert!(!client
+        .is_promotable_at(&tail.hash, Arc::new(later))
+        .unwrap());
+
+    let promotion = client.promote_transaction(&tail.hash, fake_pow()).unwrap();
+    let promotion_tail = promotion.iter().find(|tx| tx.current_index == 0).unwrap();
+    assert_eq!(promotion_tail.trunk_transaction, tail.hash);
+    assert!(promotion.iter().all(|tx| tx.value == 0));
+
+    simulator
+        .issue_milestone_referencing(std::slice::from_ref(&promotion_tail.hash))
+        .unwrap();
+    assert!(confirmed(&mut client, &tail.hash));
+    assert_eq!(balance(&mut client, RECIPIENT), 400);
+}
+
+#[test]
+fn test_replay_bundle() {
+    let (simulator, mut client, _) = funded();
+    let sent = send(&mut client, 400);
+    let tail = sent.iter().find(|tx| tx.current_index == 0).unwrap();
+
+    let replayed = client.replay_bundle(&tail.hash, fake_pow()).unwrap();
+    let replayed_tail = replayed.iter().find(|tx| tx.current_index == 0).unwrap();
+    assert_eq!(replayed.len(), sent.len());
+    assert_eq!(replayed_tail.bundle, tail.bundle);
+    assert_ne!(replayed_tail.hash, tail.hash);
+
+    simulator
+        .issue_milestone_referencing(std::slice::from_ref(&replayed_tail.hash))
+        .unwrap();
+    // The reattachment approves the original bundle, which is confirmed
+    // first, so only one of the attachments moves the funds
+    assert!(confirmed(&mut client, &tail.hash));
+    assert!(!confirmed(&mut client, &replayed_tail.hash));
+    assert!(!client.is_promotable(&replayed_tail.hash).unwrap());
+    assert_eq!(balance(&mut client, RECIPIENT), 400);
+}
+
+#[test]
+fn test_await_confirmation_promotes() {
+    let (simulator, mut client, _) = funded();
+    let sent = send(&mut client, 400);
+    let tail = sent.iter().find(|tx| tx.current_index == 0).unwrap();
+
+    let issuer = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(300));
+        simulator.issue_milestone();
+    });
+    let now = SystemClock.now_millis();
+    let confirmation = client
+        .await_confirmation(
+            &tail.hash,
+            ConfirmationPolicy {
+                poll_interval: Duration::from_millis(100),
+                timeout: Duration::from_secs(30),
+                pow: Arc::new(FakePow),
+                clock: Arc::new(FixedClock::new(now)),
+                ..ConfirmationPolicy::default()
+            },
+        )
+        .unwrap();
+    issuer.join().unwrap();
+
+    assert_eq!(confirmation.tail, tail.hash);
+    assert!(!confirmation.promotions.is_empty());
+    let promotion = client.get_bundle(&confirmation.promotions[0]).unwrap();
+    assert!(promotion.iter().all(|tx| tx.attachment_timestamp == now));
+    assert!(confirmation.reattachments.is_empty());
+    assert_eq!(balance(&mut client, RECIPIENT), 400);
+}
+
+#[test]
+fn test_await_confirmation_times_out() {
+    let (_, mut client, _) = funded();
+    let sent = send(&mut client, 400);
+    let tail = sent.iter().find(|tx| tx.current_index == 0).unwrap();
+
+    let policy =
+        ConfirmationPolicy::poll_only(Duration::from_millis(50), Duration::from_millis(200));
+    let err = client.await_confirmation(&tail.hash, policy).unwrap_err();
+    assert!(err.to_string().contains("was not confirmed"));
+}
+
+#[test]
+fn test_account_data() {
+    let (simulator, mut client, address) = funded();
+    let sent = send(&mut client, 400);
+    let tail = sent.iter().find(|tx| tx.current_index == 0).unwrap();
+    simulator.issue_milestone();
+
+    let options = GetAccountDataOptions {
+        security: Some(2),
+        ..GetAccountDataOptions::default()
+    };
+    let account = client.get_account_data(SEED, options).unwrap();
+    let remainder = iota_client::new_address(SEED, 2, 1, false).unwrap();
+    assert_eq!(account.addresses.len(), 2);
+    assert_eq!(account.addresses[0].address, address);
+    assert_eq!(account.addresses[0].balance, 0);
+    assert_eq!(account.addresses[1].address, remainder);
+    assert_eq!(account.addresses[1].balance, 600);
+    assert_eq!(account.addresses[1].key_index, 1);
+    assert_eq!(account.balance, 600);
+    assert_eq!(account.inputs().inputs_list().len(), 1);
+    assert_eq!(account.bundles, vec![tail.bundle.clone()]);
+    assert_eq!(account.transfers, vec![tail.hash.clone()]);
+    assert_eq!(
+        account.latest_address,
+        iota_client::new_address(SEED, 2, 2, false).unwrap()
+    );
+}
+
+#[test]
+fn test_new_address_gap_limit() {
+    let (simulator, mut client, _) = funded();
+    let deposit = iota_client::new_address(SEED, 2, 3, false).unwrap();
+    send_to(&mut client, &deposit, 400);
+    simulator.issue_milestone();
+
+    let mut new_address = |window, gap_limit| {
+        client
+            .get_new_address(
+                SEED,
+                false,
+                false,
+                GetNewAddressOptions {
+                    security: Some(2),
+                    window: Some(window),
+                    gap_limit: Some(gap_limit),
+                    ..GetNewAddressOptions::default()
+                },
+            )
+            .unwrap()
+    };
+    let index_2 = vec![iota_client::new_address(SEED, 2, 2, false).unwrap()];
+    let index_4 = vec![iota_client::new_address(SEED, 2, 4, false).unwrap()];
+    assert_eq!(new_address(1, 1), index_2);
+    assert_eq!(new_address(10, 1), index_2);
+    assert_eq!(new_address(1, 3), index_4);
+    assert_eq!(new_address(2, 3), index_4);
+
+    let all = client
+        .get_new_address(
+            SEED,
+            true,
+            true,
+            GetNewAddressOptions {
+                security: Some(2),
+                window: Some(10),
+                gap_limit: Some(3),
+                ..GetNewAddressOptions::default()
+            },
+        )
+        .unwrap();
+    assert_eq!(all.len(), 5);
+    assert!(all.iter().all(|address| address.len() == 90));
+}
+
+#[test]
+fn test_transfer_history() {
+    const OTHER_SEED: &str =
+        "QSNQYQLLMEVFIBMKQVXQYXNWRQIXEJAOSXHFUTLHVTSZFDTBMMLQDVFUUWXVVVHCZFGJTCFCEXCSQRGHT";
+    let (simulator, mut client, _) = funded();
+    let recipient = iota_client::new_address(OTHER_SEED, 2, 0, false).unwrap();
+    let sent = send_to(&mut client, &recipient, 400);
+    let tail = sent.iter().find(|tx| tx.current_index == 0).unwrap();
+    let replayed = client.replay_bundle(&tail.hash, fake_pow()).unwrap();
+    let replayed_tail = replayed.iter().find(|tx| tx.current_index == 0).unwrap();
+
+    let options = GetTransfersOptions {
+        security: Some(2),
+        ..GetTransfersOptions::default()
+    };
+    let history = client.get_transfers(SEED, options.clone()).unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].bundle, tail.bundle);
+    assert_eq!(history[0].tails.len(), 2);
+    assert!(history[0].tails.contains(&replayed_tail.hash));
+    assert_eq!(history[0].direction, TransferDirection::Sent);
+    assert_eq!(history[0].value, -400);
+    assert!(!history[0].confirmed);
+
+    simulator
+        .issue_milestone_referencing(std::slice::from_ref(&replayed_tail.hash))
+        .unwrap();
+    let history = client.get_transfers(OTHER_SEED, options).unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].direction, TransferDirection::Received);
+    assert_eq!(history[0].value, 400);
+    assert!(history[0].confirmed);
+    assert_eq!(history[0].transactions[0].hash, tail.hash);
+}
+
+#[test]
+fn test_transaction_objects() {
+    let (_, mut client, _) = funded();
+    let sent = send(&mut client, 400);
+
+    let found = client
+        .find_transaction_objects(FindTransactionsOptions {
+            addresses: vec![RECIPIENT.to_string()],
+            ..FindTransactionsOptions::default()
+        })
+        .unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].value, 400);
+
+    let mut hashes: Vec<String> = sent.iter().rev().map(|tx| tx.hash.clone()).collect();
+    hashes.insert(1, "9".repeat(81));
+    let fetched = client.get_transaction_objects(&hashes).unwrap();
+    assert_eq!(fetched.len(), sent.len());
+    assert!(fetched
+        .iter()
+        .zip(sent.iter().rev())
+        .all(|(a, b)| a.hash == b.hash));
+}
+
+#[test]
+fn test_offline_prepare_transfers() {
+    let (simulator, mut client, address) = funded();
+    let remainder = iota_client::new_address(SEED, 2, 1, false).unwrap();
+    let mut inputs = Inputs::default();
+    inputs.add(Input {
+        address: address.clone(),
+        balance: 1000,
+        key_index: 0,
+        security: 2,
+    });
+    inputs.set_total_balance(1000);
+    let transfer = Transfer {
+        address: RECIPIENT.to_string(),
+        value: 400,
+        ..Transfer::default()
+    };
+
+    let missing_inputs = iota_client::prepare_transfers_offline(
+        SEED,
+        vec![transfer.clone()],
+        PrepareTransfersOptions::default(),
+    );
+    assert!(missing_inputs.is_err());
+    let missing_remainder = iota_client::prepare_transfers_offline(
+        SEED,
+        vec![transfer.clone()],
+        PrepareTransfersOptions {
+            inputs: Some(inputs.clone()),
+            security: 2,
+            ..PrepareTransfersOptions::default()
+        },
+    );
+    assert!(missing_remainder.is_err());
+
+    let trytes = iota_client::prepare_transfers_offline(
+        SEED,
+        vec![transfer],
+        PrepareTransfersOptions {
+            inputs: Some(inputs),
+            remainder_address: Some(&remainder),
+            security: 2,
+            ..PrepareTransfersOptions::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(trytes.len(), 4);
+    client.send_trytes(&trytes, fake_pow()).unwrap();
+    simulator.issue_milestone();
+    assert_eq!(balance(&mut client, RECIPIENT), 400);
+    assert_eq!(balance(&mut client, &remainder), 600);
+}
+
+#[test]
+fn test_clock_makes_trytes_reproducible() {
+    let (_, mut client, address) = funded();
+    let remainder = iota_client::new_address(SEED, 2, 1, false).unwrap();
+    let mut inputs = Inputs::default();
+    inputs.add(Input {
+        address,
+        balance: 1000,
+        key_index: 0,
+        security: 2,
+    });
+    inputs.set_total_balance(1000);
+    let transfers = vec![
+        Transfer {
+            address: RECIPIENT.to_string(),
+            value: 400,
+            ..Transfer::default()
+        },
+        Transfer {
+            address: RECIPIENT.to_string(),
+            message: "HELLO".to_string(),
+            ..Transfer::default()
+        },
+    ];
+    let prepare = |clock: Arc<dyn Clock>| {
+        iota_client::prepare_transfers_offline(
+            SEED,
+            transfers.clone(),
+            PrepareTransfersOptions {
+                inputs: Some(inputs.clone()),
+                remainder_address: Some(&remainder),
+                security: 2,
+                clock,
+                ..PrepareTransfersOptions::default()
+            },
+        )
+        .unwrap()
+    };
+
+    let fixed = prepare(Arc::new(FixedClock::new(1_500_000_000_000)));
+    assert_eq!(fixed, prepare(Arc::new(FixedClock::new(1_500_000_000_000))));
+    assert_ne!(fixed, prepare(Arc::new(FixedClock::new(1_600_000_000_000))));
+    let stepping = prepare(Arc::new(SteppingClock::new(1_500_000_000_000, 1000)));
+    assert_eq!(
+        stepping,
+        prepare(Arc::new(SteppingClock::new(1_500_000_000_000, 1000)))
+    );
+    let timestamps: Vec<i64> = stepping
+        .iter()
+        .rev()
+        .map(|trytes| trytes.parse::<Transaction>().unwrap().timestamp)
+        .collect();
+    assert_eq!(
+        timestamps,
+        vec![
+            1_500_000_000,
+            1_500_000_001,
+            1_500_000_002,
+            1_500_000_002,
+            1_500_000_002
+        ]
+    );
+
+    let sent = client
+        .send_trytes(
+            &fixed,
+            SendTrytesOptions {
+                clock: Arc::new(FixedClock::new(1_500_000_000_123)),
+                ..fake_pow()
+            },
+        )
+        .unwrap();
+    assert!(sent
+        .iter()
+        .all(|tx| tx.attachment_timestamp == 1_500_000_000_123));
+}
+
+#[test]
+fn test_cold_wallet() {
+    let (simulator, mut client, address) = funded();
+    let remainder = iota_client::new_address(SEED, 2, 1, false).unwrap();
+    let mut inputs = Inputs::default();
+    inputs.add(Input {
+        address,
+        balance: 1000,
+        key_index: 0,
+        security: 2,
+    });
+    inputs.set_total_balance(1000);
+    let transfer = Transfer {
+        address: RECIPIENT.to_string(),
+        value: 400,
+        ..Transfer::default()
+    };
+
+    // Online host, without the seed
+    let unsigned = iota_client::prepare_unsigned_bundle(
+        vec![transfer],
+        PrepareTransfersOptions {
+            inputs: Some(inputs),
+            remainder_address: Some(&remainder),
+            security: 2,
+            ..PrepareTransfersOptions::default()
+        },
+    )
+    .unwrap();
+    assert!(Bundle::from_signed_json(&unsigned).is_err());
+
+    // Offline host
+    let (mut bundle, inputs) = Bundle::from_unsigned_json(&unsigned).unwrap();
+    let outputs = bundle.outputs();
+    assert_eq!(outputs.len(), 2);
+    assert_eq!(outputs[0].address, RECIPIENT);
+    assert_eq!(outputs[0].value, 400);
+    assert!(bundle.to_signed_json().is_err());
+    iota_signing::sign_bundle(&SEED.trits(), &inputs, &mut bundle).unwrap();
+    let signed = bundle.to_signed_json().unwrap();
+
+    // Online host
+    let bundle = Bundle::from_signed_json(&signed).unwrap();
+    client.send_signed_bundle(&bundle, fake_pow()).unwrap();
+    simulator.issue_milestone();
+    assert_eq!(balance(&mut client, RECIPIENT), 400);
+    assert_eq!(balance(&mut client, &remainder), 600);
+}
+
+#[test]
+fn test_tampered_unsigned_bundle_is_rejected() {
+    let (_, _, address) = funded();
+    let mut inputs = Inputs::default();
+    inputs.add(Input {
+        address,
+        balance: 1000,
+        key_index: 0,
+        security: 2,
+    });
+    inputs.set_total_balance(1000);
+    let transfer = Transfer {
+        address: RECIPIENT.to_string(),
+        value: 1000,
+        ..Transfer::default()
+    };
+    let unsigned = iota_client::prepare_unsigned_bundle(
+        vec![transfer],
+        PrepareTransfersOptions {
+            inputs: Some(inputs),
+            security: 2,
+            ..PrepareTransfersOptions::default()
+        },
+    )
+    .unwrap();
+    let mut json: serde_json::Value = serde_json::from_str(&unsigned).unwrap();
+    assert_eq!(json["version"], 1);
+    assert_eq!(json["state"], "unsigned");
+    assert_eq!(json["inputs"][0]["key_index"], 0);
+    json["transactions"][0]["address"] = json!(SEED);
+    assert!(Bundle::from_unsigned_json(&json.to_string()).is_err());
+    json["version"] = json!(2);
+    assert!(Bundle::from_unsigned_json(&json.to_string()).is_err());
+}
+
+#[test]
+fn test_offline_signer_refuses_tampered_bundles() {
+    let (_, _, address) = funded();
+    let remainder = iota_client::new_address(SEED, 2, 1, false).unwrap();
+    let mut inputs = Inputs::default();
+    inputs.add(Input {
+        address,
+        balance: 1000,
+        key_index: 0,
+        security: 2,
+    });
+    inputs.set_total_balance(1000);
+    let unsigned = iota_client::prepare_unsigned_bundle(
+        vec![Transfer {
+            address: RECIPIENT.to_string(),
+            value: 400,
+            ..Transfer::default()
+        }],
+        PrepareTransfersOptions {
+            inputs: Some(inputs),
+            remainder_address: Some(&remainder),
+            security: 2,
+            ..PrepareTransfersOptions::default()
+        },
+    )
+    .unwrap();
+    let sign = |json: &serde_json::Value| {
+        let (mut bundle, inputs) = Bundle::from_unsigned_json(&json.to_string()).unwrap();
+        let signed = iota_signing::sign_bundle(&SEED.trits(), &inputs, &mut bundle);
+        assert!(bundle
+            .iter()
+            .all(|tx| tx.signature_fragments.chars().all(|c| c == '9') || tx.value >= 0));
+        signed
+    };
+    let json: serde_json::Value = serde_json::from_str(&unsigned).unwrap();
+
+    let mut other_key = json.clone();
+    other_key["inputs"][0]["key_index"] = json!(1);
+    assert!(sign(&other_key).is_err());
+    let mut other_security = json.clone();
+    other_security["inputs"][0]["security"] = json!(1);
+    assert!(sign(&other_security).is_err());
+
+    let (mut bundle, inputs) = Bundle::from_unsigned_json(&unsigned).unwrap();
+    let output = bundle.iter().position(|tx| tx.value == 400).unwrap();
+    bundle[output].address = remainder.clone();
+    assert!(iota_signing::sign_bundle(&SEED.trits(), &inputs, &mut bundle).is_err());
+
+    let (mut bundle, inputs) = Bundle::from_unsigned_json(&unsigned).unwrap();
+    let trytes = "9ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    let insecure = trytes
+        .chars()
+        .flat_map(|a| trytes.chars().map(move |b| format!("{}{}", a, b)))
+        .find_map(|prefix| {
+            bundle[0].obsolete_tag = prefix + &"9".repeat(25);
+            let hash = bundle.essence_hash().unwrap();
+            if Bundle::normalized_bundle(&hash).contains(&13) {
+                Some(hash)
+            } else {
+                None
+            }
+        })
+        .unwrap();
+    for tx in bundle.iter_mut() {
+        tx.bundle = insecure.clone();
+    }
+    let error = iota_signing::sign_bundle(&SEED.trits(), &inputs, &mut bundle).unwrap_err();
+    assert!(error.to_string().contains("13"));
+    assert!(bundle.to_unsigned_json(&inputs).is_err());
+
+    let (mut bundle, inputs) = Bundle::from_unsigned_json(&unsigned).unwrap();
+    iota_signing::sign_bundle(&SEED.trits(), &inputs, &mut bundle).unwrap();
+}
+
+/// Stands in for a signer living in another process
+#[derive(Debug)]
+struct RemoteSigner {
+    seed: SeedSigner,
+    signatures: AtomicUsize,
+}
+
+impl Signer for RemoteSigner {
+    fn address(&self, index: usize, security: usize) -> Result<String, ClientError> {
+        self.seed.address(index, security)
+    }
+
+    fn sign(
+        &self,
+        bundle_hash: &str,
+        index: usize,
+        security: usize,
+    ) -> Result<Vec<String>, ClientError> {
+        self.signatures.fetch_add(1, Ordering::SeqCst);
+        self.seed.sign(bundle_hash, index, security)
+    }
+}
+
+#[test]
+fn test_custom_signer() {
+    let (simulator, mut client, address) = funded();
+    let signer = Arc::new(RemoteSigner {
+        seed: SeedSigner::new(SEED).unwrap(),
+        signatures: AtomicUsize::new(0),
+    });
+    let account = client
+        .get_account_data(signer.clone(), GetAccountDataOptions::default())
+        .unwrap();
+    assert_eq!(account.latest_address, address);
+
+    let transfer = Transfer {
+        address: RECIPIENT.to_string(),
+        value: 400,
+        ..Transfer::default()
+    };
+    client
+        .send_transfers(
+            vec![transfer],
+            signer.clone(),
+            SendTransferOptions {
+                pow: Arc::new(FakePow),
+                security: 2,
+                ..SendTransferOptions::default()
+            },
+        )
+        .unwrap();
+    simulator.issue_milestone();
+    assert_eq!(signer.signatures.load(Ordering::SeqCst), 1);
+    assert_eq!(balance(&mut client, RECIPIENT), 400);
+}
+
+#[test]
+fn test_input_selectors() {
+    let addresses = iota_client::new_addresses(SEED, 2, 0..3, false).unwrap();
+    let simulator = Simulator::with_snapshot(&[
+        (&addresses[0], 30),
+        (&addresses[1], 500),
+        (&addresses[2], 100),
+    ]);
+    let mut client = Client::new("http://simulator").with_transport(simulator.clone());
+    let mut inputs = Inputs::default();
+    for (key_index, address) in addresses.iter().enumerate() {
+        inputs.add(Input {
+            address: address.clone(),
+            balance: 0,
+            key_index,
+            security: 2,
+        });
+    }
+    let transfer = Transfer {
+        address: RECIPIENT.to_string(),
+        value: 90,
+        ..Transfer::default()
+    };
+    let spent = |trytes: Vec<String>| -> Vec<String> {
+        trytes
+            .iter()
+            .map(|trytes| trytes.parse::<Transaction>().unwrap())
+            .filter(|tx| tx.value < 0)
+            .map(|tx| tx.address)
+            .collect()
+    };
+
+    let oldest_first = client
+        .prepare_transfers(
+            SEED,
+            vec![transfer.clone()],
+            PrepareTransfersOptions {
+                inputs: Some(inputs.clone()),
+                security: 2,
+                ..PrepareTransfersOptions::default()
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        spent(oldest_first),
+        vec![addresses[1].clone(), addresses[0].clone()]
+    );
+    let smallest_sufficient = client
+        .prepare_transfers(
+            SEED,
+            vec![transfer.clone()],
+            PrepareTransfersOptions {
+                inputs: Some(inputs.clone()),
+                security: 2,
+                selector: Arc::new(SmallestSufficient),
+                ..PrepareTransfersOptions::default()
+            },
+        )
+        .unwrap();
+    assert_eq!(spent(smallest_sufficient), vec![addresses[2].clone()]);
+
+    client
+        .send_transfers(
+            vec![transfer],
+            SEED,
+            SendTransferOptions {
+                pow: Arc::new(FakePow),
+                inputs: Some(inputs),
+                security: 2,
+                selector: Arc::new(LargestFirst),
+                exclude: vec![addresses[1].clone()],
+                ..SendTransferOptions::default()
+            },
+        )
+        .unwrap();
+    simulator.issue_milestone();
+    assert_eq!(balance(&mut client, &addresses[1]), 500);
+    assert_eq!(balance(&mut client, &addresses[2]), 0);
+    assert_eq!(balance(&mut client, RECIPIENT), 90);
+}
+
+#[test]
+fn test_spent_address_protection() {
+    let (_, _, address) = funded();
+    let remainder = iota_client::new_address(SEED, 2, 1, false).unwrap();
+    let mut inputs = Inputs::default();
+    inputs.add(Input {
+        address: address.clone(),
+        balance: 1000,
+        key_index: 0,
+        security: 2,
+    });
+    inputs.set_total_balance(1000);
+    let to = |address: &str| {
+        vec![Transfer {
+            address: address.to_string(),
+            value: 400,
+            ..Transfer::default()
+        }]
+    };
+    let ledger = Arc::new(MemoryLedger::default());
+    let options = |remainder_address| PrepareTransfersOptions {
+        inputs: Some(inputs.clone()),
+        remainder_address,
+        security: 2,
+        ledger: Some(ledger.clone()),
+        ..PrepareTransfersOptions::default()
+    };
+    let error = |result: Result<Vec<String>, ClientError>| result.unwrap_err().to_string();
+
+    assert_eq!(
+        error(iota_client::prepare_transfers_offline(
+            SEED,
+            to(&address),
+            options(Some(&remainder))
+        )),
+        iota_constants::SEND_TO_INPUTS_ERROR
+    );
+    assert_eq!(
+        error(iota_client::prepare_transfers_offline(
+            SEED,
+            to(RECIPIENT),
+            options(Some(&address))
+        )),
+        iota_constants::SEND_TO_INPUTS_ERROR
+    );
+    assert!(!ledger.is_spent(&address).unwrap());
+
+    iota_client::prepare_transfers_offline(SEED, to(RECIPIENT), options(Some(&remainder))).unwrap();
+    assert!(ledger.is_spent(&address).unwrap());
+    assert_eq!(
+        error(iota_client::prepare_transfers_offline(
+            SEED,
+            to(RECIPIENT),
+            options(Some(&remainder))
+        )),
+        iota_constants::PRIVATE_KEY_REUSE_ERROR
+    );
+
+    let mut other = Inputs::default();
+    other.add(Input {
+        address: remainder.clone(),
+        balance: 1000,
+        key_index: 1,
+        security: 2,
+    });
+    let spent_remainder = iota_client::new_address(SEED, 2, 2, false).unwrap();
+    ledger
+        .record(std::slice::from_ref(&spent_remainder), None)
+        .unwrap();
+    assert_eq!(
+        error(iota_client::prepare_transfers_offline(
+            SEED,
+            to(RECIPIENT),
+            PrepareTransfersOptions {
+                inputs: Some(other),
+                ..options(Some(&spent_remainder))
+            }
+        )),
+        iota_constants::SENDING_TO_USED_ADDRESS_ERROR
+    );
+}
+
+#[test]
+fn test_payouts_are_split_into_chained_bundles() {
+    let (simulator, mut client, address) = funded();
+    let recipients: Vec<String> = "ABCDEFG"
+        .chars()
+        .map(|tryte| format!("{}{}", &RECIPIENT[..80], tryte))
+        .collect();
+    let transfers: Vec<Transfer> = recipients
+        .iter()
+        .zip(1..)
+        .map(|(address, value)| Transfer {
+            address: address.clone(),
+            value: value * 10,
+            ..Transfer::default()
+        })
+        .collect();
+    let options = SendPayoutsOptions {
+        pow: Arc::new(FakePow),
+        security: 2,
+        max_transactions: 5,
+        ..SendPayoutsOptions::default()
+    };
+
+    let too_small = SendPayoutsOptions {
+        max_transactions: 3,
+        ..options.clone()
+    };
+    assert!(client
+        .send_payouts(SEED, transfers.clone(), too_small)
+        .is_err());
+
+    let payouts = client.send_payouts(SEED, transfers, options).unwrap();
+    assert_eq!(payouts.tails.len(), 4);
+    for tail in &payouts.tails {
+        assert!(client.get_bundle(tail).unwrap().len() <= 5);
+    }
+    let remainder = payouts.remainder.unwrap();
+    assert_eq!(remainder.balance, 720);
+    assert_eq!(remainder.key_index, 4);
+    assert_eq!(
+        remainder.address,
+        iota_client::new_address(SEED, 2, 4, false).unwrap()
+    );
+
+    simulator
+        .issue_milestone_referencing(&payouts.tails[3..])
+        .unwrap();
+    for tail in &payouts.tails {
+        assert!(confirmed(&mut client, tail));
+    }
+    for (recipient, value) in recipients.iter().zip(1..) {
+        assert_eq!(balance(&mut client, recipient), value * 10);
+    }
+    assert_eq!(balance(&mut client, &address), 0);
+    assert_eq!(balance(&mut client, &remainder.address), 720);
+}
+
+#[test]
+fn test_invalid_bundle_is_rejected() {
+    let (_, mut client, _) = funded();
+    let sent = send(&mut client, 400);
+    let mut tampered: Vec<Transaction> = sent.clone();
+    for tx in &mut tampered {
+        if tx.address == RECIPIENT {
+            tx.value = 1000;
+        }
+    }
+    let trytes: Vec<String> = tampered
+        .into_iter()
+        .map(|tx| tx.try_into().unwrap())
+        .collect();
+    let err = client.store_transactions(&trytes).unwrap_err();
+    assert!(err.to_string().starts_with("Invalid bundle"));
+}
+
+#[test]
+fn test_bundle_signatures_are_verified() {
+    for security in 1..=3 {
+        let address = iota_client::new_address(SEED, security, 0, false).unwrap();
+        let remainder = iota_client::new_address(SEED, security, 1, false).unwrap();
+        let mut inputs = Inputs::default();
+        inputs.add(Input {
+            address: address.clone(),
+            balance: 1000,
+            key_index: 0,
+            security,
+        });
+        inputs.set_total_balance(1000);
+        let trytes = iota_client::prepare_transfers_offline(
+            SEED,
+            vec![Transfer {
+                address: RECIPIENT.to_string(),
+                value: 400,
+                ..Transfer::default()
+            }],
+            PrepareTransfersOptions {
+                inputs: Some(inputs),
+                remainder_address: Some(&remainder),
+                security,
+                hmac_key: Some("HMAC"),
+                ..PrepareTransfersOptions::default()
+            },
+        )
+        .unwrap();
+        let mut bundle: Vec<Transaction> = trytes
+            .iter()
+            .rev()
+            .map(|trytes| trytes.parse().unwrap())
+            .collect();
+        assert!(iota_validation::is_bundle(&bundle).unwrap());
+
+        let last_fragment = bundle.iter().rposition(|tx| tx.address == address).unwrap();
+        bundle[last_fragment].signature_fragments = "A".repeat(2187);
+        assert!(!iota_validation::is_bundle(&bundle).unwrap());
+    }
+
+    let (simulator, _, _) = funded();
+    let mut client = Client::new("http://simulator").with_transport(simulator);
+    let mut forged = send(&mut client, 400);
+    let input = forged.iter().position(|tx| tx.value < 0).unwrap();
+    forged[input].signature_fragments = "9".repeat(2187);
+    let trytes: Vec<String> = forged
+        .into_iter()
+        .map(|tx| tx.try_into().unwrap())
+        .collect();
+    let err = client.store_transactions(&trytes).unwrap_err();
+    assert!(err.to_string().starts_with("Invalid bundle"));
+}
+
+#[test]
+fn test_unknown_command() {
+    let simulator = Simulator::new();
+    let response = simulator.handle(&json!({"command": "getMissingThings"}));
+    assert_eq!(response["error"], "Command [getMissingThings] is unknown");
+}
+
+#[test]
+fn test_http_server() {
+    let simulator = Simulator::new();
+    simulator.issue_milestone();
+    let addr = simulator
+        .spawn_server(&"127.0.0.1:0".parse().unwrap())
+        .unwrap();
+    let mut client = Client::new(&format!("http://{}", addr));
+    let info = client.get_node_info().unwrap();
+    assert_eq!(info.app_name(), "IRI Simulator");
+    assert_eq!(info.latest_milestone_index(), 2);
+}
+
+/// Does fake proof of work for the first `attachments` bundles, then fails
+#[derive(Debug)]
+struct FailingPow {
+    attachments: AtomicUsize,
+}
+
+impl PowProvider for FailingPow {
+    fn attach(
+        &self,
+        client: &AsyncClient,
+        trunk_transaction: &str,
+        branch_transaction: &str,
+        min_weight_magnitude: usize,
+        trytes: &[String],
+        clock: Arc<dyn Clock>,
+    ) -> BoxFuture<Vec<String>> {
+        if self.attachments.fetch_sub(1, Ordering::SeqCst) == 0 {
+            return Box::new(future::err(ClientError::Iri("PoW failed".to_string())));
+        }
+        FakePow.attach(
+            client,
+            trunk_transaction,
+            branch_transaction,
+            min_weight_magnitude,
+            trytes,
+            clock,
+        )
+    }
+}
+
+#[test]
+fn test_failed_payouts_return_the_bundles_sent() {
+    let (simulator, mut client, address) = funded();
+    let transfers: Vec<Transfer> = "ABCDEFG"
+        .chars()
+        .map(|tryte| Transfer {
+            address: format!("{}{}", &RECIPIENT[..80], tryte),
+            value: 10,
+            ..Transfer::default()
+        })
+        .collect();
+    let options = SendPayoutsOptions {
+        pow: Arc::new(FailingPow {
+            attachments: AtomicUsize::new(2),
+        }),
+        security: 2,
+        max_transactions: 5,
+        ..SendPayoutsOptions::default()
+    };
+
+    let error = client.send_payouts(SEED, transfers, options).unwrap_err();
+    assert_eq!(error.cause.kind(), ErrorKind::Iri);
+    assert_eq!(error.sent.tails.len(), 2);
+    let remainder = error.sent.remainder.unwrap();
+    assert_eq!(remainder.key_index, 2);
+    assert_eq!(remainder.balance, 960);
+
+    simulator
+        .issue_milestone_referencing(&error.sent.tails[1..])
+        .unwrap();
+    for tail in &error.sent.tails {
+        assert!(confirmed(&mut client, tail));
+    }
+    assert_eq!(balance(&mut client, &address), 0);
+    assert_eq!(balance(&mut client, &remainder.address), 960);
+}
@@ -1,6 +1,5 @@
 use regex::Regex;
 
-use iota_constants;
 use iota_model::*;
 
 lazy_static! {
@@ -10,8 +9,7 @@ lazy_static! {
 
 /// Validates that the provided string is an address
 pub fn is_address(address: &str) -> bool {
-    address.len() == iota_constants::ADDRESS_LENGTH_WITHOUT_CHECKSUM
-        || address.len() == iota_constants::ADDRESS_LENGTH_WITH_CHECKSUM && is_trytes(address)
+    iota_conversion::is_address(address)
 }
 
 /// Validates that a slice of strings are all addresses
@@ -212,7 +210,10 @@ mod tests {
 
     #[test]
     fn test_is_address() {
-        assert!(is_address(TEST_ADDRESS_WITHOUT_CHECKSUM))
+        assert!(is_address(TEST_ADDRESS_WITHOUT_CHECKSUM));
+        assert!(!is_address(
+            &TEST_ADDRESS_WITHOUT_CHECKSUM.to_lowercase()[..81]
+        ));
     }
 
     #[test]
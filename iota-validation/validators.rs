@@ -8,7 +8,16 @@ use crate::Result;
 use super::input_validator;
 use std::convert::TryInto;
 
-/// Validates that a slice of transactions is a valid bundle
+/// Highest security level, the number of fragments of the largest signatures
+const MAX_SECURITY_LEVEL: usize = 3;
+
+/// Validates that a slice of transactions is a valid bundle: indexes and
+/// values are consistent, the bundle hash matches the transactions, and
+/// the signature of every input, spread over the input transaction and
+/// the zero-value transactions following it at the same address (one per
+/// security level), was made by the key of its address. HMACs only
+/// overwrite the messages of outputs, so they are never read as
+/// signatures.
 pub fn is_bundle(bundle: &[Transaction]) -> Result<bool> {
     if !input_validator::is_slice_of_transactions(bundle) {
         return Ok(false);
@@ -30,13 +39,17 @@ pub fn is_bundle(bundle: &[Transaction]) -> Result<bool> {
         let tx_trits = (&tx_trytes[2187..2187 + 162]).trits();
         kerl.absorb(&tx_trits)?;
         if tx_value < 0 {
-            let this_address = &tx.address;
-            let mut new_signature = Signature::default();
-            for i in index..bundle.len() - 1 {
-                let new_tx = &bundle[i + 1];
-                if new_tx.address == *this_address && new_tx.value == 0 {
-                    new_signature.add_fragment(new_tx.signature_fragments.clone());
-                }
+            let mut new_signature = Signature {
+                address: tx.address.clone(),
+                ..Signature::default()
+            };
+            new_signature.add_fragment(tx.signature_fragments.clone());
+            for new_tx in bundle[index + 1..]
+                .iter()
+                .take(MAX_SECURITY_LEVEL - 1)
+                .take_while(|new_tx| new_tx.address == tx.address && new_tx.value == 0)
+            {
+                new_signature.add_fragment(new_tx.signature_fragments.clone());
             }
             signatures_to_validate.push(new_signature);
         }
@@ -52,15 +65,80 @@ pub fn is_bundle(bundle: &[Transaction]) -> Result<bool> {
     if bundle.last().unwrap().current_index != bundle.last().unwrap().last_index {
         return Ok(false);
     }
-    // TODO
-    // for sig in signatures_to_validate {
-    //     if !crypto::signing::validate_signatures(
-    //         sig.address(),
-    //         sig.signature_fragments(),
-    //         &bundle_hash,
-    //     )? {
-    //         return Ok(false);
-    //     }
-    // }
+    for sig in signatures_to_validate {
+        match iota_signing::validate_signatures(&sig.address, &sig.signature_fragments, bundle_hash)
+        {
+            Ok(true) => {}
+            _ => return Ok(false),
+        }
+    }
     Ok(true)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use iota_model::Bundle;
+    use iota_signing::HMAC;
+
+    /// An output of 6 with an HMAC, followed by inputs of 1, 2 and 3 at
+    /// security 1, 2 and 3, signed with key indexes 1, 2 and 3
+    const TEST_BUNDLE: [&str; 7] = [
+        "Y9DNNVPNZGEVHQACJMXRZQNWVOQPPOGUIPDTE9NMXDDOYEJIRKTEEZYKDTDXZIYH9LCGAG9KVNXNTASKR999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999PNGMCSNRCTRHCHPXYTPKEJYPCOWKOMRXZFHH9N9VDIKMNVAZCMIYRHVJIAZARZTUETJVFDMBEBIQE9QTHF99999999999999999999999999TYBUNDLE9999999999999999999DCGFKAD99999999999F99999999RIOTKWHFSPYFBVIYWAFACLTXYWDRSGOAFFAWCBDVSPJBBOXID9FKSVH9XXDTKGEIJGLEVSLTZGUDUQVRY999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999ISBUNDLE99999999999999999999CGRSPC999CGRSPC999CGRSPC99999999999999999999999999999",
+        "HDLLMBIC9EXCTDBEYRGWVSLVPEPJPHMVBBMTSPHQHZLHEPNKROIDBWPTXJASKWHGGNFLWZBVUJVBHMOSALYPUKQRA9HTDQESIQDMDBXRGNSSKOIILZJPBZOFZKUZJGQVEKAZEYBQKFMZNRGJRPNUOIXOKF9BQOLZLBQEAOABJ9MY9JPYRFBCRDHDWDEXYHJHTLDNP9RHPNHY9NIGLEOHKTIPXXOIDNIJQASPPUK9ZK9NDSLVVSCQ9EYLZWNQXZGYXSHYSZHVWBLTUHAYTHDSWBFDIWNTCMYDIYJJQPEVQIHZVWUHOUHMXOZVM9FRKIACYWWXTOKXBYVGEJGIOKFVRWRZBASKOKOXEZUGCQMJOAWCCW9XXXSFVRAREPLTYJKPWPOVMYHCRROBBUDTBFLOWK9NRNSESMGEOHPRMMLZDFYSMAUVADSIXYKZKAPEJUWLSLG9XCPHKSQDIYCUHHDJV9DFWBBH9RKFXPQQJCWNOGNW9CDDDTGZJMIACBAFIIFTDBVJDQLOUFKAAQFJLBVJFYEWBNVFORTZIOMBHVQMQFH9TEAYEIQXGHYQQRULIVDB9VYPRA9ZVQAUMEYAEJZXPDZPQIUYOMSMKA9EHSOEHQETSXDSQHGALGYBWFMJGTG9IHEWGXECATS9GZAKN9YPIAQZMDNECQNAJJNELNNUHNKWKV9YDEBLLC9POQSJABUYCHRS9IYLHGSKHOGGINVIJNSLA9QKFUXQ99QERZPGGLNDVL9BNEMMJ9OUPKOTAPQWUDVEULTF9BXSCK9AFYJ9X9RSAM9REP9ZWJVCQUUXNXEZCVFFUVBKQFBVNQBOPCFOMXCIJFZGVACRWVRRQ9NETRTOGOAKKVFCPQEFBOVEUVLPQDKSXTZX9IANZHYZSZLKWELTPJJTI9FGLY9IRSZZLNAEXK9BJAPNRLIHXKCQT9FVMJRAC9OZPXEGVBCVT9FACYMFHIFIWEZDGRCOUI9GSMESSJNUEPEFRJWMHUOBBHPMHSTDUZFLUZDUP9VOWLPRLHHJKJICHGOREZGMIHZAIAEVLJWMBKVFZSEGQARE9OOAPVJCYMOGEITTWIHFPSEHYSHBKHXQFANHNGTKAPZR9MDNCJDEQWCD9Q9WQDGJP9FLFYCGWCAPEMZQAQTKNXXIQ9DJEFIEWVMKB9IGYBMISABWMSQJDSHBHONGG9FBAOHEUFJAAIIZGXDPXPISPTADJBWZLKFDX9AAYMYUPPVKZNXXDLRX9HOQXEDMZUHEUBMBSMETHXCYYJVJIEMKOVCGKXTHBTQVRVLDF9JXJMRYECNQVZPVEUXB9ZBCSXWFGPHYPP9MKXXHGTGNKQ9YYMLBAVZLXCLRTR9CNEBPZTFY9ODAAIUAVZLNBPAXHQKLNWCOOEEULOMZWNICDZCYYBKKTYRDITLBVCUNDMZWYLMSOLZFZKDSQ9WTZCJWJEZIKUOJLD9LBDO9XR9SZZZTRKWYPIPNGWCSWVCQEJQJH9UX9DBYNYRPTTWIRLKXOHACWZ9NZZHWAQLKJDFJUQ9ANDUWHQXNSZYMGR9XEPBCQGUVJRRLSEYFFNAMEREBHADEJKOXGKDUTIGOPDZTAQDRWHVQBLWBZHFSLJPXSEYOOOVYLTRMWENNDKEAMIHXJQM9RTGVOHQRRAFGHLZHXHFJGWYVMHWOTEMVDAGZDFZSRMZCAHVWNCWPBOJYPLMYAGGQGSRUTBAZQNGBPPWWTEKDLPLZBHUQWWGORRTFEC99FTLUCHSCLDJUNTDYCNGWR9W9YZHEWTNRXOBDTBDKL9MNQAPYBBIWHPHLNFYKGZ9POTQRIXIKZAFYUDTESEBDZ9OBVKJIF9AXGQSFMIVZYFERG9XKMSHTVPDJWCWOPJF9NPWHCM9MKPKUMJLFFVUS9KVECDWHQGAYKVBHTENPYWEWHXGNQNQMSDSRMBKMBUSU9SANSRMYCGMZSMFDNXDIIUCMOXYXPUINCIIXXQLFVHUTGUWKSTP9NLVOOHPYOAFEZOOOQTCLIPBUTFGN9HASGA9RZEIDJIJCMJTHFNNAYUHAXUQSILAESTJTUMDXBHRRVCPEUU9XZOBXAMOGOZYICMGQLAIARIBNSGHASWVFJZEXRIA9RKYODPBZJBABPCYTBIGMCS9WPKUBKJJHGMHQHGGCWWFDQKMBNYYJHVIITDTKTGXNBJCLNEDCS9AXVWIO9XRAHKTUBHMAWEKCJCAZCTCAKQAIEMUH9RTIQTLMRAALSPZHNAJXRQXTYZGXXDGBFBKPLZQPKZFCZ99999999999999999999999999ISBUNDLE9999999999999999999DCGFKAD99A99999999F99999999RIOTKWHFSPYFBVIYWAFACLTXYWDRSGOAFFAWCBDVSPJBBOXID9FKSVH9XXDTKGEIJGLEVSLTZGUDUQVRY999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999ISBUNDLE99999999999999999999CGRSPC999CGRSPC999CGRSPC99999999999999999999999999999",
+        "NO9FGCXSXGLVZRRJBDSDIZTRVQGXIQETXILIDU9ETOSRNXOKXFYSUDRICYLELYSLSYDDGXBXHPOFJXPTXTPQZVXWZ9GCYPOE9OHZCBCV9BTOZZXJXGWYJYIRKIBAYDCBKOG9SJGSFUBCPYTQUUIP9WBEWTUGTRIKJCNQ9LRH9LX9WPEZLTC9PNHQFSQMJXJBEBR9EOMXACRVBURKYONNAURHEGXXPXLUGBOWFVYWOUABYHOGQQDBSXIRKVSJ9CCYYADUFWCBHW9XRMMZNJFESWRSO9DEDWKDJH9BRZPNHHEJSBXR9BLAYCCUBZIJLOHLHPJZCSYTQNWMSLAFJURECFRRXRNYPTKPLCCQATLFISINRPBKUNXVWPHTTGZ99XXRDMQCCQZ9FULPWXOEBJUBWWB9CWJVEDSTHORHLZJXCADD9ATSAIRB9OGXIKKAQR9VM9XBROEPSSIUEWDXMBDXVQYGTWBCCOHQARYHRYXU9BQDOITKOLMVEKS9XVDDEKRMVPEQKQVWZWCYLXWBEORPLTLJVMXGGJPDZFXHJY9XAALBRV9INHRCSUCJVMADGOPTXQSRXZQUUOUNXVGTO9XCBJBLTDEKPDYEDDTRMHYXSQIVITIQMOLTHVYIFAMDKBHPFRXYZVMAXNHSPULTINDLQVXDKKLHOXGNFNPJCYYEBCWQHWIMDXWSSVV9JZRXYPPMPBDXQUPLYTOLUVSCPOFBREPP9EKWARZNBJEZ9DQVROKQBOBIVGGRDFQJCIFCMLKYUA9JNULZLRVZCPNWZPNMJFBZGXWBTPIWKPIYEIFVBBNFLBXXMI9EZASVCPZJKBYXSWIFTGHKLDLEZEWKXXHQC9FMDJYOQKKBTGLILZZYISYYARPTYWRZQTHOSQZZJ9DQNNNAGQNCGF9GGRPCZPPMSTQQNDUH9TVJSLYFJFQFNSBFNVSBVTEXXFXWOEZCSWPMHDZGZAXELAV9KSAJZWAWRQGNNKTDDQHSPOPYDJJQAZOW9BTNHVJHZMRYPYKMNIVGOKAI9VXTBEBNOYVZM9FOTJWDAAXWDAAZPCKDUEISPSJWKVO9JASODTMMR9KPZBNMSXYOXKVIXOCQM9DEELHXVMGTCPARWYIYS9JNSSNNMDFOSDRVBSJNET9RTXKX9TBMUJOQPHFFAWAUNHKHJGUMAEGSNSHYVBXDYIFDFS9TVDKWSBDFJCU9PEWTSCQHMRDCZJMUWNRVCNIOCUQL99QMFZWRKELK9XDTOZ9NACJMKOTZC9LBEFKBGSRIYWYYNBZUKLDKPLKAIYDKTBGDFNGYMQUGDQLFDBFGBPTXQDDLOEEMWXEHPLDMTCQGCDJUGIDCQNJFUXWLTGAW9OWRQAUWQNEAVQQXWVNNBIDABYHFREVE9AWFXLYUKSAIPJWSYNQYBQSKQWAJ9PGCGW9GBBUSKAJDETHCOGXDLEEKLEKYQHSHYIKVTDKFHPXBAVPBVICC9IIMWLGJGWNKSDJTRELVZHI9TTGQPKLPFHEJNOPSZLJTMHW9BDUYAHWJQDLMPRIXQPBXXZQN9LVNLYLBVNOLPUAMSLOTUFYIEZROTDZNQGEPKZ9KMW9UQLOKGKQB9HGZZ9KTDKQQYKEPLEODOJSWUAVNKZQSNCDCRXJYSLNJAWHLUWP9QCKTOXXRDTEOHPPSIQFYT9FZLQXCNWBKDQ9IGGQJHBFTFSAQMWOFZJGOQFNQ9GGYAYC9TZVSYSDJP9NVO9HA9YULTPBZLWDU9KSDZRAHCWHEPFOOSPDKHYFHFXXYVIAZHYWQHAHYIPWPDUXW9ZLWMHDLNOENXOBUYQSNEBGDLPYPSJAQDDFRSDQ9TBEMHNWPEXWZDPABDS9QFVBILLJRLQWCLCGS9ZIOWNMBRWLYISUQDTNZ9XIZRAFYXOX9HBYJXVDZSFGCOEQEVCYPW9WBHSIGYGTDOQMQISCKEAYYKHAWBVSHLEJXYBYPYJISFQUJNSJQOPH9U9BNHMWWKCEGJVYZLDELAIITFRYEXPEXVTECHKAXVPLTCKIPC9PSJTUHSOEUCHCFVSB9EJZYGYZOLFVLCJOFJSRDASJNMHPXF9O9YUPRRMSBCUVA9LLKSYSLARR9W9JWEZFFVPXLYFUOOFCVG9IYDFAAEFIYSSFUGSXIRHPNRMTYUAHNAJBDKCXNDCQUMNGCNYRFRKNGAEPDGNGUSX99JRGTBWAZCRGBTUIVITTMPHGYRTVRQ9HAUQJKNICDDTECBBWRHUESMMKMGBYFWCIXZTDR9GYCFVDZYLFXY99999999999999999999999999ISBUNDLE9999999999999999999DCGFKAD99B99999999F99999999RIOTKWHFSPYFBVIYWAFACLTXYWDRSGOAFFAWCBDVSPJBBOXID9FKSVH9XXDTKGEIJGLEVSLTZGUDUQVRY999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999ISBUNDLE99999999999999999999CGRSPC999CGRSPC999CGRSPC99999999999999999999999999999",
+        "M9MIKCMLSOFPDRVKPSKWXANJZQDAAED9JSVEKT9CIUFJCFY9BILOQBPCGRFRDCZPZFZSVCPAN9BBXZFACHAEAGONBSNZSDZDUECNO9CBPHIVAL9IMBSHIAUTCFFKWJLLNDDSRBDVIT9CSYR9WKTHOKHMQPSKLFVTVDFZWVKVNPNVHEFIDUUGJWTHO9HHA9ULCOLINGZZFUWIPAX9FKXAIJGYOCGMEGRQNKHWENSKT9MQLKZDSYWFX9MRZLIGBI9RZRQJAHEOBJHYHMIFJYNKRYRM9XLYOUUOZRZGULMLSQZJDZETKQTHDOW9XMXUIHBOJRGAVXDKUQX9IBUWLETASKS9AMZ9EUICKUZAAQIBVPXVMCJCXVXQAJWCKDQD9ENOVTHC9VPMOAFHSNQC9IBRBBUNWJTKBPWHGFDNXYHFWU9YJQHDJLSXSPGXBFQTQRPL9L9RKJZCBLWYJNAPJRENH9KK9LKCKBADVWLQLCOECCOTBDMHGVJEJMRLJQPUQSLLWMTPVLPQNOKBESPAQYFWUKDVYXHPDYKDKMVPAHLPPZAORTDEQROOXVDCTHMAYWWFSDABFMCTZRFW9MKAZBZSNKCLACSOJWKMIRDIYKHCRFACDAWTH9HIFVBCCNMVGAWNGEEETHBYKRXUGUIBGEKDCPGBADIUJQUZEKSMVLYKKACWKPRIWTODUMVJBUCGMHFVLIMKD9BJGFDAMQFEYEUQZZDDDUIEGRH9RPUPPELUTOCTNAS9JLMHEEDPRMMBIUHFYSCPGHVWAVMPFQPOUXUFA9FL9OUAFQPLQKLZZCSXMZLPDCZUVQR9EQWAOURIFUAPXSRBCEYJEQVP9YBAQVXH9WARVMFLPRAHUETXZJZGGVTHZPTUGCMLZXHBZFWAGJJUAHDANLXYUGTFHQTRLZNCMGJQZFJKYRJFSYMIABHBHVIWAJUMU9OAHGIESFODJBDITYNYDZHLHKDCOLONFCKHBUECMAOGUQUCYTM9VRHMDFUNKQVLZOBFUWVPAXKTNUJB9WR9CZOYOM9WPZHYLTYYIKRJOZBEXMRQMYOQWHRE9ETZRLADUSGDPPOZGELFHXNPCHX9D9AJWTUAEKEDLAZBXRATPSUAIKNWDVCIMLFTGILXEWXZACEEFFHBLTEXMZGO9XGCJZHQQVMBQZDFRSZQYXBUOXCGJFVFJTUEVM9UJBQOEJSVBRCNJZASOSHGFJDENAJKUDFGXLSGKOSUJMOLRW9HQUIPGRFATEXVGEXHAZZFCINASXNKHSYLSFPDRTMLHFJFKKFXIIVOD9AZVTABXMKDMNCUAKDOOZACVVIKBVVUORVYYEXQPLSDBLAJTSCSBIFDDUJFWLVSBPSTDVPPYDYWONVNYZHDAJQGPNVTYOFYVHNOIUXMUMFJCJDQQSPGJREXMOHIBTYKCLYFDAF9YWVH9UUL9XMGHEQNJJZQUJBZLDCRXXZ9VXTNTPMBITWDUOGLD9MRCUUXDSCCUFOPE9PXXBDYEOOUGGJKCKUPSLLWIOHUWVSRKOFSFWUKZAQLHJCHWCRCOQISQTDQTXAG9NFIKXVWNFWBIGSZPTZTRLGKHYKYXLKZIOOMQWHROKYMKKFNSLLSSEOYACCL9MGWHSGXGYVAJBKHHLIBNZBT9NAPUY9BGGHCRMCSDVHGODMCFRTWAYHXWZBRZ9TQLNXGSRQTRFWWR9KUTVAXCFZPNJUECGNMWYQJJGESXCAEGOCNLEFHNPRFAKMWTHJAKXEEX9XNGDDEQZWCQWLIBYYBCWPBRMRYB9QJDASBTA9UFSENMFVGCXCZBJLKBCINKWCLQWSIXNNDKRUIESKOJHAHZFWVXEBAYINEKZHKZPWPMT9CZFIGXLYNATAZ9KFAVDVULFVWRRLMANTEQRUEUGMZIHTBBOPZEENCJIQGTUNRZRNERAWZME99P9WZMFTKXLEHYY9YWAYXDUCGEITHMS9UCEIEVJGGWSHFSSLQXTWWJEIFVUZSWPE9DKMFEIXALXDIBDIFKDGQICIDXBJ9LXLX9YQQJNHACNVNCIDQGS9BDMNLFIMPYXVRPMVGPHLO9KERPHLTJFUCMXJCGLWERMLYXGTMGBJVOLTZDZYKQYPCULDIZWWYUAJPLKW9HUFBQQFVHEDTULI9HMEMOIKPZNNENZHJYURTSBNNRWRQCDRZWEMA9PJWNGZNMAZ9JRGTBWAZCRGBTUIVITTMPHGYRTVRQ9HAUQJKNICDDTECBBWRHUESMMKMGBYFWCIXZTDR9GYCFVDZYLFX999999999999999999999999999ISBUNDLE9999999999999999999DCGFKAD99C99999999F99999999RIOTKWHFSPYFBVIYWAFACLTXYWDRSGOAFFAWCBDVSPJBBOXID9FKSVH9XXDTKGEIJGLEVSLTZGUDUQVRY999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999ISBUNDLE99999999999999999999CGRSPC999CGRSPC999CGRSPC99999999999999999999999999999",
+        "HORNEGLGPTTUDJMEILYNG9NCQBRT9GOHMOODD9QHDWEZPPRFBLONFLNMUJBJVXSJQLZSWCVPBTKMNDSCDSSTPWXBCIKLFSQLADGIHKNYTOKJJWIKXOSEIQQTP9GLXEHAXXPJUEL9WA9CMAEGFAZEKEGQPILZXZFM9C9ZR9HBVDLFYYELWUVTXFQYSCXC9FOIBGEIJOXM9ZENRPFHOTO9I9WUOSIFHAJHMAFZPSQBBVNTQMPVNJXHWLZMWXAMQZICAPVUETPLMZMNALERVKTRLCOJKHDCYWSHHSWEAOLJALWTPZVZVFDOC9YDCWWLXJCCCCLXXAUSGMAEGBPEDGKSGOEYDKWBDBKCCRTD9JCERAWPGWFXUCQZGPCSVJZ9EWTKHMVKAMBV9YCMLPCAOERTWNXVKNQLBIPFGYJEZQUOPRGEVUWHVQXXO9AZKODEKKDGLCVMGBMLBCO9JOCBRBTPWQJHFHBKWVOYVAPWZWLKISBVVTXR9IORHKEAFPGAHIANIQ9WYIQEMKLHJGHQPTHGLVJIBXCBM9IXJMVZGEJLZ9IPWIDVEPVNWWXZXSTKNURIFTFBB9OOFIDTRHRSGXSPMPTIEDSJMQYJXCOBFUBDHOMQQNQA99UYRRXK9HRFKWELWPTQGZZC9XOSGF9QYP9EKGLWUJQZBKJQH9HUTWU9NRXRWHVUPJDIOZFXINFXFLKLFOFEKPFSRXMFZJPFDYSCTGWOZUGR9PTUEDFWVFKIYUMOFQJCIDDKTXWYTIPQEFJVFQBBNDTGMYCLFKPJWKIXRVAWRJGXGCTVGZXWFPSKUDJQF9SGBYIAULTPN9ZPOXIQCBQKJVPJWRFUKRUJRACVFIHYUDMTSCSDDDIICQIMACFBKW9AVGTDRALOPQCZDXMWRUQKSAURXUFHFMCGVQCJLVDBCFFWY9TJVXJEFOEIC9ZIIBASFFEKHZQJWFSXPGFHSMHGINBSPBYCQEBFTBOLRYTMBUYFYHMJUBHESKFLBIWTKOQRKHUWNBQXMWALZPUAMQZMFOJSZIJZUZLQUD9QTBCZTRKWXBISZ9MWXGUDX9KUXYNAUWBISOMZSSYVQDJGKVEEWBKAQIR9KTVVGO99S9IFKUGCK9YTSVOECCRYJXKVEDWIZMCRBITLGKAVFFPHTVGTARYEOTCSW9HDYLNDNHEJZPIETFYMJQVJLVESHIB9DIYYXRUDUHHQNFBUDNAHJPNNNTK9KYNAHCOIAAOHEXAXDTPAJRHHYJIAGSRRMZZXKMENKDSZBRF9DKBSPGXJMLRGXODNYM9QGUKYPPBDQGWYJSROXVZWYFLFHCGUSABHMZVWMBJSWJ9XWRRAEEMCVZ9DFXAMIB9IGNTKYAU99XWOKTLPLFPGDLUDXFX9ZDMZXQGCQLPOF9WEFJKHASGSGRQXIJZQHCNLLSHEDFWZJ9S9OOOAXS9NYQXEBHCJOTNABB9HUCPLKWEGWRCCIFMNAIXNENIYTAMZOQJNXTRNH9ZTCONLWGLDZALIHRGHWDIEULRGJUOCCYCYLZBVSFFXMIWLCZKYY9FQBKMRXKPUD9KZHTQMYIQOMKDCFDXRZODPLP9KSDAXBNZBALFOWK9CRLANOOXHAJPXE9SYKHLDJODRZTWWYVUJNFPLSILPLWWXJTHMAXSKPNYOAUZH9FCORHHHOE9F9BWLOMUXLIQFNKILTHMWMAEWRVKKYSUMZQZOHDFYUSAQYMUZZWUCYBOLGGDY9EBYRKFGYSGKKK9BVEUVLLIYCEPELQATXXHVZIUVTOLCKZFNHWYSHNCFAIKULLRLCUEBPSBMGAI9UHXYQYSOYMZF9WVKPPEQPZJJOJNMJSTZXGOFPBJLCJSGDHK9FCVKWDWIIIIZQHOGQJOOQJVJYDWGORVMPQGMHAXUBAEZQ9CFKPNSQPIDLPTCUEZQCZFCSRMTDYVWQHNVBEZEOMKWUNSKLFIWIMMSDPFUCEHAXIOBNSPY9YIWJTDLQHZVQRIXQRAYEDBUILELUY9XARPM9FYOVLMROYSPDJRGYXZRGNZJGVPI9THUZOOSE9DHHPCPYZYYUW9HGRQAGZMDDPERKFAUHTOUBDLOBMAAH9PYTZKAWKSQJOSLXWEHFLTQQLOGGLTGRTUYHKJS9YUJIGEQLMMELZFZYEIYAHODKFXRAYHTTFEIAUTMPV9FHYHGGECIIPDIMCWPLJMMSSSECCGKYAWLPOKPABBFTGAMQCBVMX9AMPAXLXXIY9JPPVIQTHZSUQHLJSEYCQGLPCXLKTVCJQG9X99999999999999999999999999ISBUNDLE9999999999999999999DCGFKAD99D99999999F99999999RIOTKWHFSPYFBVIYWAFACLTXYWDRSGOAFFAWCBDVSPJBBOXID9FKSVH9XXDTKGEIJGLEVSLTZGUDUQVRY999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999ISBUNDLE99999999999999999999CGRSPC999CGRSPC999CGRSPC99999999999999999999999999999",
+        "WZHNLXRNTAMUQYVZDNPNUKVNAWKQOUWDSBE9BKJYJMHRNN9PPVZWUZHGAUCWHBSZAPLGVKAUUQOBYRYK9KBIUXUELMEPKYZGVYFSONPLE9WJZ9LCGQOOOPSWONKRKFXO9FDAFSZYJNYKNTJSGKJIIOSTITUTZWNMGWMAXYTSHVVAKBMRNMVSRBFDKSGGWBPUDKFU9QKNRXTNHGLQW9FXHIQIL9OFM9TILWFHEJXLADXOTEWVAJANQKSCHBMVUVNYCKDEHBZEN99FGWOQMYOBLPKU9B9SOHXWMPBKUAACDMTHAEKTSUU9PLYZWVUGUXAPLHSBTIKNYWQMQMSMEYSKWTQBFKVARXEQOXMKKYI9GBQSCVKPSDOYDLYPWSGSMXMNSJSKRQU9NQYVE9URBNDPBEKMASKFBIL99ZXJCUMRLOWFHZLWADGLVVYBLNMBSXZDURJRPPMBFRUSLCAMSHHBJGZXSTIZSWDLAYFTB9PDBKOLVZOOZUGWHCVQCZWJOUL9KSEUQXDLGTLATKUTNWCGQRBDEUQVBT9URM9PACXBLVYBNOJQKWVHKCCN9JCOTNKPWJSFZLRACNVTBAZCUXKHGSQTCBTEPXWSUSGITPDDVPRSWQPNZWYSOEGJIOAKPFW9JTYKJPCXOGDWUY9OPCWKUSANHZUQWJ9BXMSCBAVUEHOBJVXXLDHSJVRFMELAANV9J9GRIGURNSZGNNYMGKWAYCEYD9F9GENQAKVIEHCGZ9TZ9CUYASGECN9XBJEGXKRZEQTTNJXDGAGUY9VZCPGQIKZVVVOBPXOVCOY9QGPRACRRNAAUPCMZWT9OF9FDWVBMREDWSFWKQYXCBXCHNERLN9WGUXUQZHEAGB9GMCETBRGMBQQIDIFXIIDSBVZSMBSYZQWUQPPPAQNKAXLCSMLBCWUNMSMAKVWOENUMIGIVITDJW9VLIISXNTMPBDLGQ9LIMZAYZTJHADOXMYHNVSXQATPDXTISXXRRENBPUODTGTJWPJAADUHFCQVSCGNKXINAHSJMFRJTPKHFWCJJYKYRENCSUTUNWUFX9HIBBXUHXUETSJBOVQENTXGTYKCLWPQSSWNZUCUDIUQTIOLOBOFYKOMVFCYTMORXKQDHGTOISUZQTZLKKGE9HK9WRUOLKMKBAIAFKBVSQHCHJYEZFNSNMBZGTYTXNVUC9CQLBIDTFQDTTDTLHLFBOEPBBAPLXKDBYNZXMJAFYTQSHPBERZKMEGOSKPNXBTKCMFXSFQY9TQTCHRCWXHWCI9BLGOOMGWYOLOLLBWWKDJEFOYKAKKPXMYBFHJXIXVCTIBSHGJLIFQDVDHDNAMLYMQFEDKCDVVSLQMKOJDFDIYLRBSTPJMKYDYZDEM9GCIXCZTFHKYVWEYFHFWUHHLQKGBHOMNOEHADUZFLDZQWIIVOGTXJJYMHKIZLONRVXTROBHYTSADAACSDMMVNKHWOTXISXQILNYBZGVUNMWEXFNHDXXEKVOMJVQFABHBMIELNRTDYRQ9LHVIEXZCNVKGTERJ9EADPRHHTTTWXVOZOVWLGNA9UZCZOMVTEYDHYUCCJYTUWYSBWDGKMFOZHTUJI9JUVSHDHRJDSAKGDGJYWRSIZHWOXOEVNYUXYR9TTRWBJQFIVXWEPSSXBXVTCRN9NQRUPFQSDCKMQT9ZRDYFLDLVKBW99OWLOGVNKYNWGIUBEIUWGRBNCOACPMBTYXZME9L9UKWM9OAQJXBUK9BMBQCKZBTFPXBTPAY9KUNYDRNXILANVVMPLKESQMJOKMVHACTXWHIBSJBIRR9NHRQWMHRDUQLINPSV9YTRMPTDHVKHLNDNNWIUATKPQOOFFWGWQDBYPCVAWWFWHMYMLMYHCGRRSKQIDARB9JYCWBJJONRSCCCAHHSSBRFOKPXF9HZUYEREW9ZKBCXTWVHIUEYZLF9LGQYPKLMXIWEKZAQELLWYZTGKHZER9UQQRLNMTNXDPLCRDZJU9KLFIPWAMXXFOPFSVSJUGFWLNQRPTPEFIMGZXYLOJALTGABOXEA9T9OTBEIOPXUOKQTMPDUNKGUQKFCMQSYD9FQQTXUXOWXDETBFICDXICOHYOCTSUSQEGFICUTPKAIZRHVABNSG9OKRPJOLWEADP9DHPLZHIGQCWJWCDDXXWCEQHJDRL9MWXSNUNOEEYCNAPEFBUDJCSSUMQAVKYPLJMMSSSECCGKYAWLPOKPABBFTGAMQCBVMX9AMPAXLXXIY9JPPVIQTHZSUQHLJSEYCQGLPCXLKTVCJQG9999999999999999999999999999ISBUNDLE9999999999999999999DCGFKAD99E99999999F99999999RIOTKWHFSPYFBVIYWAFACLTXYWDRSGOAFFAWCBDVSPJBBOXID9FKSVH9XXDTKGEIJGLEVSLTZGUDUQVRY999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999ISBUNDLE99999999999999999999CGRSPC999CGRSPC999CGRSPC99999999999999999999999999999",
+        "9GICYQMMQRYRGGQLAWSDGPQSZNXZGLPFGJIQRQZZNCHRRMUCBLCR9VTWUEFQXXSYRRHZDQNYLSBMLSFSWGYFQSUHCQVWMBYLYUYNBOWRIQUGIB9KDRRNWHNWPP99YZPFDQMKHOYYNXSCATQADGPWANRPTTODPBD9PXPSI9YFFCFNZBKOYVAELG9HMYRXAYTPWAEESDRXLEVVKQWDXQCRATRLPSNYMQP9REKELSSXYJGDESIMTFDVLSTGDEWAHWHBVOPRIWDFSLSSBKGVMCARHFUXMXJXPSVPSHKPNXVXFWZXAFPTKKCVPL9XCPOEDGM9QGLDLENFTPDLCXCDOEVNIYMIFTKDYSHNJNOKMEYJGQULWSZO9GQFZIXENELGIVVXULPEHNMFMB9YPRJLDNRBAFALOZ9ZFAKVKOUFLIISDNFWCCQPNNMDHYBKGEGX9NOETCSYAACWJVMBYIVWKKQZKPBVNULIABDWFHCDA9MQADFXRLAGDOOGNISZT9TV9URFHXRQXONKAWKQHAOZIJYVPICVCLDJZPETHRNTBGSBEAWEZXOJFJKNZAXAFPCTBIOXKDBTLMGZTKFGRPAWYWGCDCEVEXKXCZ9UE9UOHXESGNA9WCANNBENSYZ9DKXQTPRVBCJJCDP9FGYXIV9QWLAIARXBON9KM9GXBRQW99FXXBFREATMPGZHDZXOOWVUYRUGFEBUWET9HFJVIJYUECDYWEHVZFPHS9BZNFBFTPGKNGCYGJPQNQDUDBMETZA9IKF9WR9WSVJQOTZGQVKMDUXUGRQVWIBBTQPYSEMCVTAZGDPSI9EQPGPSVWGVXSHGKMY9MBTDRMXORXPWKPFNSFBPXSKGVMCKWXYYGSHPEIYPRQH9NZZEIINIBBXXOECJZXNTSTTOSYABTEDAPGTBGMFFE9YINYCFGACKRPGPCIA9TADMH9LTLZMZSWPGBBPNCYTHFDTHLCWKPLCZNUSJLFENPQE9FRIWVONNQMVYKIQLGYL9HDIQPJYDQZBCGPFUKWVKWIQEIHMUW9AGNGTHFIG9TC99SAXCCPRPAFYGHQYREYSLWCCJMPEEHLBO9TVCQQXRKQURUBBAYZFXUAAZH9R9FNEMKKYJHLXASQGQKOWRMZYUBXJLVIPEUWHHEETADUJWJ9EOPAQSHHEMCVCMEI9S9XWNJWCZPCML9BQGTA9WMWUWSRGEGYINQWVF9NUQWWDRDQIIKSAIMVZMULATKXWJZMCLZGWKGQXZZMKLUHMSTMNLOXVTO9TFUGEYDQUQ9COVVKI9SAVPTXOVIPACUSUJZOXTFCKZPQBDAFLMUWQJAHNPTXSELMXVEX9YUPXRMRC9NLJLLMOZXVPLRSGHSVZXOXMHTDBNKWTBW9HPPSOYXINNDKEUFRNDVZWCKPFOBEJAAYDNGBONPFEGNKLIOTXEECHQO9XLFRYHYSEYPXGE9KQRPHRYACYDEVETQBTGSUBDBATWITCIJSYGCWIMGBIA9NTTHATSICNOLGNGUFFWFXXMDFZMKYARCO9GQCXWYUICXDBOOBPKZEGFGUVZQPQFZDOHTEHKDUWCSYYXKEZOLSKMEJBSHLBLWFFBOXJLUCXP9QEODKJ9JT9KEVNGZ9RDLGUIKKMMFRRRYNQDSIRD9WU9WHWOFYLUKPQQFQYSDFIGFVNZOPFY9BHLVNYVXWPNGCLWJDCGUJOJ9FXEZKMLOVNUESKMHBLQAVQ9AJCOBQLKHPBBQNPQHRMGY9ONCUAXQABUKEVRGMZROOIOQCSTKJIYSEGGTORMYWEMECVKUQOMGDASDCLCGTXWHAYYCNMNKUXOVQOYXVCXPSEMJATFAATPXTUXDQBLHXQDNTEKLNVETNCUKBWPDXFZXBPZOQFADCQHGYOPZORQT9DCBPKLCSRRYQZLXMXERUYTVUKTDMWISXTJV9ELJOXVCMKZEWNGZOTWUOC9HXGLPWUXYXKELQKSWCJUIUTFSD9XPXHDNQHGVX99EANS9EZQTNNHKVVOVGGMNEZWFL9IUANUMLTZCQZDRXZTLEESWXLQQNPNXTEDUWAPYEAAFRLYMHSWPDMQEKCJCZGJLAGTQLLJKPNQA9KTYQXNXATOGZTGVDJGGLDXBKGXPTPKFMXRYMKPGVG9XKUMTPNRQCHXLRZJGOTRUIHEFF9ZHHSZEGGLZPRBHAVJFRCHLTCCXHCPLJMMSSSECCGKYAWLPOKPABBFTGAMQCBVMX9AMPAXLXXIY9JPPVIQTHZSUQHLJSEYCQGLPCXLKTVCJQG9999999999999999999999999999ISBUNDLE9999999999999999999DCGFKAD99F99999999F99999999RIOTKWHFSPYFBVIYWAFACLTXYWDRSGOAFFAWCBDVSPJBBOXID9FKSVH9XXDTKGEIJGLEVSLTZGUDUQVRY999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999999ISBUNDLE99999999999999999999CGRSPC999CGRSPC999CGRSPC99999999999999999999999999999",
+    ];
+
+    fn test_bundle() -> Vec<Transaction> {
+        TEST_BUNDLE
+            .iter()
+            .map(|trytes| trytes.parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_is_bundle_with_inputs_of_every_security() {
+        let bundle = test_bundle();
+        assert_eq!(bundle[1].value, -1);
+        assert_ne!(bundle[1].address, bundle[2].address);
+        assert_eq!(bundle[2].value, -2);
+        assert_eq!(bundle[2].address, bundle[3].address);
+        assert_eq!(bundle[4].value, -3);
+        assert!(bundle[4..].iter().all(|tx| tx.address == bundle[4].address));
+        assert!(is_bundle(&bundle).unwrap());
+    }
+
+    #[test]
+    fn test_is_bundle_rejects_forged_fragment() {
+        for index in 1..TEST_BUNDLE.len() {
+            let mut bundle = test_bundle();
+            let forged = if bundle[index].signature_fragments.starts_with('A') {
+                "B"
+            } else {
+                "A"
+            };
+            bundle[index]
+                .signature_fragments
+                .replace_range(0..1, forged);
+            assert!(
+                !is_bundle(&bundle).unwrap(),
+                "fragment of transaction {}",
+                index
+            );
+        }
+    }
+
+    #[test]
+    fn test_is_bundle_ignores_hmac_on_outputs() {
+        let bundle = test_bundle();
+        assert!(!bundle[0].signature_fragments.starts_with(&"9".repeat(81)));
+
+        let mut bundle = Bundle::new(bundle);
+        HMAC::new("ANOTHER9KEY").add_hmac(&mut bundle).unwrap();
+        assert!(is_bundle(&bundle).unwrap());
+    }
+}